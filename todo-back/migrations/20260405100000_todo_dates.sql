@@ -0,0 +1,4 @@
+ALTER TABLE todos ADD COLUMN due_at TIMESTAMPTZ;
+ALTER TABLE todos ADD COLUMN start_at TIMESTAMPTZ;
+
+CREATE INDEX todos_workspace_id_due_at_idx ON todos (workspace_id, due_at);
@@ -0,0 +1,55 @@
+-- todos.text (weight A) と付与されたラベル名 (weight B) をまとめた検索用ベクトル
+ALTER TABLE todos ADD COLUMN search_vector TSVECTOR NOT NULL DEFAULT ''::tsvector;
+
+CREATE FUNCTION todo_label_search_vector(target_todo_id INTEGER) RETURNS TSVECTOR AS $$
+    SELECT setweight(to_tsvector('simple', coalesce(string_agg(labels.name, ' '), '')), 'B')
+    FROM todo_labels tl
+             INNER JOIN labels ON labels.id = tl.label_id
+    WHERE tl.todo_id = target_todo_id
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION todos_search_vector_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector := setweight(to_tsvector('simple', NEW.text), 'A')
+        || todo_label_search_vector(NEW.id);
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todos_search_vector_update
+    BEFORE INSERT OR UPDATE OF text ON todos
+    FOR EACH ROW EXECUTE FUNCTION todos_search_vector_trigger();
+
+CREATE FUNCTION todo_labels_search_vector_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    -- UPDATE OF text ではないので todos 側のトリガーは発火しない
+    UPDATE todos
+    SET search_vector = setweight(to_tsvector('simple', todos.text), 'A')
+        || todo_label_search_vector(todos.id)
+    WHERE todos.id = (CASE WHEN TG_OP = 'DELETE' THEN OLD.todo_id ELSE NEW.todo_id END);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todo_labels_search_vector_update
+    AFTER INSERT OR DELETE ON todo_labels
+    FOR EACH ROW EXECUTE FUNCTION todo_labels_search_vector_trigger();
+
+CREATE FUNCTION labels_search_vector_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE todos
+    SET search_vector = setweight(to_tsvector('simple', todos.text), 'A')
+        || todo_label_search_vector(todos.id)
+    WHERE todos.id IN (SELECT todo_id FROM todo_labels WHERE label_id = NEW.id);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER labels_search_vector_update
+    AFTER UPDATE OF name ON labels
+    FOR EACH ROW EXECUTE FUNCTION labels_search_vector_trigger();
+
+UPDATE todos
+SET search_vector = setweight(to_tsvector('simple', text), 'A') || todo_label_search_vector(id);
+
+CREATE INDEX todos_search_vector_idx ON todos USING GIN (search_vector);
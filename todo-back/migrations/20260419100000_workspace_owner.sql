@@ -0,0 +1,7 @@
+ALTER TABLE workspaces ADD COLUMN owner_id INTEGER REFERENCES users (id);
+
+-- 作成者の記録がないため、既存workspaceは最も古いメンバーをオーナーとする
+UPDATE workspaces SET owner_id = (
+    SELECT min(wu.user_id) FROM workspace_users wu
+    WHERE wu.workspace_id = workspaces.id
+);
@@ -0,0 +1,13 @@
+CREATE TYPE workspace_role AS ENUM ('owner', 'admin', 'editor', 'viewer');
+
+ALTER TABLE workspace_users ADD COLUMN role workspace_role NOT NULL DEFAULT 'editor';
+
+-- オーナーはworkspace_users.roleで管理する
+UPDATE workspace_users SET role = 'owner'
+FROM workspaces
+WHERE workspaces.id = workspace_users.workspace_id
+AND workspaces.owner_id = workspace_users.user_id;
+
+ALTER TABLE workspaces DROP COLUMN owner_id;
+
+CREATE UNIQUE INDEX workspace_users_owner_idx ON workspace_users (workspace_id) WHERE role = 'owner';
@@ -0,0 +1,18 @@
+CREATE TYPE invitation_status AS ENUM ('pending', 'accepted', 'declined');
+
+CREATE TABLE workspace_invitations
+(
+    id           SERIAL PRIMARY KEY,
+    workspace_id INTEGER           NOT NULL REFERENCES workspaces (id),
+    email        TEXT              NOT NULL,
+    role         workspace_role    NOT NULL DEFAULT 'editor',
+    token        TEXT              NOT NULL UNIQUE,
+    status       invitation_status NOT NULL DEFAULT 'pending',
+    invited_by   INTEGER REFERENCES users (id),
+    expires_at   TIMESTAMPTZ       NOT NULL,
+    created_at   TIMESTAMPTZ       NOT NULL DEFAULT now()
+);
+
+-- 同じworkspaceへの未回答の招待はメールアドレスごとに1件まで
+CREATE UNIQUE INDEX workspace_invitations_pending_idx ON workspace_invitations (workspace_id, lower(email)) WHERE status = 'pending';
+CREATE INDEX workspace_invitations_email_idx ON workspace_invitations (lower(email));
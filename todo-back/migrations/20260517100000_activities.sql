@@ -0,0 +1,29 @@
+CREATE TYPE activity_target AS ENUM ('todo', 'label', 'workspace');
+CREATE TYPE activity_action AS ENUM ('create', 'update', 'delete');
+
+-- workspaceやユーザーが削除されても履歴は残すため外部キーは張らない
+CREATE TABLE activities
+(
+    id           BIGSERIAL PRIMARY KEY,
+    workspace_id INTEGER,
+    actor_id     INTEGER,
+    target       activity_target NOT NULL,
+    target_id    INTEGER         NOT NULL,
+    action       activity_action NOT NULL,
+    before       JSONB,
+    after        JSONB,
+    created_at   TIMESTAMPTZ     NOT NULL DEFAULT now()
+);
+
+CREATE INDEX activities_workspace_id_idx ON activities (workspace_id, id DESC);
+
+-- 追記のみ許可する
+CREATE FUNCTION reject_activity_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'activities are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER activities_append_only
+    BEFORE UPDATE OR DELETE ON activities
+    FOR EACH ROW EXECUTE FUNCTION reject_activity_change();
@@ -0,0 +1,4 @@
+-- 削除したtodoは保持期間が過ぎるまでゴミ箱に残す
+ALTER TABLE todos ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX todos_trash_idx ON todos (workspace_id, deleted_at) WHERE deleted_at IS NOT NULL;
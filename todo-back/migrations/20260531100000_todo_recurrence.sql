@@ -0,0 +1,6 @@
+-- 繰り返しtodo、series_idは最初の回のidで同じ系列の回をつなぐ
+-- 最初の回がゴミ箱から完全に削除されても系列は残るので外部キーにはしない
+ALTER TABLE todos ADD COLUMN recurrence JSONB;
+ALTER TABLE todos ADD COLUMN series_id INTEGER;
+
+CREATE INDEX todos_series_idx ON todos (series_id, id) WHERE series_id IS NOT NULL;
@@ -0,0 +1,6 @@
+-- カレンダーアプリが購読するフィードのURL、トークンはハッシュだけを保存する
+CREATE TABLE calendar_feeds (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
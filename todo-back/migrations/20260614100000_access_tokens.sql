@@ -0,0 +1,15 @@
+-- スクリプトやCIがJWTの代わりに使う個人用アクセストークン、トークンはハッシュだけを保存する
+CREATE TABLE access_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- 一覧でトークンを見分けるための先頭数文字
+    token_hint TEXT NOT NULL,
+    scopes JSONB NOT NULL,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX access_tokens_user_idx ON access_tokens (user_id);
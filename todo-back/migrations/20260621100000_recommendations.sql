@@ -0,0 +1,17 @@
+CREATE TYPE recommendation_status AS ENUM ('pending', 'accepted', 'rejected');
+
+-- AIの提案。未回答のものは短時間で期限切れになり、却下されたものは次の提案で避ける
+CREATE TABLE recommendations
+(
+    id           SERIAL PRIMARY KEY,
+    workspace_id INTEGER               NOT NULL REFERENCES workspaces (id) ON DELETE CASCADE,
+    user_id      INTEGER               NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    text         TEXT                  NOT NULL,
+    status       recommendation_status NOT NULL DEFAULT 'pending',
+    todo_id      INTEGER REFERENCES todos (id) ON DELETE SET NULL,
+    expires_at   TIMESTAMPTZ           NOT NULL,
+    decided_at   TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ           NOT NULL DEFAULT now()
+);
+
+CREATE INDEX recommendations_workspace_idx ON recommendations (workspace_id, status);
@@ -0,0 +1,4 @@
+-- 優先度、未設定のtodoはNULLのまま
+CREATE TYPE todo_priority AS ENUM ('low', 'medium', 'high');
+
+ALTER TABLE todos ADD COLUMN priority todo_priority;
@@ -0,0 +1,33 @@
+-- workspaceで共有するラベル、NULLは従来通りユーザー個人のラベル
+ALTER TABLE labels ADD COLUMN workspace_id INTEGER REFERENCES workspaces (id) ON DELETE CASCADE;
+
+ALTER TABLE labels DROP CONSTRAINT labels_user_id_name_key;
+CREATE UNIQUE INDEX labels_user_id_name_key ON labels (user_id, name) WHERE workspace_id IS NULL;
+CREATE UNIQUE INDEX labels_workspace_id_name_key ON labels (workspace_id, name) WHERE workspace_id IS NOT NULL;
+
+-- 共有workspaceのtodoに付いた個人ラベルを、同名の共有ラベルに置き換える
+INSERT INTO labels (name, user_id, workspace_id)
+SELECT DISTINCT ON (t.workspace_id, l.name) l.name, l.user_id, t.workspace_id
+FROM todo_labels tl
+JOIN todos t ON t.id = tl.todo_id
+JOIN labels l ON l.id = tl.label_id
+JOIN workspaces w ON w.id = t.workspace_id
+WHERE w.is_personal = false
+  AND l.workspace_id IS NULL
+ORDER BY t.workspace_id, l.name, l.id;
+
+INSERT INTO todo_labels (todo_id, label_id)
+SELECT tl.todo_id, shared.id
+FROM todo_labels tl
+JOIN todos t ON t.id = tl.todo_id
+JOIN labels l ON l.id = tl.label_id
+JOIN labels shared ON shared.workspace_id = t.workspace_id AND shared.name = l.name
+WHERE l.workspace_id IS NULL
+ON CONFLICT DO NOTHING;
+
+DELETE FROM todo_labels tl
+USING todos t, labels l
+WHERE t.id = tl.todo_id
+  AND l.id = tl.label_id
+  AND l.workspace_id IS NULL
+  AND EXISTS (SELECT 1 FROM workspaces w WHERE w.id = t.workspace_id AND w.is_personal = false);
@@ -0,0 +1,3 @@
+-- ラベルの色(#rrggbb)とアイコン、未設定はNULL
+ALTER TABLE labels ADD COLUMN color TEXT;
+ALTER TABLE labels ADD COLUMN icon TEXT;
@@ -0,0 +1,25 @@
+-- 手動で並べ替えた順番、同じ親を持つtodo同士をバイト順で比較する
+ALTER TABLE todos ADD COLUMN position TEXT COLLATE "C";
+
+-- 既存のtodoは今までの表示順(トップレベルは新しい順、サブタスクは古い順)で並べる
+-- 桁数は兄弟の数に合わせ、lpadで切り詰めないようにする
+UPDATE todos
+SET position = ranked.position
+FROM (
+    SELECT id,
+           'i' || lpad(
+               (row_number() OVER siblings)::TEXT,
+               greatest(5, length((count(*) OVER (PARTITION BY workspace_id, parent_id))::TEXT)),
+               '0'
+           ) || 'i' AS position
+    FROM todos
+    WINDOW siblings AS (
+        PARTITION BY workspace_id, parent_id
+        ORDER BY CASE WHEN parent_id IS NULL THEN -id ELSE id END
+    )
+) ranked
+WHERE ranked.id = todos.id;
+
+ALTER TABLE todos ALTER COLUMN position SET NOT NULL;
+
+CREATE INDEX todos_workspace_id_parent_id_position_idx ON todos (workspace_id, parent_id, position);
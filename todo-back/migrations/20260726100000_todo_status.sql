@@ -0,0 +1,32 @@
+-- workspaceごとのボードの列、終了扱いの列にあるtodoは完了とみなす
+CREATE TABLE statuses
+(
+    id           SERIAL PRIMARY KEY,
+    workspace_id INTEGER NOT NULL REFERENCES workspaces (id) ON DELETE CASCADE,
+    name         TEXT    NOT NULL,
+    position     INTEGER NOT NULL,
+    wip_limit    INTEGER CHECK (wip_limit > 0),
+    is_terminal  BOOLEAN NOT NULL DEFAULT false,
+    UNIQUE (workspace_id, name)
+);
+
+INSERT INTO statuses (workspace_id, name, position, is_terminal)
+SELECT workspaces.id, defaults.name, defaults.position, defaults.is_terminal
+FROM workspaces
+CROSS JOIN (VALUES ('backlog', 0, false), ('doing', 1, false), ('review', 2, false), ('done', 3, true))
+    AS defaults (name, position, is_terminal);
+
+-- 既存のtodoは完了状態に合わせて最初の列か完了の列に入れる
+ALTER TABLE todos ADD COLUMN status_id INTEGER REFERENCES statuses (id);
+
+UPDATE todos
+SET status_id = statuses.id
+FROM statuses
+WHERE statuses.workspace_id = todos.workspace_id
+  AND statuses.name = CASE WHEN todos.completed THEN 'done' ELSE 'backlog' END;
+
+ALTER TABLE todos ALTER COLUMN status_id SET NOT NULL;
+
+CREATE INDEX todos_status_id_idx ON todos (status_id);
+
+ALTER TYPE activity_target ADD VALUE 'status';
@@ -0,0 +1,12 @@
+-- todoの担当者、workspaceのメンバーに限る
+-- メンバーから外れるとそのworkspaceでの割り当ても消える
+CREATE TABLE todo_assignees
+(
+    todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+    workspace_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    PRIMARY KEY (todo_id, user_id),
+    FOREIGN KEY (workspace_id, user_id) REFERENCES workspace_users (workspace_id, user_id) ON DELETE CASCADE
+);
+
+CREATE INDEX todo_assignees_user_id_idx ON todo_assignees (user_id);
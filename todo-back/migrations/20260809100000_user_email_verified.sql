@@ -0,0 +1,2 @@
+-- 本人確認済みのemailだけで招待を受けられる、確認はIDトークンのemail_verifiedで行う
+ALTER TABLE users ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT false;
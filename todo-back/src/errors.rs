@@ -0,0 +1,170 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use validator::{ValidationErrors, ValidationErrorsKind};
+use crate::repositories::RepositoryError;
+
+/// A failed validation rule of a request body field, `__all__` for rules spanning several fields.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: Option<String>,
+}
+
+impl FieldError {
+    pub fn new(field: &str, code: &str, message: Option<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+/// Flattens what `validate()` found into one entry per failed rule, ordered by field.
+/// Rules of nested structs are reported under their path, e.g. `recurrence.interval`.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut details = vec![];
+    collect_field_errors(errors, "", &mut details);
+    details.sort_by(|a, b| a.field.cmp(&b.field));
+    details
+}
+
+fn collect_field_errors(errors: &ValidationErrors, prefix: &str, details: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = format!("{}{}", prefix, field);
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                details.extend(errors.iter().map(|error| FieldError {
+                    field: path.clone(),
+                    code: error.code.to_string(),
+                    message: error.message.as_ref().map(|message| message.to_string()),
+                }));
+            }
+            ValidationErrorsKind::Struct(errors) => collect_field_errors(errors, &format!("{}.", path), details),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(errors, &format!("{}[{}].", path, index), details);
+                }
+            }
+        }
+    }
+}
+
+/// What every failing request answers with.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+}
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Validation failed")]
+    Validation(Vec<FieldError>),
+    #[error("Authentication required")]
+    Unauthorized,
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Gone(String),
+    #[error("Internal server error")]
+    Internal(anyhow::Error),
+}
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) | AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Gone(_) => StatusCode::GONE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Gone(_) => "gone",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// Turns a missing record into `error`, for lookups of ids the client put in the body.
+    pub fn not_found_as(self, error: AppError) -> AppError {
+        match self {
+            AppError::NotFound(_) => error,
+            other => other,
+        }
+    }
+}
+
+// Only repository outcomes the client can act on get their own status, anything else is our fault.
+impl From<anyhow::Error> for AppError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast_ref::<RepositoryError>() {
+            Some(RepositoryError::NotFound(_)) => AppError::NotFound(e.to_string()),
+            Some(RepositoryError::Duplicate(_))
+            | Some(RepositoryError::InUse(_))
+            | Some(RepositoryError::WipLimit(_))
+            | Some(RepositoryError::LastStatus(_)) => AppError::Conflict(e.to_string()),
+            Some(RepositoryError::UnknownLabel(_)) => {
+                AppError::Validation(vec![FieldError::new("label_ids", "unknown_label", Some(e.to_string()))])
+            }
+            Some(RepositoryError::UnknownStatus(_)) => {
+                AppError::Validation(vec![FieldError::new("status_id", "unknown_status", Some(e.to_string()))])
+            }
+            Some(RepositoryError::UnknownAssignee(_)) => {
+                AppError::Validation(vec![FieldError::new("assignee_ids", "unknown_assignee", Some(e.to_string()))])
+            }
+            Some(RepositoryError::StartAfterDue(_)) => {
+                AppError::Validation(vec![FieldError::new("start_at", "start_after_due", Some(e.to_string()))])
+            }
+            _ => AppError::Internal(e),
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::Validation(field_errors(&errors))
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        if let AppError::Internal(e) = &self {
+            tracing::error!("{:?}", e);
+        }
+        let body = ErrorBody {
+            code: self.code().to_string(),
+            message: self.to_string(),
+            details: match self {
+                AppError::Validation(ref details) => details.clone(),
+                _ => vec![],
+            },
+        };
+        (self.status(), Json(body)).into_response()
+    }
+}
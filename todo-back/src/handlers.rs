@@ -2,14 +2,23 @@ pub mod label;
 pub mod workspace;
 pub mod todo;
 pub mod user;
+pub mod search;
+pub mod invitation;
+pub mod activity;
+pub mod event;
+pub mod calendar;
+pub mod transfer;
+pub mod access_token;
+pub mod status;
 
 use axum::{
-    extract::{FromRequest, Request},
-    http::StatusCode,
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::request::Parts,
     BoxError, Json,
 };
 use serde::de::DeserializeOwned;
 use validator::Validate;
+use crate::errors::AppError;
 
 #[derive(Debug)]
 pub struct ValidatedJson<T>(T);
@@ -19,18 +28,95 @@ where
     T: DeserializeOwned + Validate,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = AppError;
 
     async fn from_request(req: Request, state: &S
     ) -> Result<Self, Self::Rejection> {
         let Json(value) = Json::<T>::from_request(req, state).await.map_err(|rejection| {
-            let message = format!("Json parse error: [{}]", rejection);
-            (StatusCode::BAD_REQUEST, message)
-        })?;
-        value.validate().map_err(|rejection| {
-            let message = format!("Validation error: [{}]", rejection).replace('\n', ", ");
-            (StatusCode::BAD_REQUEST, message)
+            AppError::BadRequest(format!("Json parse error: [{}]", rejection))
         })?;
+        value.validate()?;
         Ok(ValidatedJson(value))
     }
+}
+
+#[derive(Debug)]
+pub struct ValidatedQuery<T>(T);
+
+impl <T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state).await.map_err(|rejection| {
+            AppError::BadRequest(format!("Query parse error: [{}]", rejection))
+        })?;
+        value.validate()?;
+        Ok(ValidatedQuery(value))
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use std::sync::Arc;
+    use axum::Router;
+    use crate::{
+        create_app,
+        repositories::{
+            activity::test_utils::ActivityRepositoryForMemory,
+            event::test_utils::EventRepositoryForMemory,
+            label::test_utils::LabelRepositoryForMemory,
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            todo::test_utils::TodoRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+        services::{
+            llm::{LlmProvider, MockLlm},
+            oidc::OidcVerifier,
+        },
+    };
+
+    /// The in-memory repositories a test app runs on. Anything a test doesn't
+    /// set starts out empty.
+    pub struct TestApp {
+        pub label_repository: LabelRepositoryForMemory,
+        pub workspace_repository: WorkspaceRepositoryForMemory,
+        pub todo_repository: TodoRepositoryForMemory,
+        pub user_repository: UserRepositoryForMemory,
+        pub activity_repository: ActivityRepositoryForMemory,
+        pub event_repository: EventRepositoryForMemory,
+        pub llm: Arc<dyn LlmProvider>,
+    }
+
+    impl Default for TestApp {
+        fn default() -> Self {
+            TestApp {
+                label_repository: LabelRepositoryForMemory::new(),
+                workspace_repository: WorkspaceRepositoryForMemory::new(vec![]),
+                todo_repository: TodoRepositoryForMemory::new(vec![]),
+                user_repository: UserRepositoryForMemory::new(),
+                activity_repository: ActivityRepositoryForMemory::new(),
+                event_repository: EventRepositoryForMemory::new(),
+                llm: Arc::new(MockLlm::default()),
+            }
+        }
+    }
+
+    impl TestApp {
+        pub fn build(self) -> Router {
+            create_app(
+                self.label_repository,
+                self.workspace_repository,
+                self.todo_repository,
+                self.user_repository,
+                self.activity_repository,
+                self.event_repository,
+                OidcVerifier::default(),
+                self.llm,
+            )
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,185 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    AppState,
+    errors::AppError,
+    middlewares::auth::AuthenticatedUser,
+    models::access_token::CreateAccessToken,
+};
+use super::ValidatedJson;
+
+pub async fn all_access_tokens(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let access_tokens = state.user_repository
+        .all_access_tokens(user.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(access_tokens)))
+}
+
+// a leaked token must not be able to outlive its own revocation by minting others
+pub async fn create_access_token(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateAccessToken>,
+) -> Result<impl IntoResponse, AppError> {
+    if auth_user.access_token_id.is_some() {
+        return Err(AppError::Forbidden("Access tokens can not create other tokens".to_string()));
+    }
+
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let access_token = state.user_repository
+        .create_access_token(user.id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(access_token)))
+}
+
+pub async fn revoke_access_token(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(token_id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    state.user_repository
+        .revoke_access_token(user.id, token_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        errors::ErrorBody,
+        models::{
+            access_token::AccessToken,
+            user::CreateUser,
+        },
+        repositories::user::test_utils::UserRepositoryForMemory,
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        response::Response,
+    };
+    use tower::ServiceExt;
+    use crate::repositories::user::UserRepository;
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req_with_json(method: Method, path: &str, json_body: String) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(json_body))
+            .unwrap()
+    }
+
+    fn build_req_with_token(method: Method, path: &str, token: &str, json_body: String) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(json_body))
+            .unwrap()
+    }
+
+    async fn res_to_string(res: Response) -> String {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_authenticate_with_access_token() {
+        let user_repository = UserRepositoryForMemory::new();
+        user_repository
+            .create(CreateUser::new(TEST_SUB.to_string(), "test_user".to_string(), "test@example.com".to_string()))
+            .await
+            .unwrap();
+        let app = TestApp {
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        // tokens can't already be expired
+        let res = app.clone().oneshot(build_req_with_json(
+            Method::POST,
+            "/users/me/tokens",
+            r#"{"name": "ci", "expires_at": "2000-01-01T00:00:00Z"}"#.to_string(),
+        )).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let body: ErrorBody = serde_json::from_str(&res_to_string(res).await).unwrap();
+        assert_eq!("expires_in_past", body.details[0].code);
+
+        let res = app.clone().oneshot(build_req_with_json(
+            Method::POST,
+            "/users/me/tokens",
+            r#"{"name": "read only"}"#.to_string(),
+        )).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let read_only: AccessToken = serde_json::from_str(&res_to_string(res).await).unwrap();
+        let read_only_token = read_only.token.clone().unwrap();
+
+        let res = app.clone().oneshot(build_req_with_json(
+            Method::POST,
+            "/users/me/tokens",
+            r#"{"name": "ci", "scopes": ["read", "write"]}"#.to_string(),
+        )).await.unwrap();
+        let writer: AccessToken = serde_json::from_str(&res_to_string(res).await).unwrap();
+        let writer_token = writer.token.clone().unwrap();
+
+        let res = app.clone().oneshot(build_req_with_token(Method::GET, "/users/me", &read_only_token, String::new())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert!(res_to_string(res).await.contains(TEST_SUB));
+
+        let label = r#"{"name": "scripted"}"#.to_string();
+        let res = app.clone().oneshot(build_req_with_token(Method::POST, "/labels", &read_only_token, label.clone())).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let res = app.clone().oneshot(build_req_with_token(Method::POST, "/labels", &writer_token, label)).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+
+        // tokens can't mint more tokens
+        let res = app.clone().oneshot(build_req_with_token(
+            Method::POST,
+            "/users/me/tokens",
+            &writer_token,
+            r#"{"name": "another"}"#.to_string(),
+        )).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        // the list never shows the secret, but tells when a token was last used
+        let res = app.clone().oneshot(build_req_with_json(Method::GET, "/users/me/tokens", String::new())).await.unwrap();
+        let listed: Vec<AccessToken> = serde_json::from_str(&res_to_string(res).await).unwrap();
+        assert_eq!(vec![read_only.id, writer.id], listed.iter().map(|access_token| access_token.id).collect::<Vec<_>>());
+        assert!(listed.iter().all(|access_token| access_token.token.is_none() && access_token.last_used_at.is_some()));
+
+        let path = format!("/users/me/tokens/{}", writer.id);
+        let res = app.clone().oneshot(build_req_with_json(Method::DELETE, &path, String::new())).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let res = app.oneshot(build_req_with_token(Method::GET, "/users/me", &writer_token, String::new())).await.unwrap();
+        assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+    }
+}
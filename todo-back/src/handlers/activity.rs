@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    AppState,
+    errors::AppError,
+    middlewares::workspace::WorkspaceAccess,
+    models::activity::ActivityQuery,
+};
+
+pub async fn all_activity(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let page = state.activity_repository
+        .all_by_workspace(access.workspace_id, query)
+        .await?;
+
+    Ok((StatusCode::OK, Json(page)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        models::{
+            activity::{ActivityAction, ActivityPage, ActivityTarget, NewActivity},
+            todo::TodoEntity,
+            user::{CreateUser, User},
+            workspace::CreateWorkspace,
+        },
+        repositories::{
+            activity::test_utils::ActivityRepositoryForMemory,
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+        response::Response,
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{user::UserRepository, workspace::WorkspaceRepository};
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn res_to_page(res: Response) -> ActivityPage {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).expect("cannot convert ActivityPage instance")
+    }
+
+    #[tokio::test]
+    async fn should_page_activity() {
+        let user_repository = UserRepositoryForMemory::new();
+        user_repository
+            .create(CreateUser::new(TEST_SUB.to_string(), "test_user".to_string(), "test@example.com".to_string()))
+            .await
+            .unwrap();
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+            User::new(2, "auth0|other_sub".to_string(), Some("other_user".to_string()), Some("other@example.com".to_string())),
+        ]);
+        for (user_id, name) in [(1, "mine"), (2, "others")] {
+            workspace_repository
+                .create(user_id, CreateWorkspace::new(name.to_string(), false, vec![]))
+                .await
+                .unwrap();
+        }
+        let activity_repository = ActivityRepositoryForMemory::new();
+        let todo = TodoEntity::new(1, "audited".to_string(), vec![], 1, 1);
+        let done = TodoEntity { completed: true, ..todo.clone() };
+        for (workspace_id, before, after) in [
+            (1, None, Some(&todo)),
+            (1, Some(&todo), Some(&done)),
+            (2, None, Some(&todo)),
+            (1, Some(&done), None),
+        ] {
+            activity_repository.record(NewActivity::new(Some(workspace_id), 1, ActivityTarget::Todo, todo.id, before, after));
+        }
+        let app = TestApp {
+            workspace_repository,
+            user_repository,
+            activity_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/1/activity?limit=2")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let page = res_to_page(res).await;
+        assert_eq!(
+            vec![ActivityAction::Delete, ActivityAction::Update],
+            page.items.iter().map(|activity| activity.action).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(2), page.next_cursor);
+
+        let path = format!("/workspaces/1/activity?limit=2&cursor={}", page.next_cursor.unwrap());
+        let page = res_to_page(app.clone().oneshot(build_req_with_empty(Method::GET, &path)).await.unwrap()).await;
+        assert_eq!(vec![1], page.items.iter().map(|activity| activity.id).collect::<Vec<_>>());
+        assert_eq!(None, page.next_cursor);
+
+        // not a member of the other workspace
+        let res = app.oneshot(build_req_with_empty(Method::GET, "/workspaces/2/activity")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+}
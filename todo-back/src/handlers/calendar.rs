@@ -0,0 +1,276 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+
+use crate::{
+    AppState,
+    errors::AppError,
+    middlewares::{auth::AuthenticatedUser, workspace::WorkspaceAccess},
+    models::{
+        calendar::CalendarQuery,
+        todo::{TodoCursor, TodoEntity, TodoQuery, TodoSort, MAX_PAGE_SIZE},
+    },
+    services::calendar,
+};
+
+const ICS_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+const FEED_NAME: &str = "Todos";
+
+// calendars get the whole list, so every page is collected
+async fn all_todos(state: &AppState, workspace_id: i32) -> Result<Vec<TodoEntity>, AppError> {
+    let mut query = TodoQuery {
+        sort: TodoSort::DueAt,
+        limit: Some(MAX_PAGE_SIZE),
+        ..Default::default()
+    };
+    let mut todos = vec![];
+    loop {
+        let page = state.todo_repository
+            .all_by_workspace(workspace_id, query.clone())
+            .await?;
+        todos.extend(page.items);
+        match page.next_cursor {
+            Some(cursor) => query.cursor = Some(TodoCursor::decode(&cursor)?),
+            None => return Ok(todos),
+        }
+    }
+}
+
+pub async fn export_calendar(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let workspace = state.workspace_repository
+        .find(access.workspace_id)
+        .await?;
+    let todos = all_todos(&state, access.workspace_id).await?;
+
+    let body = calendar::render(&workspace.name, &todos, query.component, Utc::now());
+    let headers = [
+        (header::CONTENT_TYPE, ICS_CONTENT_TYPE.to_string()),
+        (header::CONTENT_DISPOSITION, format!("attachment; filename=\"workspace-{}.ics\"", workspace.id)),
+    ];
+    Ok((StatusCode::OK, headers, body))
+}
+
+// calendar clients can't sign in, the secret token in the path stands in for the bearer token
+pub async fn calendar_feed(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_calendar_token(token)
+        .await?;
+
+    let workspaces = state.workspace_repository
+        .all_by_user(user.id)
+        .await?;
+    let mut todos = vec![];
+    for workspace in workspaces {
+        todos.extend(all_todos(&state, workspace.id).await?);
+    }
+
+    let body = calendar::render(FEED_NAME, &todos, query.component, Utc::now());
+    Ok((StatusCode::OK, [(header::CONTENT_TYPE, ICS_CONTENT_TYPE)], body))
+}
+
+pub async fn find_calendar_feed(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let feed = state.user_repository
+        .find_calendar_feed(user.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(feed)))
+}
+
+pub async fn create_calendar_feed(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let feed = state.user_repository
+        .create_calendar_feed(user.id)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(feed)))
+}
+
+pub async fn revoke_calendar_feed(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+) -> Result<StatusCode, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    state.user_repository
+        .revoke_calendar_feed(user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        models::{
+            calendar::CalendarFeed,
+            label::Label,
+            todo::{CreateTodo, UpdateTodo},
+            user::{CreateUser, User},
+            workspace::CreateWorkspace,
+        },
+        repositories::{
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            todo::test_utils::TodoRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        response::Response,
+        Router,
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{todo::TodoRepository, user::UserRepository, workspace::WorkspaceRepository};
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn res_to_string(res: Response) -> String {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    // workspace 1 holds a labelled todo with a subtask, a completed one and one without dates
+    async fn setup() -> Router {
+        let user_repository = UserRepositoryForMemory::new();
+        user_repository
+            .create(CreateUser::new(TEST_SUB.to_string(), "test_user".to_string(), "test@example.com".to_string()))
+            .await
+            .unwrap();
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+        ]);
+        workspace_repository
+            .create(1, CreateWorkspace::new("home, sweet home".to_string(), false, vec![]))
+            .await
+            .unwrap();
+
+        let todo_repository = TodoRepositoryForMemory::new(vec![Label::new(1, "errands".to_string(), 1)]);
+        let mut payload = CreateTodo::new("buy milk; and eggs".to_string(), vec![1]);
+        payload.start_at = Some("2026-06-01T08:00:00Z".parse().unwrap());
+        payload.due_at = Some("2026-06-01T09:00:00Z".parse().unwrap());
+        let parent = todo_repository.create(1, 1, payload).await.unwrap();
+        let mut payload = CreateTodo::new("find the list".to_string(), vec![]);
+        payload.parent_id = Some(parent.id);
+        todo_repository.create(1, 1, payload).await.unwrap();
+        let mut payload = CreateTodo::new("pay rent".to_string(), vec![]);
+        payload.due_at = Some("2026-06-02T00:00:00Z".parse().unwrap());
+        let paid = todo_repository.create(1, 1, payload).await.unwrap();
+        todo_repository
+            .update(paid.id, 1, UpdateTodo { completed: Some(true), ..Default::default() })
+            .await
+            .unwrap();
+        // nothing in the text breaks out of its content line
+        todo_repository.create(1, 1, CreateTodo::new("someday\rEND:VTODO\r\n\u{7}again".to_string(), vec![])).await.unwrap();
+
+        TestApp {
+            workspace_repository,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+    }
+
+    #[tokio::test]
+    async fn should_export_workspace_calendar() {
+        let app = setup().await;
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/1/calendar.ics")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!("text/calendar; charset=utf-8", res.headers()[header::CONTENT_TYPE]);
+        let body = res_to_string(res).await;
+        assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(body.ends_with("END:VCALENDAR\r\n"));
+        assert!(body.contains("X-WR-CALNAME:home\\, sweet home\r\n"));
+        assert_eq!(4, body.matches("BEGIN:VTODO").count());
+        for line in [
+            "UID:todo-1@todo-back",
+            "SUMMARY:buy milk\\; and eggs",
+            "DTSTART:20260601T080000Z",
+            "DUE:20260601T090000Z",
+            "CATEGORIES:errands",
+            "RELATED-TO:todo-1@todo-back",
+            "STATUS:COMPLETED",
+            "STATUS:NEEDS-ACTION",
+            "SUMMARY:someday\\nEND:VTODO\\nagain",
+        ] {
+            assert!(body.contains(&format!("{}\r\n", line)), "missing {} in {}", line, body);
+        }
+        assert!(!body.replace("\r\n", "").contains(|c: char| c.is_control()));
+
+        // only todos with a due date become events
+        let res = app.oneshot(build_req_with_empty(Method::GET, "/workspaces/1/calendar.ics?component=event")).await.unwrap();
+        let body = res_to_string(res).await;
+        assert_eq!(0, body.matches("BEGIN:VTODO").count());
+        assert_eq!(2, body.matches("BEGIN:VEVENT").count());
+        assert!(body.contains("DTEND:20260601T090000Z\r\n"));
+    }
+
+    #[tokio::test]
+    async fn should_serve_calendar_feed_by_token() {
+        let app = setup().await;
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/users/me/calendar-feed")).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::POST, "/users/me/calendar-feed")).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let feed: CalendarFeed = serde_json::from_str(&res_to_string(res).await).unwrap();
+        let path = format!("/feeds/{}/calendar.ics", feed.token.clone().unwrap());
+
+        // the feed is fetched without signing in
+        let req = Request::builder().uri(&path).body(Body::empty()).unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert!(res_to_string(res).await.contains("SUMMARY:pay rent\r\n"));
+
+        // the token is only handed out once
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/users/me/calendar-feed")).await.unwrap();
+        let found: CalendarFeed = serde_json::from_str(&res_to_string(res).await).unwrap();
+        assert_eq!(CalendarFeed { token: None, ..feed }, found);
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, "/users/me/calendar-feed")).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let req = Request::builder().uri(&path).body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+}
@@ -0,0 +1,140 @@
+use std::convert::Infallible;
+use axum::{
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+use crate::{
+    AppState,
+    errors::AppError,
+    middlewares::auth::AuthenticatedUser,
+};
+
+pub async fn stream_events(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let is_member = state.workspace_repository
+        .is_member(workspace_id, user.id)
+        .await?;
+    if !is_member {
+        return Err(AppError::Forbidden("Not a member of this workspace".to_string()));
+    }
+
+    let events = BroadcastStream::new(state.event_repository.subscribe())
+        // a subscriber falling behind skips what it missed, clients refetch on every event anyway
+        .filter_map(|event| event.ok())
+        .filter(move |event| event.workspace_id == workspace_id)
+        .take_while(move |event| !event.removes(user.id))
+        .map(|event| {
+            let sse = Event::default()
+                .event(event.target.as_str())
+                .json_data(&event)
+                .unwrap_or_default();
+            Ok(sse)
+        });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        models::{
+            activity::ActivityAction,
+            event::{EventTarget, WorkspaceEvent},
+            user::{CreateUser, User},
+            workspace::CreateWorkspace,
+        },
+        repositories::{
+            event::test_utils::EventRepositoryForMemory,
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{user::UserRepository, workspace::WorkspaceRepository};
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_stream_workspace_events() {
+        let user_repository = UserRepositoryForMemory::new();
+        user_repository
+            .create(CreateUser::new(TEST_SUB.to_string(), "test_user".to_string(), "test@example.com".to_string()))
+            .await
+            .unwrap();
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+            User::new(2, "auth0|other_sub".to_string(), Some("other_user".to_string()), Some("other@example.com".to_string())),
+        ]);
+        for (user_id, name) in [(1, "mine"), (2, "others")] {
+            workspace_repository
+                .create(user_id, CreateWorkspace::new(name.to_string(), false, vec![]))
+                .await
+                .unwrap();
+        }
+        let event_repository = EventRepositoryForMemory::new();
+        let app = TestApp {
+            workspace_repository,
+            user_repository,
+            event_repository: event_repository.clone(),
+            ..Default::default()
+        }
+        .build();
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/2/events")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = app.oneshot(build_req_with_empty(Method::GET, "/workspaces/1/events")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        for event in [
+            WorkspaceEvent::new(2, EventTarget::Todo, 1, ActivityAction::Create, 2),
+            WorkspaceEvent::new(1, EventTarget::Todo, 2, ActivityAction::Update, 2),
+            WorkspaceEvent::new(1, EventTarget::Label, 3, ActivityAction::Create, 1),
+            // removing the subscriber ends the stream
+            WorkspaceEvent::new(1, EventTarget::Member, 1, ActivityAction::Delete, 2),
+            WorkspaceEvent::new(1, EventTarget::Todo, 4, ActivityAction::Delete, 2),
+        ] {
+            event_repository.publish(event);
+        }
+
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+        let events: Vec<(&str, WorkspaceEvent)> = body
+            .split("\n\n")
+            .filter_map(|frame| {
+                let name = frame.lines().find_map(|line| line.strip_prefix("event: "))?;
+                let data = frame.lines().find_map(|line| line.strip_prefix("data: "))?;
+                Some((name, serde_json::from_str(data).unwrap()))
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("todo", WorkspaceEvent::new(1, EventTarget::Todo, 2, ActivityAction::Update, 2)),
+                ("label", WorkspaceEvent::new(1, EventTarget::Label, 3, ActivityAction::Create, 1)),
+            ],
+            events
+        );
+    }
+}
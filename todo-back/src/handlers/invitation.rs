@@ -0,0 +1,223 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use crate::{
+    AppState,
+    errors::AppError,
+    middlewares::auth::AuthenticatedUser,
+    models::{
+        user::User,
+        workspace::{InvitationStatus, WorkspaceInvitation},
+    },
+};
+
+pub async fn all_invitation(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let invitations = match user.email.filter(|_| user.email_verified) {
+        Some(email) => state.workspace_repository
+            .pending_invitations(email)
+            .await?,
+        None => vec![],
+    };
+
+    Ok((StatusCode::OK, Json(invitations)))
+}
+
+// invitations are addressed to an email, the token alone isn't enough to answer one
+// and the email has to be verified, anybody could claim an address otherwise
+async fn find_invitation_for_user(
+    state: &AppState,
+    sub: String,
+    token: String,
+) -> Result<(User, WorkspaceInvitation), AppError> {
+    let user = state.user_repository
+        .find_by_sub(sub)
+        .await?;
+
+    let invitation = state.workspace_repository
+        .find_invitation(token)
+        .await?;
+
+    if !user.email_verified {
+        return Err(AppError::Forbidden("Verify your email to answer invitations".to_string()));
+    }
+    if !user.email.as_deref().is_some_and(|email| invitation.is_for(email)) {
+        return Err(AppError::Forbidden("Invitation is addressed to another email".to_string()));
+    }
+    if invitation.status != InvitationStatus::Pending {
+        return Err(AppError::Conflict("Invitation was already answered".to_string()));
+    }
+    if invitation.is_expired() {
+        return Err(AppError::Gone("Invitation has expired".to_string()));
+    }
+
+    Ok((user, invitation))
+}
+
+pub async fn accept_invitation(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user, invitation) = find_invitation_for_user(&state, auth_user.sub, token).await?;
+
+    let workspace = state.workspace_repository
+        .accept_invitation(invitation.token, user.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(workspace)))
+}
+
+pub async fn decline_invitation(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let (_user, invitation) = find_invitation_for_user(&state, auth_user.sub, token).await?;
+
+    state.workspace_repository
+        .decline_invitation(invitation.token)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        models::{
+            user::{CreateUser, User},
+            workspace::{CreateWorkspace, WorkspaceEntity, WorkspaceInvitation, WorkspaceRole},
+        },
+        repositories::{
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        response::Response,
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{user::UserRepository, workspace::WorkspaceRepository};
+
+    const OWNER_SUB: &str = "auth0|owner_sub";
+    const NEWCOMER_SUB: &str = "auth0|newcomer_sub";
+    const IMPOSTOR_SUB: &str = "auth0|impostor_sub";
+
+    fn build_req_with_json(sub: &str, path: &str, method: Method, json_body: String) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header("X-Test-Sub", sub)
+            .body(Body::from(json_body))
+            .unwrap()
+    }
+
+    // signs up with whatever email the body names, only `verified_email` is vouched for by the issuer
+    fn build_sign_up_req(sub: &str, email: &str, verified_email: Option<&str>) -> Request<Body> {
+        let mut req = build_req_with_json(sub, "/users", Method::POST, format!(r#"{{ "name": "user", "email": "{}" }}"#, email));
+        if let Some(verified_email) = verified_email {
+            req.headers_mut().insert("X-Test-Email", verified_email.parse().unwrap());
+        }
+        req
+    }
+
+    fn build_req_with_empty(sub: &str, method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", sub)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn res_to_invitations(res: Response) -> Vec<WorkspaceInvitation> {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).expect("cannot convert WorkspaceInvitation list instance")
+    }
+
+    #[tokio::test]
+    async fn should_invite_unregistered_user() {
+        let user_repository = UserRepositoryForMemory::new();
+        let owner = user_repository
+            .create(CreateUser::new(OWNER_SUB.to_string(), "owner".to_string(), "owner@example.com".to_string()))
+            .await
+            .unwrap();
+        // the newcomer only signs up after being invited
+        let newcomer = User::new(2, NEWCOMER_SUB.to_string(), Some("newcomer".to_string()), Some("newcomer@example.com".to_string()));
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![owner.clone(), newcomer.clone()]);
+        for name in ["accepted", "declined"] {
+            workspace_repository
+                .create(owner.id, CreateWorkspace::new(name.to_string(), false, vec![]))
+                .await
+                .unwrap();
+        }
+        let app = TestApp {
+            workspace_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        for (path, role) in [("/workspaces/1/members", "viewer"), ("/workspaces/2/members", "editor")] {
+            let body = format!(r#"{{ "email": "newcomer@example.com", "role": "{}" }}"#, role);
+            let res = app.clone().oneshot(build_req_with_json(OWNER_SUB, path, Method::POST, body)).await.unwrap();
+            assert_eq!(StatusCode::ACCEPTED, res.status());
+        }
+
+        // the issuer's verified email wins over the one in the body
+        let req = build_sign_up_req(NEWCOMER_SUB, "someone@example.com", Some("newcomer@example.com"));
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+
+        // claiming the address isn't enough to see or answer the invitations
+        let res = app.clone().oneshot(build_sign_up_req(IMPOSTOR_SUB, "newcomer@example.com", None)).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(IMPOSTOR_SUB, Method::GET, "/invitations")).await.unwrap();
+        assert!(res_to_invitations(res).await.is_empty());
+
+        let res = app.clone().oneshot(build_req_with_empty(NEWCOMER_SUB, Method::GET, "/invitations")).await.unwrap();
+        let invitations = res_to_invitations(res).await;
+        assert_eq!(vec![2, 1], invitations.iter().map(|i| i.workspace_id).collect::<Vec<_>>());
+        let (declined, accepted) = (&invitations[0], &invitations[1]);
+
+        // somebody else can't answer for the newcomer
+        let path = format!("/invitations/{}/accept", accepted.token);
+        for sub in [OWNER_SUB, IMPOSTOR_SUB] {
+            let res = app.clone().oneshot(build_req_with_empty(sub, Method::POST, &path)).await.unwrap();
+            assert_eq!(StatusCode::FORBIDDEN, res.status());
+        }
+
+        let res = app.clone().oneshot(build_req_with_empty(NEWCOMER_SUB, Method::POST, &path)).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let workspace: WorkspaceEntity = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(Some(WorkspaceRole::Viewer), workspace.role_of(newcomer.id));
+
+        let res = app.clone().oneshot(build_req_with_empty(NEWCOMER_SUB, Method::POST, &path)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        let path = format!("/invitations/{}/decline", declined.token);
+        let res = app.clone().oneshot(build_req_with_empty(NEWCOMER_SUB, Method::POST, &path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+
+        let res = app.clone().oneshot(build_req_with_empty(NEWCOMER_SUB, Method::GET, "/invitations")).await.unwrap();
+        assert!(res_to_invitations(res).await.is_empty());
+
+        let res = app.oneshot(build_req_with_empty(NEWCOMER_SUB, Method::POST, "/invitations/unknown/accept")).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+}
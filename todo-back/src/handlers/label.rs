@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -7,8 +7,9 @@ use axum::{
 
 use crate::{
     AppState,
-    middlewares::auth::AuthenticatedUser,
-    models::label::CreateLabel,
+    errors::{AppError, FieldError},
+    middlewares::{auth::AuthenticatedUser, workspace::WorkspaceAccess},
+    models::label::{CreateLabel, DeleteLabelQuery, MergeLabel, UpdateLabel},
 };
 use super::ValidatedJson;
 
@@ -16,16 +17,14 @@ pub async fn create_label(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
     ValidatedJson(payload): ValidatedJson<CreateLabel>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
     let label = state.label_repository
         .create(user.id, payload)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
 
     Ok((StatusCode::CREATED, Json(label)))
 }
@@ -33,44 +32,152 @@ pub async fn create_label(
 pub async fn all_label(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
     let labels = state.label_repository
         .all(user.id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
     Ok((StatusCode::OK, Json(labels)))
 }
 
+pub async fn update_label(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    ValidatedJson(payload): ValidatedJson<UpdateLabel>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let label = state.label_repository
+        .update(id, user.id, payload)
+        .await?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
+pub async fn merge_label(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    ValidatedJson(payload): ValidatedJson<MergeLabel>,
+) -> Result<impl IntoResponse, AppError> {
+    check_merge(id, payload)?;
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let label = state.label_repository
+        .merge(id, payload.into, user.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
 pub async fn delete_label(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
     Path(id): Path<i32>,
-) -> Result<StatusCode, StatusCode> {
+    Query(query): Query<DeleteLabelQuery>,
+) -> Result<StatusCode, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
     state.label_repository
-        .delete(id, user.id)
-        .await
-        .map(|_| StatusCode::NO_CONTENT)
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))
+        .delete(id, user.id, query.detach)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn create_workspace_label(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateLabel>,
+) -> Result<impl IntoResponse, AppError> {
+    let label = state.label_repository
+        .create_in_workspace(access.workspace_id, access.user.id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(label)))
+}
+
+pub async fn all_workspace_label(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let labels = state.label_repository
+        .all_by_workspace(access.workspace_id, access.user.id)
+        .await?;
+    Ok((StatusCode::OK, Json(labels)))
+}
+
+pub async fn update_workspace_label(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, label_id)): Path<(i32, i32)>,
+    ValidatedJson(payload): ValidatedJson<UpdateLabel>,
+) -> Result<impl IntoResponse, AppError> {
+    let label = state.label_repository
+        .update_in_workspace(access.workspace_id, label_id, access.user.id, payload)
+        .await?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
+pub async fn merge_workspace_label(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, label_id)): Path<(i32, i32)>,
+    ValidatedJson(payload): ValidatedJson<MergeLabel>,
+) -> Result<impl IntoResponse, AppError> {
+    check_merge(label_id, payload)?;
+    let label = state.label_repository
+        .merge_in_workspace(access.workspace_id, label_id, payload.into, access.user.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(label)))
+}
+
+pub async fn delete_workspace_label(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, label_id)): Path<(i32, i32)>,
+    Query(query): Query<DeleteLabelQuery>,
+) -> Result<StatusCode, AppError> {
+    state.label_repository
+        .delete_in_workspace(access.workspace_id, label_id, access.user.id, query.detach)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn check_merge(id: i32, payload: MergeLabel) -> Result<(), AppError> {
+    if payload.into == id {
+        return Err(AppError::Validation(vec![FieldError::new(
+            "into",
+            "same_label",
+            Some("A label can not be merged into itself".to_string()),
+        )]));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
-        create_app,
+        handlers::test_utils::TestApp,
+        errors::ErrorBody,
         models::{
             label::{CreateLabel, Label},
-            user::CreateUser,
+            user::{CreateUser, User},
+            workspace::CreateWorkspace,
         },
         repositories::{
             label::test_utils::LabelRepositoryForMemory,
@@ -85,7 +192,7 @@ mod test {
         http::{header, Method, Request, StatusCode},
     };
     use tower::ServiceExt;
-    use crate::repositories::{label::LabelRepository, user::UserRepository};
+    use crate::repositories::{label::LabelRepository, user::UserRepository, workspace::WorkspaceRepository};
 
     const TEST_SUB: &str = "auth0|test_sub";
 
@@ -124,6 +231,9 @@ mod test {
                 id,
                 name: String::from("test label"),
                 user_id,
+                workspace_id: None,
+                color: None,
+                icon: None,
             }],
             vec![id],
         )
@@ -151,13 +261,12 @@ mod test {
             Method::POST,
             r#"{ "name": "should_create_label" }"#.to_string(),
         );
-        let res = create_app(
-            LabelRepositoryForMemory::new(),
-            WorkspaceRepositoryForMemory::new(),
-            TodoRepositoryForMemory::new(labels.clone()),
+        let res = TestApp {
+            todo_repository: TodoRepositoryForMemory::new(labels.clone()),
             user_repository,
-            String::new(),
-        )
+            ..Default::default()
+        }
+        .build()
         .oneshot(req)
         .await
         .unwrap();
@@ -178,13 +287,13 @@ mod test {
         seed_test_user(&user_repository).await;
 
         let req = build_req_with_empty(Method::GET, "/labels");
-        let res = create_app(
+        let res = TestApp {
             label_repository,
-            WorkspaceRepositoryForMemory::new(),
-            TodoRepositoryForMemory::new(labels.clone()),
+            todo_repository: TodoRepositoryForMemory::new(labels.clone()),
             user_repository,
-            String::new(),
-        )
+            ..Default::default()
+        }
+        .build()
         .oneshot(req)
         .await
         .unwrap();
@@ -208,16 +317,122 @@ mod test {
         let user_repository = UserRepositoryForMemory::new();
         seed_test_user(&user_repository).await;
         let req = build_req_with_empty(Method::DELETE, "/labels/1");
-        let res = create_app(
+        let res = TestApp {
             label_repository,
-            WorkspaceRepositoryForMemory::new(),
-            TodoRepositoryForMemory::new(labels.clone()),
+            todo_repository: TodoRepositoryForMemory::new(labels.clone()),
             user_repository,
-            String::new(),
-        )
+            ..Default::default()
+        }
+        .build()
         .oneshot(req)
         .await
         .unwrap();
         assert_eq!(StatusCode::NO_CONTENT, res.status());
     }
+
+    #[tokio::test]
+    async fn should_share_labels_within_workspace() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        // workspace 1 belongs to the test user, workspace 2 to somebody else
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+            User::new(2, "auth0|other_sub".to_string(), Some("other_user".to_string()), Some("other@example.com".to_string())),
+        ]);
+        workspace_repository
+            .create(1, CreateWorkspace::new("test_workspace".to_string(), false, vec![]))
+            .await
+            .unwrap();
+        workspace_repository
+            .create(2, CreateWorkspace::new("other_workspace".to_string(), false, vec![]))
+            .await
+            .unwrap();
+        let label_repository = LabelRepositoryForMemory::new();
+        label_repository
+            .create_in_workspace(2, 2, CreateLabel::new("theirs".to_string()))
+            .await
+            .unwrap();
+        let app = TestApp {
+            label_repository,
+            workspace_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let req = build_req_with_json("/workspaces/1/labels", Method::POST, r#"{ "name": "team" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let label = res_to_label(res).await;
+        assert_eq!(Label::new(2, "team".to_string(), 1).in_workspace(1), label);
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/1/labels")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let labels: Vec<Label> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(vec![label.clone()], labels);
+
+        // the shared labels of a workspace are no business of non-members
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/2/labels")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let path = format!("/workspaces/1/labels/{}", label.id);
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let res = app.oneshot(build_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_update_and_merge_label() {
+        let label_repository = LabelRepositoryForMemory::new();
+        label_repository.create(1, CreateLabel::new("bug".to_string())).await.unwrap();
+        label_repository.create(1, CreateLabel::new("defect".to_string())).await.unwrap();
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let app = TestApp {
+            label_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let req = build_req_with_json(
+            "/labels/1",
+            Method::PATCH,
+            serde_json::json!({ "name": "issue", "color": "#12ab34", "icon": "🐞" }).to_string(),
+        );
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let expected = Label {
+            color: Some("#12ab34".to_string()),
+            icon: Some("🐞".to_string()),
+            ..Label::new(1, "issue".to_string(), 1)
+        };
+        assert_eq!(expected, res_to_label(res).await);
+
+        let req = build_req_with_json("/labels/1", Method::PATCH, r#"{ "color": "red" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(("color", "invalid_color"), (error.details[0].field.as_str(), error.details[0].code.as_str()));
+
+        let req = build_req_with_json("/labels/1/merge", Method::POST, r#"{ "into": 1 }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+
+        let req = build_req_with_json("/labels/2/merge", Method::POST, r#"{ "into": 1 }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!(expected, res_to_label(res).await);
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/labels")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let labels: Vec<Label> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(vec![expected], labels);
+
+        let res = app.oneshot(build_req_with_empty(Method::DELETE, "/labels/1?detach=true")).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+    }
 }
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    AppState,
+    errors::AppError,
+    middlewares::auth::AuthenticatedUser,
+    models::search::{SearchQuery, WorkspaceSearchResult},
+};
+
+pub async fn search(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = state.user_repository
+        .find_by_sub(auth_user.sub)
+        .await?;
+
+    let workspaces = state.workspace_repository
+        .all_by_user(user.id)
+        .await?;
+    let workspace_ids: Vec<i32> = workspaces.iter().map(|ws| ws.id).collect();
+
+    let hits = state.todo_repository
+        .search(&workspace_ids, query)
+        .await?;
+
+    // hits are ranked, so the first hit of a workspace decides where the group goes
+    let mut results: Vec<WorkspaceSearchResult> = vec![];
+    for hit in hits {
+        match results.iter_mut().find(|result| result.workspace_id == hit.workspace_id) {
+            Some(result) => result.hits.push(hit),
+            None => {
+                let workspace_name = workspaces
+                    .iter()
+                    .find(|ws| ws.id == hit.workspace_id)
+                    .map(|ws| ws.name.clone())
+                    .unwrap_or_default();
+                results.push(WorkspaceSearchResult {
+                    workspace_id: hit.workspace_id,
+                    workspace_name,
+                    hits: vec![hit],
+                });
+            }
+        }
+    }
+
+    Ok((StatusCode::OK, Json(results)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        models::{
+            search::WorkspaceSearchResult,
+            todo::CreateTodo,
+            user::{CreateUser, User},
+            workspace::CreateWorkspace,
+        },
+        repositories::{
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            todo::test_utils::TodoRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{todo::TodoRepository, user::UserRepository, workspace::WorkspaceRepository};
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn seed_test_user(repo: &UserRepositoryForMemory) {
+        repo.create(CreateUser::new(
+            TEST_SUB.to_string(),
+            "test_user".to_string(),
+            "test@example.com".to_string(),
+        ))
+        .await
+        .expect("failed to seed test user");
+    }
+
+    #[tokio::test]
+    async fn should_group_hits_by_workspace() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+            User::new(2, "auth0|other_sub".to_string(), Some("other_user".to_string()), Some("other@example.com".to_string())),
+        ]);
+        let home = workspace_repository
+            .create(1, CreateWorkspace::new("home".to_string(), true, vec![]))
+            .await
+            .unwrap();
+        let office = workspace_repository
+            .create(1, CreateWorkspace::new("office".to_string(), false, vec![]))
+            .await
+            .unwrap();
+        let others = workspace_repository
+            .create(2, CreateWorkspace::new("others".to_string(), false, vec![]))
+            .await
+            .unwrap();
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        for (workspace_id, text) in [
+            (home.id, "pay rent"),
+            (office.id, "rent a meeting room"),
+            (home.id, "buy milk"),
+            (office.id, "review rental contract"),
+            (others.id, "rent is not yours"),
+            (office.id, "rent <script>alert(1)</script>"),
+        ] {
+            todo_repository
+                .create(1, workspace_id, CreateTodo::new(text.to_string(), vec![]))
+                .await
+                .unwrap();
+        }
+
+        let req = build_req_with_empty(Method::GET, "/search?q=rent");
+        let res = TestApp {
+            workspace_repository,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+        .oneshot(req)
+        .await
+        .unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let results: Vec<WorkspaceSearchResult> = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(vec!["office", "home"], results.iter().map(|r| r.workspace_name.as_str()).collect::<Vec<_>>());
+        assert_eq!(vec![6, 4, 2], results[0].hits.iter().map(|hit| hit.id).collect::<Vec<_>>());
+        assert_eq!("<mark>rent</mark> &lt;script&gt;alert(1)&lt;/script&gt;", results[0].hits[0].highlight);
+        assert_eq!("pay <mark>rent</mark>", results[1].hits[0].highlight);
+    }
+}
@@ -0,0 +1,327 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    AppState,
+    errors::{AppError, FieldError},
+    middlewares::workspace::WorkspaceAccess,
+    models::{
+        status::{BoardColumn, CreateStatus, DeleteStatusQuery, UpdateStatus},
+        todo::TodoQuery,
+    },
+};
+use super::{ValidatedJson, ValidatedQuery};
+
+pub async fn all_status(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let statuses = state.todo_repository
+        .all_statuses(access.workspace_id)
+        .await?;
+    Ok((StatusCode::OK, Json(statuses)))
+}
+
+pub async fn create_status(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateStatus>,
+) -> Result<impl IntoResponse, AppError> {
+    check_manage(&access)?;
+    let status = state.todo_repository
+        .create_status(access.workspace_id, access.user.id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(status)))
+}
+
+pub async fn update_status(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, status_id)): Path<(i32, i32)>,
+    ValidatedJson(payload): ValidatedJson<UpdateStatus>,
+) -> Result<impl IntoResponse, AppError> {
+    check_manage(&access)?;
+    let status = state.todo_repository
+        .update_status(access.workspace_id, status_id, access.user.id, payload)
+        .await?;
+
+    Ok((StatusCode::OK, Json(status)))
+}
+
+pub async fn delete_status(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, status_id)): Path<(i32, i32)>,
+    Query(query): Query<DeleteStatusQuery>,
+) -> Result<StatusCode, AppError> {
+    check_manage(&access)?;
+    if query.move_to == Some(status_id) {
+        return Err(AppError::Validation(vec![FieldError::new(
+            "move_to",
+            "same_status",
+            Some("Todos can not be moved to the status being deleted".to_string()),
+        )]));
+    }
+
+    state.todo_repository
+        .delete_status(access.workspace_id, status_id, access.user.id, query.move_to)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Every column with its first page of top level todos, filtered like the todo list.
+pub async fn board(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    ValidatedQuery(query): ValidatedQuery<TodoQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let statuses = state.todo_repository
+        .all_statuses(access.workspace_id)
+        .await?;
+
+    let mut columns = vec![];
+    for status in statuses {
+        // later pages of a column come from the todo list filtered by its status
+        let query = TodoQuery {
+            status_id: Some(status.id),
+            cursor: None,
+            ..query.clone()
+        };
+        let todos = state.todo_repository
+            .all_by_workspace(access.workspace_id, query)
+            .await?;
+        columns.push(BoardColumn { status, todos });
+    }
+
+    Ok((StatusCode::OK, Json(columns)))
+}
+
+// the board is set up for everybody, so only owners and admins change its columns
+fn check_manage(access: &WorkspaceAccess) -> Result<(), AppError> {
+    if !access.role.can_manage() {
+        return Err(AppError::Forbidden("Only owners and admins edit the board".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        handlers::test_utils::TestApp,
+        errors::ErrorBody,
+        models::{
+            status::Status,
+            todo::{CreateTodo, TodoEntity},
+            user::{CreateUser, User},
+            workspace::{CreateWorkspace, WorkspaceRole},
+        },
+        repositories::{
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            todo::test_utils::TodoRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::response::Response;
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use serde::de::DeserializeOwned;
+    use tower::ServiceExt;
+    use crate::repositories::{todo::TodoRepository, user::UserRepository, workspace::WorkspaceRepository};
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req_with_json(path: &str, method: Method, json_body: String) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::from(json_body))
+            .unwrap()
+    }
+
+    fn build_req_with_empty(method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn res_to<T: DeserializeOwned>(res: Response) -> T {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).expect("cannot convert response body")
+    }
+
+    async fn seed_test_user(repo: &UserRepositoryForMemory) {
+        repo.create(CreateUser::new(
+            TEST_SUB.to_string(),
+            "test_user".to_string(),
+            "test@example.com".to_string(),
+        ))
+        .await
+        .expect("failed to seed test user");
+    }
+
+    // the test user owns workspace 1 and edits workspace 2
+    async fn seed_workspace() -> WorkspaceRepositoryForMemory {
+        let repo = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+            User::new(2, "auth0|other_sub".to_string(), Some("other_user".to_string()), Some("other@example.com".to_string())),
+        ]);
+        repo.create(1, CreateWorkspace::new("test_workspace".to_string(), false, vec![]))
+            .await
+            .expect("failed to seed test workspace");
+        repo.create(2, CreateWorkspace::new("other_workspace".to_string(), false, vec![]))
+            .await
+            .expect("failed to seed other workspace");
+        repo.add_member(2, 2, 1, WorkspaceRole::Editor)
+            .await
+            .expect("failed to seed membership");
+        repo
+    }
+
+    async fn build_app(todo_repository: TodoRepositoryForMemory) -> axum::Router {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+    }
+
+    #[tokio::test]
+    async fn should_configure_statuses() {
+        let app = build_app(TodoRepositoryForMemory::new(vec![])).await;
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/1/statuses")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let statuses: Vec<Status> = res_to(res).await;
+        let names: Vec<&str> = statuses.iter().map(|status| status.name.as_str()).collect();
+        assert_eq!(vec!["backlog", "doing", "review", "done"], names);
+        assert_eq!(vec![false, false, false, true], statuses.iter().map(|status| status.is_terminal).collect::<Vec<_>>());
+
+        let body = r#"{ "name": "blocked", "wip_limit": 2 }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/statuses", Method::POST, body.clone())).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let blocked: Status = res_to(res).await;
+        assert_eq!((4, Some(2), false), (blocked.position, blocked.wip_limit, blocked.is_terminal));
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/statuses", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        // moved next to doing, the others shift over
+        let path = format!("/workspaces/1/statuses/{}", blocked.id);
+        let body = r#"{ "position": 2, "wip_limit": null }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, body)).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/1/statuses")).await.unwrap();
+        let statuses: Vec<Status> = res_to(res).await;
+        let names: Vec<&str> = statuses.iter().map(|status| status.name.as_str()).collect();
+        assert_eq!(vec!["backlog", "doing", "blocked", "review", "done"], names);
+        assert_eq!(None, statuses[2].wip_limit);
+        assert_eq!(vec![0, 1, 2, 3, 4], statuses.iter().map(|status| status.position).collect::<Vec<_>>());
+
+        // the board always keeps an open and a terminal column
+        let done_path = format!("/workspaces/1/statuses/{}", statuses[4].id);
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &done_path)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+        let body = r#"{ "is_terminal": false }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json(&done_path, Method::PATCH, body)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+
+        // editors use the board but don't set it up
+        let body = r#"{ "name": "mine" }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/2/statuses", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let res = app.oneshot(build_req_with_empty(Method::GET, "/workspaces/2/statuses")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_group_todos_on_board() {
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let statuses = todo_repository.all_statuses(1).await.unwrap();
+        let (backlog, doing, done) = (statuses[0].id, statuses[1].id, statuses[3].id);
+        let mut todos = vec![];
+        for text in ["write", "review", "ship"] {
+            todos.push(todo_repository.create(1, 1, CreateTodo::new(text.to_string(), vec![])).await.unwrap());
+        }
+        let app = build_app(todo_repository).await;
+
+        let path = format!("/workspaces/1/statuses/{}", doing);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, r#"{ "wip_limit": 1 }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+
+        // the status decides whether a todo is completed, and completing picks a terminal status
+        let path = format!("/workspaces/1/todos/{}", todos[0].id);
+        let body = format!(r#"{{ "status_id": {} }}"#, doing);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, body)).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let todo: TodoEntity = res_to(res).await;
+        assert_eq!((doing, false), (todo.status_id, todo.completed));
+        let path = format!("/workspaces/1/todos/{}", todos[2].id);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, r#"{ "completed": true }"#.to_string())).await.unwrap();
+        let todo: TodoEntity = res_to(res).await;
+        assert_eq!((done, true), (todo.status_id, todo.completed));
+
+        // doing is full, and statuses of other workspaces are unknown
+        let path = format!("/workspaces/1/todos/{}", todos[1].id);
+        let body = format!(r#"{{ "status_id": {} }}"#, doing);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, body)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+        let body = r#"{ "text": "other", "label_ids": [], "status_id": 999 }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error: ErrorBody = res_to(res).await;
+        assert_eq!("unknown_status", error.details[0].code);
+
+        let res = app.clone().oneshot(build_req_with_empty(Method::GET, "/workspaces/1/board")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let columns: Vec<BoardColumn> = res_to(res).await;
+        let texts = |column: &BoardColumn| column.todos.items.iter().map(|todo| todo.text.clone()).collect::<Vec<_>>();
+        assert_eq!(4, columns.len());
+        assert_eq!(vec!["review"], texts(&columns[0]));
+        assert_eq!(vec!["write"], texts(&columns[1]));
+        assert!(columns[2].todos.items.is_empty());
+        assert_eq!(vec!["ship"], texts(&columns[3]));
+        assert_eq!(backlog, columns[0].status.id);
+
+        // a column holding todos only goes away with somewhere to put them
+        let path = format!("/workspaces/1/statuses/{}", doing);
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &format!("{}?move_to={}", path, doing))).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        // ...that has room for them
+        let done_path = format!("/workspaces/1/statuses/{}", done);
+        let res = app.clone().oneshot(build_req_with_json(&done_path, Method::PATCH, r#"{ "wip_limit": 1 }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &format!("{}?move_to={}", path, done))).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+        let res = app.clone().oneshot(build_req_with_json(&done_path, Method::PATCH, r#"{ "wip_limit": null }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(Method::DELETE, &format!("{}?move_to={}", path, done))).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let path = format!("/workspaces/1/todos?status_id={}", done);
+        let res = app.oneshot(build_req_with_empty(Method::GET, &path)).await.unwrap();
+        let page: crate::models::todo::TodoPage = res_to(res).await;
+        assert!(page.items.iter().all(|todo| todo.completed));
+        assert_eq!(2, page.total);
+    }
+}
\ No newline at end of file
@@ -1,186 +1,364 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
+use chrono::{DateTime, FixedOffset};
+use validator::Validate;
 use crate::{
     AppState,
-    middlewares::auth::AuthenticatedUser,
-    models::todo::{CreateTodo, RecommendedTodo, UpdateTodo},
-    services::groq,
+    errors::{field_errors, AppError, FieldError},
+    middlewares::{auth::AuthenticatedUser, workspace::WorkspaceAccess},
+    models::{
+        label::CreateLabel,
+        quick_add::{LabelMatch, ParseTodo, QuickAddPreview, QuickAddQuery},
+        recommendation::{RecommendationIds, REJECTED_IN_PROMPT, TODOS_IN_PROMPT},
+        todo::{CreateTodo, MoveTodo, SeriesQuery, SeriesScope, TodoQuery, UpdateTodo},
+    },
+    services::{quick_add, recommend},
 };
-use super::ValidatedJson;
+use super::{ValidatedJson, ValidatedQuery};
 
 pub async fn create_todo(
-    auth_user: AuthenticatedUser,
+    access: WorkspaceAccess,
     State(state): State<AppState>,
-    Path(workspace_id): Path<i32>,
+    ValidatedQuery(query): ValidatedQuery<QuickAddQuery>,
     ValidatedJson(payload): ValidatedJson<CreateTodo>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let user = state.user_repository
-        .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+) -> Result<impl IntoResponse, AppError> {
+    let payload = if query.parse {
+        apply_quick_add(&state, &access, payload, query.now()).await?
+    } else {
+        payload
+    };
 
-    let is_member = state.workspace_repository
-        .is_member(workspace_id, user.id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    if let Some(parent_id) = payload.parent_id {
+        let parent = state.todo_repository
+            .find(parent_id)
+            .await
+            .map_err(|e| AppError::from(e).not_found_as(AppError::BadRequest("Parent todo does not exist".to_string())))?;
 
-    if !is_member {
-        return Err(StatusCode::FORBIDDEN);
+        // subtasks nest a single level deep and stay in their parent's workspace
+        if parent.workspace_id != access.workspace_id || parent.parent_id.is_some() {
+            return Err(AppError::BadRequest("Subtasks nest a single level deep in the same workspace".to_string()));
+        }
+        if payload.recurrence.is_some() {
+            return Err(AppError::BadRequest("Subtasks can not recur".to_string()));
+        }
     }
 
     let todo = state.todo_repository
-        .create(user.id, workspace_id, payload)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .create(access.user.id, access.workspace_id, payload)
+        .await?;
 
     Ok((StatusCode::CREATED, Json(todo)))
 }
 
-pub async fn all_todo(
-    auth_user: AuthenticatedUser,
+pub async fn parse_todo(
+    access: WorkspaceAccess,
     State(state): State<AppState>,
-    Path(workspace_id): Path<i32>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let user = state.user_repository
-        .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+    ValidatedQuery(query): ValidatedQuery<QuickAddQuery>,
+    ValidatedJson(payload): ValidatedJson<ParseTodo>,
+) -> Result<impl IntoResponse, AppError> {
+    let parsed = quick_add::parse(&payload.text, query.now());
+    let labels = match_labels(&state, &access, &parsed.labels).await?;
 
-    let is_member = state.workspace_repository
-        .is_member(workspace_id, user.id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    let preview = QuickAddPreview {
+        text: parsed.text,
+        due_at: parsed.due_at,
+        labels,
+        priority: parsed.priority,
+        recurrence: parsed.recurrence,
+    };
+    Ok((StatusCode::OK, Json(preview)))
+}
 
-    if !is_member {
-        return Err(StatusCode::FORBIDDEN);
+// Labels go by name, whatever the case, the ones the workspace already has keep theirs.
+async fn match_labels(state: &AppState, access: &WorkspaceAccess, names: &[String]) -> Result<Vec<LabelMatch>, AppError> {
+    let labels = state.label_repository
+        .all_by_workspace(access.workspace_id, access.user.id)
+        .await?;
+
+    Ok(names
+        .iter()
+        .map(|name| match labels.iter().find(|label| label.name.to_lowercase() == name.to_lowercase()) {
+            Some(label) => LabelMatch { name: label.name.clone(), id: Some(label.id) },
+            None => LabelMatch { name: name.clone(), id: None },
+        })
+        .collect())
+}
+
+// What the payload sets itself wins over what its text says, labels named in the text are added.
+async fn apply_quick_add(
+    state: &AppState,
+    access: &WorkspaceAccess,
+    mut payload: CreateTodo,
+    now: DateTime<FixedOffset>,
+) -> Result<CreateTodo, AppError> {
+    let parsed = quick_add::parse(&payload.text, now);
+    payload.text = parsed.text;
+    payload.due_at = payload.due_at.or(parsed.due_at);
+    payload.priority = payload.priority.or(parsed.priority);
+    payload.recurrence = payload.recurrence.or(parsed.recurrence);
+    payload.validate()?;
+
+    // labels are only checked once the todo is known to be valid
+    let labels = match_labels(state, access, &parsed.labels).await?;
+    let missing: Vec<CreateLabel> = labels
+        .iter()
+        .filter(|label| label.id.is_none())
+        .map(|label| CreateLabel::new(label.name.clone()))
+        .collect();
+    for label in &missing {
+        if let Err(errors) = label.validate() {
+            let errors = field_errors(&errors)
+                .into_iter()
+                .map(|error| FieldError { field: "text".to_string(), ..error })
+                .collect();
+            return Err(AppError::Validation(errors));
+        }
     }
+    payload.label_ids.extend(labels.iter().filter_map(|label| label.id));
+    payload.label_ids.sort_unstable();
+    payload.label_ids.dedup();
+    // created in the same transaction as the todo, a rejected todo leaves no labels behind
+    payload.new_labels = missing;
+
+    Ok(payload)
+}
 
+pub async fn all_todo(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    ValidatedQuery(query): ValidatedQuery<TodoQuery>,
+) -> Result<impl IntoResponse, AppError> {
     let todos = state.todo_repository
-        .all_by_workspace(workspace_id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .all_by_workspace(access.workspace_id, query)
+        .await?;
 
     Ok((StatusCode::OK, Json(todos)))
 }
 
-pub async fn update_todo(
+/// The caller's todos across every workspace they are a member of, filtered like a workspace's list.
+pub async fn all_assigned_todo(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
-    Path((workspace_id, todo_id)): Path<(i32, i32)>,
-    ValidatedJson(payload): ValidatedJson<UpdateTodo>,
-) -> Result<impl IntoResponse, StatusCode> {
+    ValidatedQuery(query): ValidatedQuery<TodoQuery>,
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
-    let is_member = state.workspace_repository
-        .is_member(workspace_id, user.id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    let todos = state.todo_repository
+        .all_assigned(user.id, query)
+        .await?;
+
+    Ok((StatusCode::OK, Json(todos)))
+}
 
+pub async fn update_todo(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, todo_id)): Path<(i32, i32)>,
+    Query(series): Query<SeriesQuery>,
+    ValidatedJson(payload): ValidatedJson<UpdateTodo>,
+) -> Result<impl IntoResponse, AppError> {
     let todo = state.todo_repository
         .find(todo_id)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
-    let is_authorized = is_member && todo.workspace_id == workspace_id;
-    if !is_authorized {
-        return Err(StatusCode::FORBIDDEN);
+    if todo.workspace_id != access.workspace_id {
+        return Err(AppError::Forbidden("Todo belongs to another workspace".to_string()));
+    }
+    if todo.parent_id.is_some() && matches!(payload.recurrence, Some(Some(_))) {
+        return Err(AppError::BadRequest("Subtasks can not recur".to_string()));
     }
 
-    let updated_todo = state.todo_repository
-        .update(todo_id, payload)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+    let updated_todo = match series.scope {
+        SeriesScope::This => state.todo_repository.update(todo_id, access.user.id, payload).await?,
+        SeriesScope::Future => state.todo_repository.update_series(todo_id, access.user.id, payload).await?,
+    };
 
     Ok((StatusCode::OK, Json(updated_todo)))
 }
 
-pub async fn delete_todo(
-    auth_user: AuthenticatedUser,
+pub async fn move_todo(
+    access: WorkspaceAccess,
     State(state): State<AppState>,
-    Path((workspace_id, todo_id)): Path<(i32, i32)>,
-) -> Result<StatusCode, StatusCode> {
-    let user = state.user_repository
-        .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+    Path((_, todo_id)): Path<(i32, i32)>,
+    ValidatedJson(payload): ValidatedJson<MoveTodo>,
+) -> Result<impl IntoResponse, AppError> {
+    let todo = state.todo_repository
+        .find(todo_id)
+        .await?;
 
-    let is_member = state.workspace_repository
-        .is_member(workspace_id, user.id)
+    if todo.workspace_id != access.workspace_id {
+        return Err(AppError::Forbidden("Todo belongs to another workspace".to_string()));
+    }
+    let field = if payload.before.is_some() { "before" } else { "after" };
+    let anchor = state.todo_repository
+        .find(payload.anchor_id())
         .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .map_err(|e| AppError::from(e).not_found_as(AppError::Validation(vec![FieldError::new(
+            field,
+            "not_found",
+            Some("Todo does not exist".to_string()),
+        )])))?;
+    if !todo.is_sibling(&anchor) {
+        return Err(AppError::Validation(vec![FieldError::new(
+            field,
+            "not_sibling",
+            Some("Todos are only placed next to another todo of the same list".to_string()),
+        )]));
+    }
 
+    let moved_todo = state.todo_repository
+        .move_todo(todo_id, access.user.id, payload)
+        .await?;
+
+    Ok((StatusCode::OK, Json(moved_todo)))
+}
+
+pub async fn delete_todo(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, todo_id)): Path<(i32, i32)>,
+    Query(series): Query<SeriesQuery>,
+) -> Result<StatusCode, AppError> {
     let todo = state.todo_repository
         .find(todo_id)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
-    let is_authorized = is_member && todo.workspace_id == workspace_id;
+    // editors only delete their own todos
+    let is_authorized = todo.workspace_id == access.workspace_id
+        && (todo.user_id == access.user.id || access.role.can_manage());
     if !is_authorized {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden("Editors only delete their own todos".to_string()));
     }
 
-    state.todo_repository
-        .delete(todo_id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    match series.scope {
+        SeriesScope::This => state.todo_repository.delete(todo_id, access.user.id).await?,
+        SeriesScope::Future => state.todo_repository.delete_series(todo_id, access.user.id).await?,
+    };
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn recommend_todos(
-    auth_user: AuthenticatedUser,
+pub async fn all_trash(
+    access: WorkspaceAccess,
     State(state): State<AppState>,
-    Path(workspace_id): Path<i32>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let user = state.user_repository
-        .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+) -> Result<impl IntoResponse, AppError> {
+    let todos = state.todo_repository
+        .all_trashed(access.workspace_id)
+        .await?;
 
-    let is_member = state.workspace_repository
-        .is_member(workspace_id, user.id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    Ok((StatusCode::OK, Json(todos)))
+}
+
+pub async fn restore_todo(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Path((_, todo_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError> {
+    let todo = state.todo_repository
+        .find_trashed(todo_id)
+        .await?;
 
-    if !is_member {
-        return Err(StatusCode::FORBIDDEN);
+    // same rule as deleting, editors only bring back their own todos
+    let is_authorized = todo.workspace_id == access.workspace_id
+        && (todo.user_id == access.user.id || access.role.can_manage());
+    if !is_authorized {
+        return Err(AppError::Forbidden("Editors only restore their own todos".to_string()));
+    }
+    // a subtask comes back with its parent, not on its own
+    if let Some(parent_id) = todo.parent_id
+        && state.todo_repository.find(parent_id).await.is_err()
+    {
+        return Err(AppError::Conflict("Parent todo is in the trash".to_string()));
     }
 
+    let todo = state.todo_repository
+        .restore(todo_id, access.user.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(todo)))
+}
+
+pub async fn recommend_todos(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    // only what is still to do matters to the model, and a single page of it keeps the prompt bounded
+    let query = TodoQuery {
+        completed: Some(false),
+        limit: Some(TODOS_IN_PROMPT),
+        ..Default::default()
+    };
     let todos = state.todo_repository
-        .all_by_workspace(workspace_id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .all_by_workspace(access.workspace_id, query)
+        .await?;
 
-    let existing_texts: Vec<String> = todos.iter().map(|t| t.text.clone()).collect();
+    let existing_texts: Vec<String> = todos.items.iter().map(|t| t.text.clone()).collect();
 
-    let recommendations = groq::recommend_todos(&state.gemini_api_key, &existing_texts)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+    let rejected_texts = state.todo_repository
+        .rejected_recommendations(access.workspace_id, access.user.id, REJECTED_IN_PROMPT)
+        .await?;
 
-    let result: Vec<RecommendedTodo> = recommendations
-        .into_iter()
-        .map(|text| RecommendedTodo { text })
+    let recommendations = recommend::recommend_todos(state.llm.as_ref(), &existing_texts, &rejected_texts)
+        .await?;
+
+    // whatever the model says has to fit in a todo before it is offered as one
+    let texts: Vec<String> = recommendations
+        .iter()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty())
+        .map(|text| text.chars().take(100).collect())
         .collect();
 
-    Ok((StatusCode::OK, Json(result)))
+    let recommendations = state.todo_repository
+        .save_recommendations(access.workspace_id, access.user.id, texts)
+        .await?;
+
+    Ok((StatusCode::OK, Json(recommendations)))
+}
+
+pub async fn accept_recommendations(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<RecommendationIds>,
+) -> Result<impl IntoResponse, AppError> {
+    let todos = state.todo_repository
+        .accept_recommendations(access.workspace_id, access.user.id, &payload.ids)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(todos)))
+}
+
+pub async fn reject_recommendations(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<RecommendationIds>,
+) -> Result<impl IntoResponse, AppError> {
+    state.todo_repository
+        .reject_recommendations(access.workspace_id, access.user.id, &payload.ids)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use std::sync::Arc;
     use crate::{
-        create_app,
+        handlers::test_utils::TestApp,
+        errors::{ErrorBody, FieldError},
         models::{
-            label::Label,
-            todo::{CreateTodo, TodoEntity},
-            user::CreateUser,
+            label::{CreateLabel, Label},
+            quick_add::QuickAddPreview,
+            recommendation::{Recommendation, RecommendationStatus},
+            todo::{rank_between, CreateTodo, Frequency, DEFAULT_PAGE_SIZE, Priority, TodoEntity, TodoPage, TodoProgress},
+            user::{CreateUser, User},
+            workspace::{CreateWorkspace, WorkspaceRole},
         },
         repositories::{
             label::test_utils::LabelRepositoryForMemory,
@@ -188,6 +366,7 @@ mod test {
             todo::test_utils::TodoRepositoryForMemory,
             user::test_utils::UserRepositoryForMemory,
         },
+        services::llm::MockLlm,
     };
     use axum::response::Response;
     use axum::{
@@ -195,7 +374,8 @@ mod test {
         http::{header, Method, Request, StatusCode},
     };
     use tower::ServiceExt;
-    use crate::repositories::{todo::TodoRepository, user::UserRepository};
+    use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+    use crate::repositories::{label::LabelRepository, todo::TodoRepository, user::UserRepository, workspace::WorkspaceRepository};
 
     const TEST_SUB: &str = "auth0|test_sub";
 
@@ -226,6 +406,11 @@ mod test {
         todo
     }
 
+    async fn res_to_error(res: Response) -> ErrorBody {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).expect("cannot convert ErrorBody instance")
+    }
+
     fn label_fixture() -> (Vec<Label>, Vec<i32>) {
         let id = 999;
         let user_id = 1;
@@ -234,6 +419,9 @@ mod test {
                 id,
                 name: String::from("test label"),
                 user_id,
+                workspace_id: None,
+                color: None,
+                icon: None,
             }],
             vec![id],
         )
@@ -249,6 +437,891 @@ mod test {
         .expect("failed to seed test user");
     }
 
-    // Note: workspace-based tests require WorkspaceRepositoryForMemory to implement
-    // is_member, which currently returns todo!(). Handler tests are simplified for now.
+    fn user_fixture() -> Vec<User> {
+        vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+            User::new(2, "auth0|other_sub".to_string(), Some("other_user".to_string()), Some("other@example.com".to_string())),
+        ]
+    }
+
+    // workspace 1 belongs to the test user, workspace 2 to somebody else
+    async fn seed_workspace() -> WorkspaceRepositoryForMemory {
+        let repo = WorkspaceRepositoryForMemory::new(user_fixture());
+        repo.create(1, CreateWorkspace::new("test_workspace".to_string(), false, vec![]))
+            .await
+            .expect("failed to seed test workspace");
+        repo.create(2, CreateWorkspace::new("other_workspace".to_string(), false, vec![]))
+            .await
+            .expect("failed to seed other workspace");
+        repo
+    }
+
+    #[tokio::test]
+    async fn should_create_todo_with_due_date() {
+        let (labels, _label_ids) = label_fixture();
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+
+        let req = build_req_with_json(
+            "/workspaces/1/todos",
+            Method::POST,
+            r#"{ "text": "should_create_todo", "label_ids": [999], "due_at": "2026-04-10T09:00:00Z" }"#.to_string(),
+        );
+        let res = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository: TodoRepositoryForMemory::new(labels.clone()),
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+        .oneshot(req)
+        .await
+        .unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let todo = res_to_todo(res).await;
+        let mut expected = TodoEntity::new(1, "should_create_todo".to_string(), labels, 1, 1);
+        expected.due_at = Some("2026-04-10T09:00:00Z".parse().unwrap());
+        expected.position = rank_between(None, None);
+        expected.status_id = 1;
+        assert_eq!(expected, todo);
+    }
+
+    #[tokio::test]
+    async fn should_reject_start_after_due() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+
+        let req = build_req_with_json(
+            "/workspaces/1/todos",
+            Method::POST,
+            r#"{ "text": "todo", "label_ids": [], "start_at": "2026-04-11T00:00:00Z", "due_at": "2026-04-10T00:00:00Z" }"#.to_string(),
+        );
+        let res = TestApp {
+            workspace_repository: seed_workspace().await,
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+        .oneshot(req)
+        .await
+        .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error = &res_to_error(res).await.details[0];
+        assert_eq!(("__all__", "start_after_due"), (error.field.as_str(), error.code.as_str()));
+        assert_eq!(Some("start_at must not be after due_at"), error.message.as_deref());
+
+        // updates are checked against the dates the todo already has
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let mut payload = CreateTodo::new("todo".to_string(), vec![]);
+        payload.due_at = Some("2026-04-10T00:00:00Z".parse().unwrap());
+        todo_repository.create(1, 1, payload).await.expect("failed to seed todo");
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let req = build_req_with_json(
+            "/workspaces/1/todos/1",
+            Method::PATCH,
+            r#"{ "start_at": "2026-04-11T00:00:00Z" }"#.to_string(),
+        );
+        let res = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+        .oneshot(req)
+        .await
+        .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error = &res_to_error(res).await.details[0];
+        assert_eq!(("start_at", "start_after_due"), (error.field.as_str(), error.code.as_str()));
+    }
+
+    #[tokio::test]
+    async fn should_describe_validation_errors() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let req = build_req_with_json("/workspaces/1/todos", Method::POST, r#"{ "text": "", "label_ids": [] }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error = res_to_error(res).await;
+        assert_eq!("validation_failed", error.code);
+        assert_eq!(
+            vec![FieldError {
+                field: "text".to_string(),
+                code: "length".to_string(),
+                message: Some("Can not be empty".to_string()),
+            }],
+            error.details
+        );
+
+        // unparsable bodies carry no field details
+        let req = build_req_with_json("/workspaces/1/todos", Method::POST, r#"{ "text": 1 }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error = res_to_error(res).await;
+        assert_eq!("bad_request", error.code);
+        assert!(error.details.is_empty());
+
+        let req = build_req_with_json("/workspaces/1/todos/999", Method::PATCH, "{}".to_string());
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+        assert_eq!("not_found", res_to_error(res).await.code);
+    }
+
+    #[tokio::test]
+    async fn should_reject_labels_of_other_workspaces() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let labels = vec![
+            Label::new(1, "shared".to_string(), 2).in_workspace(1),
+            Label::new(2, "elsewhere".to_string(), 1).in_workspace(2),
+            Label::new(3, "someone else's".to_string(), 2),
+        ];
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository: TodoRepositoryForMemory::new(labels),
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        // a label shared in the workspace is fine, whoever created it
+        let req = build_req_with_json("/workspaces/1/todos", Method::POST, r#"{ "text": "shared", "label_ids": [1] }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let todo = res_to_todo(res).await;
+
+        for label_id in [2, 3] {
+            let body = format!(r#"{{ "text": "hidden", "label_ids": [{}] }}"#, label_id);
+            let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, res.status());
+            let error = res_to_error(res).await;
+            assert_eq!(vec!["unknown_label".to_string()], error.details.iter().map(|error| error.code.clone()).collect::<Vec<_>>());
+
+            let path = format!("/workspaces/1/todos/{}", todo.id);
+            let body = format!(r#"{{ "label_ids": [1, {}] }}"#, label_id);
+            let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, body)).await.unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, res.status());
+            assert_eq!("label_ids", res_to_error(res).await.details[0].field);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_assign_todos() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let workspace_repository = seed_workspace().await;
+        workspace_repository.add_member(2, 2, 1, WorkspaceRole::Editor).await.unwrap();
+        let app = TestApp {
+            workspace_repository,
+            todo_repository: TodoRepositoryForMemory::new(vec![]).with_members(1, &[1]).with_members(2, &[1, 2]),
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+        let texts = |page: TodoPage| page.items.into_iter().map(|todo| todo.text).collect::<Vec<_>>();
+
+        let body = r#"{ "text": "mine", "label_ids": [], "assignee_ids": [1] }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        assert_eq!(vec![1], res_to_todo(res).await.assignee_ids);
+
+        // only members of the todo's workspace are assignable
+        let body = r#"{ "text": "stranger's", "label_ids": [], "assignee_ids": [2] }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error = res_to_error(res).await;
+        assert_eq!(("assignee_ids", "unknown_assignee"), (error.details[0].field.as_str(), error.details[0].code.as_str()));
+
+        let body = r#"{ "text": "shared", "label_ids": [], "assignee_ids": [2, 1] }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/2/todos", Method::POST, body)).await.unwrap();
+        let shared = res_to_todo(res).await;
+        assert_eq!(vec![1, 2], shared.assignee_ids);
+        let body = r#"{ "text": "nobody's", "label_ids": [] }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/2/todos", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/users/me/assigned?sort=text")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(vec!["mine", "shared"], texts(serde_json::from_slice(&bytes).unwrap()));
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/2/todos?assigned=false")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(vec!["nobody's"], texts(serde_json::from_slice(&bytes).unwrap()));
+
+        let path = format!("/workspaces/2/todos/{}", shared.id);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, r#"{ "assignee_ids": [2] }"#.to_string())).await.unwrap();
+        assert_eq!(vec![2], res_to_todo(res).await.assignee_ids);
+        let res = app.oneshot(build_todo_req_with_empty(Method::GET, "/users/me/assigned")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(vec!["mine"], texts(serde_json::from_slice(&bytes).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn should_filter_overdue_todos() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let mut payload = CreateTodo::new("overdue".to_string(), vec![]);
+        payload.due_at = Some(chrono::Utc::now() - chrono::Duration::days(1));
+        let overdue = todo_repository.create(1, 1, payload).await.unwrap();
+        todo_repository
+            .create(1, 1, CreateTodo::new("no due".to_string(), vec![]))
+            .await
+            .unwrap();
+
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+        let req = build_todo_req_with_empty(Method::GET, "/workspaces/1/todos?due=overdue&tz_offset=540");
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(vec![overdue], page.items);
+
+        // offsets past UTC±14:00 don't exist
+        for tz_offset in ["841", "99999999"] {
+            let path = format!("/workspaces/1/todos?due=overdue&tz_offset={}", tz_offset);
+            let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, &path)).await.unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, res.status());
+            let error = res_to_error(res).await;
+            assert_eq!(vec![("tz_offset", "range")], error.details.iter().map(|e| (e.field.as_str(), e.code.as_str())).collect::<Vec<_>>());
+        }
+    }
+
+    #[tokio::test]
+    async fn should_page_todos() {
+        let (labels, _label_ids) = label_fixture();
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(labels.clone());
+        for text in ["first", "second", "third"] {
+            todo_repository
+                .create(1, 1, CreateTodo::new(text.to_string(), vec![999]))
+                .await
+                .unwrap();
+        }
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let req = build_todo_req_with_empty(Method::GET, "/workspaces/1/todos?label_ids=999&limit=2");
+        let res = app.clone().oneshot(req).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(3, page.total);
+        assert_eq!(vec![3, 2], page.items.iter().map(|t| t.id).collect::<Vec<_>>());
+
+        let path = format!("/workspaces/1/todos?label_ids=999&limit=2&cursor={}", page.next_cursor.unwrap());
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, &path)).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(vec![1], page.items.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert_eq!(None, page.next_cursor);
+
+        let req = build_todo_req_with_empty(Method::GET, "/workspaces/1/todos?cursor=broken");
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_forbid_non_member() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let todo = todo_repository
+            .create(2, 2, CreateTodo::new("other".to_string(), vec![]))
+            .await
+            .unwrap();
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let req = build_todo_req_with_empty(Method::GET, "/workspaces/2/todos");
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let path = format!("/workspaces/2/todos/{}", todo.id);
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        // a todo can't be reached through a workspace it doesn't belong to
+        let path = format!("/workspaces/1/todos/{}", todo.id);
+        let res = app.oneshot(build_todo_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_enforce_workspace_roles() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let workspace_repository = seed_workspace().await;
+        workspace_repository
+            .add_member(2, 2, 1, WorkspaceRole::Viewer)
+            .await
+            .unwrap();
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let others = todo_repository
+            .create(2, 2, CreateTodo::new("other".to_string(), vec![]))
+            .await
+            .unwrap();
+        let app = TestApp {
+            workspace_repository: workspace_repository.clone(),
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+        let create_req = || build_req_with_json("/workspaces/2/todos", Method::POST, r#"{ "text": "mine", "label_ids": [] }"#.to_string());
+        let others_path = format!("/workspaces/2/todos/{}", others.id);
+
+        // viewers read but never write
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/2/todos")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let res = app.clone().oneshot(create_req()).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let req = build_req_with_json(&others_path, Method::PATCH, r#"{ "completed": true }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        // editors write, but only delete their own todos
+        workspace_repository.update_role(2, 2, 1, WorkspaceRole::Editor).await.unwrap();
+        let res = app.clone().oneshot(create_req()).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let mine = res_to_todo(res).await;
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::DELETE, &others_path)).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let path = format!("/workspaces/2/todos/{}", mine.id);
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+
+        // admins delete anybody's
+        workspace_repository.update_role(2, 2, 1, WorkspaceRole::Admin).await.unwrap();
+        let res = app.oneshot(build_todo_req_with_empty(Method::DELETE, &others_path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_nest_subtasks() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let mut payload = CreateTodo::new("parent".to_string(), vec![]);
+        payload.auto_complete = true;
+        let parent = todo_repository.create(1, 1, payload).await.unwrap();
+        let other = todo_repository
+            .create(2, 2, CreateTodo::new("other".to_string(), vec![]))
+            .await
+            .unwrap();
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        let mut children = vec![];
+        for text in ["step 1", "step 2"] {
+            let body = format!(r#"{{ "text": "{}", "label_ids": [], "parent_id": {} }}"#, text, parent.id);
+            let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+            assert_eq!(StatusCode::CREATED, res.status());
+            children.push(res_to_todo(res).await);
+        }
+
+        // no grandchildren, no parents from other workspaces
+        for parent_id in [children[0].id, other.id, 999] {
+            let body = format!(r#"{{ "text": "nested", "label_ids": [], "parent_id": {} }}"#, parent_id);
+            let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        }
+
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(1, page.total);
+        assert_eq!(children, page.items[0].children);
+        assert_eq!(TodoProgress { completed: 0, total: 2 }, page.items[0].progress);
+
+        // the parent completes with its last child
+        for child in &children {
+            let path = format!("/workspaces/1/todos/{}", child.id);
+            let req = build_req_with_json(&path, Method::PATCH, r#"{ "completed": true }"#.to_string());
+            let res = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(StatusCode::OK, res.status());
+        }
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert!(page.items[0].completed);
+        assert_eq!(TodoProgress { completed: 2, total: 2 }, page.items[0].progress);
+
+        // deleting the parent takes its children along
+        let path = format!("/workspaces/1/todos/{}", parent.id);
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let path = format!("/workspaces/1/todos/{}", children[0].id);
+        let req = build_req_with_json(&path, Method::PATCH, r#"{ "completed": false }"#.to_string());
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_move_todo() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let mut todos = vec![];
+        for text in ["first", "second", "third"] {
+            todos.push(todo_repository.create(1, 1, CreateTodo::new(text.to_string(), vec![])).await.unwrap());
+        }
+        let mut payload = CreateTodo::new("step".to_string(), vec![]);
+        payload.parent_id = Some(todos[0].id);
+        let child = todo_repository.create(1, 1, payload).await.unwrap();
+        let other = todo_repository
+            .create(2, 2, CreateTodo::new("other".to_string(), vec![]))
+            .await
+            .unwrap();
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+        let texts = |page: TodoPage| page.items.into_iter().map(|todo| todo.text).collect::<Vec<String>>();
+
+        // newest on top until moved
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(vec!["third", "second", "first"], texts(serde_json::from_slice(&bytes).unwrap()));
+
+        for (id, body) in [
+            (todos[0].id, format!(r#"{{ "before": {} }}"#, todos[2].id)),
+            (todos[2].id, format!(r#"{{ "after": {} }}"#, todos[1].id)),
+        ] {
+            let path = format!("/workspaces/1/todos/{}/move", id);
+            let res = app.clone().oneshot(build_req_with_json(&path, Method::POST, body)).await.unwrap();
+            assert_eq!(StatusCode::OK, res.status());
+        }
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(vec!["first", "second", "third"], texts(serde_json::from_slice(&bytes).unwrap()));
+
+        // one anchor of the same list
+        let path = format!("/workspaces/1/todos/{}/move", todos[1].id);
+        for (body, field, code) in [
+            (format!(r#"{{ "before": {}, "after": {} }}"#, todos[0].id, todos[2].id), "__all__", "before_or_after_required"),
+            (format!(r#"{{ "after": {} }}"#, todos[1].id), "after", "not_sibling"),
+            (r#"{ "after": 99999 }"#.to_string(), "after", "not_found"),
+            (format!(r#"{{ "before": {} }}"#, child.id), "before", "not_sibling"),
+            (format!(r#"{{ "before": {} }}"#, other.id), "before", "not_sibling"),
+        ] {
+            let res = app.clone().oneshot(build_req_with_json(&path, Method::POST, body)).await.unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, res.status());
+            let error = res_to_error(res).await;
+            assert_eq!((field, code), (error.details[0].field.as_str(), error.details[0].code.as_str()));
+        }
+
+        let path = format!("/workspaces/1/todos/{}/move", other.id);
+        let body = format!(r#"{{ "before": {} }}"#, todos[0].id);
+        let res = app.oneshot(build_req_with_json(&path, Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_trash_and_restore_todo() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let parent = todo_repository
+            .create(1, 1, CreateTodo::new("parent".to_string(), vec![]))
+            .await
+            .unwrap();
+        let mut payload = CreateTodo::new("child".to_string(), vec![]);
+        payload.parent_id = Some(parent.id);
+        let child = todo_repository.create(1, 1, payload).await.unwrap();
+        let other = todo_repository
+            .create(2, 2, CreateTodo::new("other".to_string(), vec![]))
+            .await
+            .unwrap();
+        todo_repository.delete(other.id, 2).await.unwrap();
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+
+        for id in [child.id, parent.id] {
+            let path = format!("/workspaces/1/todos/{}", id);
+            let res = app.clone().oneshot(build_todo_req_with_empty(Method::DELETE, &path)).await.unwrap();
+            assert_eq!(StatusCode::NO_CONTENT, res.status());
+        }
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(0, page.total);
+
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/trash")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let trashed: Vec<TodoEntity> = serde_json::from_slice(&bytes).unwrap();
+        let mut ids: Vec<i32> = trashed.iter().map(|todo| todo.id).collect();
+        ids.sort();
+        assert_eq!(vec![parent.id, child.id], ids);
+
+        // the child waits for its parent, other workspaces' and live todos can't be restored here
+        for (id, status) in [(child.id, StatusCode::CONFLICT), (other.id, StatusCode::FORBIDDEN), (999, StatusCode::NOT_FOUND)] {
+            let path = format!("/workspaces/1/todos/{}/restore", id);
+            let res = app.clone().oneshot(build_todo_req_with_empty(Method::POST, &path)).await.unwrap();
+            assert_eq!(status, res.status());
+        }
+        for id in [parent.id, child.id] {
+            let path = format!("/workspaces/1/todos/{}/restore", id);
+            let res = app.clone().oneshot(build_todo_req_with_empty(Method::POST, &path)).await.unwrap();
+            assert_eq!(StatusCode::OK, res.status());
+        }
+        let path = format!("/workspaces/1/todos/{}/restore", parent.id);
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::POST, &path)).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+
+        let res = app.oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let page: TodoPage = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(vec![child], page.items[0].children);
+    }
+
+    #[tokio::test]
+    async fn should_repeat_recurring_todo() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+        let list = |app: axum::Router| async move {
+            let res = app.oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos?sort=due_at")).await.unwrap();
+            let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<TodoPage>(&bytes).unwrap().items
+        };
+
+        // a rule needs a due date to count from and has to make sense on its own
+        for (body, field, code) in [
+            (r#"{ "text": "chores", "label_ids": [], "recurrence": { "frequency": "weekly" } }"#, "__all__", "recurrence_without_due"),
+            (r#"{ "text": "chores", "label_ids": [], "due_at": "2026-06-01T09:00:00Z", "recurrence": { "frequency": "daily", "interval": 0 } }"#, "recurrence.interval", "range"),
+            (r#"{ "text": "chores", "label_ids": [], "due_at": "2026-06-01T09:00:00Z", "recurrence": { "frequency": "daily", "month_day": 1 } }"#, "recurrence.__all__", "month_day_not_monthly"),
+            (r#"{ "text": "chores", "label_ids": [], "due_at": "2026-06-01T09:00:00Z", "recurrence": { "frequency": "daily", "weekdays": ["Mon"] } }"#, "recurrence.__all__", "weekdays_not_weekly"),
+        ] {
+            let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body.to_string())).await.unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, res.status());
+            let error = res_to_error(res).await;
+            assert_eq!(vec![(field, code)], error.details.iter().map(|e| (e.field.as_str(), e.code.as_str())).collect::<Vec<_>>());
+        }
+
+        let req = build_req_with_json(
+            "/workspaces/1/todos",
+            Method::POST,
+            r#"{ "text": "chores", "label_ids": [], "due_at": "2026-06-01T09:00:00Z", "recurrence": { "frequency": "weekly", "weekdays": ["Mon", "Fri"] } }"#.to_string(),
+        );
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let first = res_to_todo(res).await;
+
+        let req = build_req_with_json(
+            "/workspaces/1/todos",
+            Method::POST,
+            format!(r#"{{ "text": "sub", "label_ids": [], "parent_id": {}, "due_at": "2026-06-01T09:00:00Z", "recurrence": {{ "frequency": "daily" }} }}"#, first.id),
+        );
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+
+        let path = format!("/workspaces/1/todos/{}", first.id);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, r#"{ "completed": true }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let todos = list(app.clone()).await;
+        assert_eq!(2, todos.len());
+        let next = todos[1].clone();
+        assert_eq!(Some("2026-06-05T09:00:00Z".parse().unwrap()), next.due_at);
+        assert_eq!(first.series_id, next.series_id);
+
+        // "this one" renames a single occurrence, "all future" the rest of the series
+        let path = format!("/workspaces/1/todos/{}", next.id);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, r#"{ "text": "just this" }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let path = format!("/workspaces/1/todos/{}?scope=future", first.id);
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::PATCH, r#"{ "text": "chores" }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let todos = list(app.clone()).await;
+        assert_eq!(vec!["chores", "chores"], todos.iter().map(|todo| todo.text.as_str()).collect::<Vec<_>>());
+
+        let path = format!("/workspaces/1/todos/{}?scope=future", next.id);
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::DELETE, &path)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let todos = list(app).await;
+        assert_eq!(vec![first.id], todos.iter().map(|todo| todo.id).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn should_recommend_todos_offline() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        todo_repository.create(1, 1, CreateTodo::new("book flights".to_string(), vec![])).await.unwrap();
+        todo_repository.create(2, 2, CreateTodo::new("somebody else's".to_string(), vec![])).await.unwrap();
+        // more open todos than fit in a default page, and one that is done already
+        for i in 0..DEFAULT_PAGE_SIZE {
+            todo_repository.create(1, 1, CreateTodo::new(format!("chore {}", i), vec![])).await.unwrap();
+        }
+        let done = CreateTodo { completed: true, ..CreateTodo::new("file taxes".to_string(), vec![]) };
+        todo_repository.create(1, 1, done).await.unwrap();
+        // models like to wrap their answer in a code block
+        let llm = Arc::new(MockLlm::new("```json\n[\"pack a bag\", \"renew passport\"]\n```".to_string()));
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            llm: llm.clone(),
+            ..Default::default()
+        }
+        .build();
+
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::POST, "/workspaces/1/todos/recommend")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let recommended: Vec<Recommendation> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            vec!["pack a bag", "renew passport"],
+            recommended.iter().map(|recommendation| recommendation.text.as_str()).collect::<Vec<_>>()
+        );
+        assert!(recommended.iter().all(|recommendation| recommendation.status == RecommendationStatus::Pending));
+        let prompts = llm.prompts();
+        assert_eq!(1, prompts.len());
+        assert!(prompts[0].contains(". book flights"));
+        assert!((0..DEFAULT_PAGE_SIZE).all(|i| prompts[0].contains(&format!(". chore {}\n", i))));
+        assert!(!prompts[0].contains("somebody else's"));
+        assert!(!prompts[0].contains("file taxes"));
+
+        let res = app.oneshot(build_todo_req_with_empty(Method::POST, "/workspaces/2/todos/recommend")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        assert_eq!(1, llm.prompts().len());
+    }
+
+    #[tokio::test]
+    async fn should_accept_and_reject_recommendations() {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        let todo_repository = TodoRepositoryForMemory::new(vec![]);
+        let llm = Arc::new(MockLlm::new(r#"["pack a bag", "  ", "renew passport", "call the hotel"]"#.to_string()));
+        let app = TestApp {
+            workspace_repository: seed_workspace().await,
+            todo_repository,
+            user_repository,
+            llm: llm.clone(),
+            ..Default::default()
+        }
+        .build();
+        let list = |app: axum::Router| async move {
+            let res = app.oneshot(build_todo_req_with_empty(Method::GET, "/workspaces/1/todos")).await.unwrap();
+            let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<TodoPage>(&bytes).unwrap().items
+        };
+
+        let res = app.clone().oneshot(build_todo_req_with_empty(Method::POST, "/workspaces/1/todos/recommend")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let recommended: Vec<Recommendation> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(3, recommended.len());
+
+        // one unknown id and nothing is created
+        let body = format!(r#"{{ "ids": [{}, 99] }}"#, recommended[0].id);
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/recommendations/accept", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+        assert!(list(app.clone()).await.is_empty());
+
+        let body = format!(r#"{{ "ids": [{}, {}] }}"#, recommended[0].id, recommended[2].id);
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/recommendations/accept", Method::POST, body.clone())).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let accepted: Vec<TodoEntity> = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            vec!["pack a bag", "call the hotel"],
+            accepted.iter().map(|todo| todo.text.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(2, list(app.clone()).await.len());
+
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/recommendations/accept", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        let body = format!(r#"{{ "ids": [{}] }}"#, recommended[1].id);
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/2/recommendations/reject", Method::POST, body.clone())).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/recommendations/reject", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/recommendations/reject", Method::POST, r#"{ "ids": [] }"#.to_string())).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+
+        // the next prompt steers clear of what was turned down
+        let res = app.oneshot(build_todo_req_with_empty(Method::POST, "/workspaces/1/todos/recommend")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let prompts = llm.prompts();
+        assert_eq!(2, prompts.len());
+        assert!(!prompts[0].contains("- renew passport"));
+        assert!(prompts[1].contains("- renew passport"));
+        assert!(prompts[1].contains(". pack a bag"));
+    }
+
+    const JST: i32 = 9 * 60;
+
+    fn jst(date: NaiveDate, hour: u32, minute: u32) -> DateTime<Utc> {
+        FixedOffset::east_opt(JST * 60)
+            .unwrap()
+            .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap()))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    async fn setup_quick_add() -> axum::Router {
+        let user_repository = UserRepositoryForMemory::new();
+        seed_test_user(&user_repository).await;
+        // "finance" already exists, the todo repository also knows the label a quick add creates next
+        let label_repository = LabelRepositoryForMemory::new();
+        label_repository.create_in_workspace(1, 1, CreateLabel::new("finance".to_string())).await.unwrap();
+        let labels = vec![
+            Label::new(1, "finance".to_string(), 1).in_workspace(1),
+            Label::new(2, "家計".to_string(), 1).in_workspace(1),
+        ];
+        TestApp {
+            label_repository,
+            workspace_repository: seed_workspace().await,
+            todo_repository: TodoRepositoryForMemory::new(labels),
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+    }
+
+    async fn preview(app: axum::Router, text: &str) -> QuickAddPreview {
+        let path = format!("/workspaces/1/todos/parse?tz_offset={}", JST);
+        let body = serde_json::json!({ "text": text }).to_string();
+        let res = app.oneshot(build_req_with_json(&path, Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_preview_quick_add() {
+        let app = setup_quick_add().await;
+        let now = Utc::now().with_timezone(&FixedOffset::east_opt(JST * 60).unwrap());
+        let today = now.date_naive();
+        let tomorrow = today + Duration::days(1);
+
+        let parsed = preview(app.clone(), "Pay rent tomorrow 9am #Finance #家計 !high").await;
+        assert_eq!("Pay rent", parsed.text);
+        assert_eq!(Some(jst(tomorrow, 9, 0)), parsed.due_at);
+        assert_eq!(Some(Priority::High), parsed.priority);
+        assert_eq!(
+            vec![("finance", Some(1)), ("家計", None)],
+            parsed.labels.iter().map(|label| (label.name.as_str(), label.id)).collect::<Vec<_>>()
+        );
+        assert_eq!(None, parsed.recurrence);
+
+        let parsed = preview(app.clone(), "明日の午後3時半に歯医者 ＃健康 優先度低").await;
+        assert_eq!("歯医者", parsed.text);
+        assert_eq!(Some(jst(tomorrow, 15, 30)), parsed.due_at);
+        assert_eq!(Some(Priority::Low), parsed.priority);
+        assert_eq!(vec!["健康"], parsed.labels.iter().map(|label| label.name.as_str()).collect::<Vec<_>>());
+
+        // a date alone is due by the end of that day
+        for text in ["Submit report in 3 days", "3日後までにレポート提出"] {
+            let parsed = preview(app.clone(), text).await;
+            assert_eq!(Some(jst(today + Duration::days(3), 23, 59)), parsed.due_at, "{}", text);
+        }
+        let parsed = preview(app.clone(), "Dentist on 12/24 at 14:00").await;
+        assert_eq!("Dentist", parsed.text);
+        let christmas_eve = parsed.due_at.unwrap().with_timezone(&now.timezone());
+        assert_eq!((12, 24, 14), (christmas_eve.month(), christmas_eve.day(), christmas_eve.hour()));
+        assert!(christmas_eve.date_naive() >= today);
+
+        // a rule without a date starts with its next occurrence
+        for text in ["Take out the trash every monday 9am", "毎週月曜9時にゴミ出し"] {
+            let parsed = preview(app.clone(), text).await;
+            let recurrence = parsed.recurrence.expect("no recurrence");
+            assert_eq!((Frequency::Weekly, vec![Weekday::Mon], JST), (recurrence.frequency, recurrence.weekdays, recurrence.tz_offset), "{}", text);
+            let due_at = parsed.due_at.unwrap().with_timezone(&now.timezone());
+            assert_eq!((Weekday::Mon, 9), (due_at.weekday(), due_at.hour()), "{}", text);
+            assert!(due_at >= now && due_at < now + Duration::days(7), "{}", text);
+        }
+        let parsed = preview(app.clone(), "Water the plants every other day").await;
+        let recurrence = parsed.recurrence.unwrap();
+        assert_eq!("Water the plants", parsed.text);
+        assert_eq!((Frequency::Daily, 2), (recurrence.frequency, recurrence.interval));
+
+        // nothing that merely looks like syntax is taken
+        let parsed = preview(app.clone(), "Learn C# on a sunny day").await;
+        assert_eq!(("Learn C# on a sunny day", None, None), (parsed.text.as_str(), parsed.due_at, parsed.priority));
+        assert!(parsed.labels.is_empty());
+        let parsed = preview(app.clone(), "#finance").await;
+        assert_eq!("#finance", parsed.text);
+
+        let body = serde_json::json!({ "text": "Pay rent tomorrow" }).to_string();
+        let res = app.oneshot(build_req_with_json("/workspaces/1/todos/parse?tz_offset=99999", Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let error = res_to_error(res).await;
+        assert_eq!(vec![("tz_offset", "range")], error.details.iter().map(|e| (e.field.as_str(), e.code.as_str())).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn should_create_todo_from_quick_add() {
+        let app = setup_quick_add().await;
+        let tomorrow = Utc::now().with_timezone(&FixedOffset::east_opt(JST * 60).unwrap()).date_naive() + Duration::days(1);
+
+        let path = format!("/workspaces/1/todos?parse=true&tz_offset={}", JST);
+        let body = r#"{ "text": "Pay rent tomorrow 9am #finance #家計 !high", "label_ids": [] }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+        let todo = res_to_todo(res).await;
+        assert_eq!("Pay rent", todo.text);
+        assert_eq!(Some(jst(tomorrow, 9, 0)), todo.due_at);
+        assert_eq!(Some(Priority::High), todo.priority);
+        assert_eq!(vec![1, 2], todo.labels.iter().map(|label| label.id).collect::<Vec<_>>());
+
+        // the payload wins over the text, which is stored as typed unless asked to parse it
+        let body = r#"{ "text": "Pay rent tomorrow !high", "label_ids": [], "priority": "low" }"#.to_string();
+        let res = app.clone().oneshot(build_req_with_json(&path, Method::POST, body.clone())).await.unwrap();
+        let todo = res_to_todo(res).await;
+        assert_eq!(("Pay rent", Some(Priority::Low)), (todo.text.as_str(), todo.priority));
+        let res = app.clone().oneshot(build_req_with_json("/workspaces/1/todos", Method::POST, body)).await.unwrap();
+        let todo = res_to_todo(res).await;
+        assert_eq!(("Pay rent tomorrow !high", None), (todo.text.as_str(), todo.due_at));
+
+        let body = r#"{ "text": "Pay rent tomorrow", "label_ids": [], "start_at": "2999-01-01T00:00:00Z" }"#.to_string();
+        let res = app.oneshot(build_req_with_json(&path, Method::POST, body)).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+    }
 }
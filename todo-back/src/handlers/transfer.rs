@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use futures::stream::{self, StreamExt};
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{field_errors, AppError, FieldError},
+    middlewares::workspace::WorkspaceAccess,
+    models::{
+        label::CreateLabel,
+        todo::{CreateTodo, SortOrder, TodoCursor, TodoEntity, TodoQuery, TodoSort, MAX_PAGE_SIZE},
+        transfer::{ImportReport, ImportRowResult, ImportStatus, TodoRecord, TransferFormat, TransferQuery},
+    },
+    services::transfer,
+};
+
+// one page of the export, along with the query for the next one if there is any
+async fn export_page(
+    state: &AppState,
+    workspace_id: i32,
+    format: TransferFormat,
+    mut query: TodoQuery,
+    first: bool,
+) -> anyhow::Result<(String, bool, Option<TodoQuery>)> {
+    let page = state.todo_repository
+        .all_by_workspace(workspace_id, query.clone())
+        .await?;
+    let chunk = transfer::page(format, &page.items, first)?;
+    let first = first && page.items.is_empty();
+    match page.next_cursor {
+        Some(cursor) => {
+            query.cursor = Some(TodoCursor::decode(&cursor)?);
+            Ok((chunk, first, Some(query)))
+        }
+        None => Ok((chunk, first, None)),
+    }
+}
+
+// workspaces can be large, pages are written out as they are read instead of collected first
+pub async fn export_todos(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Query(query): Query<TransferQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let workspace = state.workspace_repository
+        .find(access.workspace_id)
+        .await?;
+    let format = query.format;
+
+    let first_query = TodoQuery {
+        sort: TodoSort::Created,
+        order: Some(SortOrder::Asc),
+        limit: Some(MAX_PAGE_SIZE),
+        ..Default::default()
+    };
+    let pages = stream::unfold(Some((first_query, true)), move |next| {
+        let state = state.clone();
+        async move {
+            let (query, first) = next?;
+            match export_page(&state, workspace.id, format, query, first).await {
+                Ok((chunk, first, next)) => Some((Ok(chunk), next.map(|query| (query, first)))),
+                Err(e) => {
+                    tracing::error!("export of workspace {} failed: {:?}", workspace.id, e);
+                    Some((Err(e), None))
+                }
+            }
+        }
+    });
+    let body = stream::once(async move { transfer::header(format, &workspace.name) })
+        .chain(pages)
+        .chain(stream::once(async move { Ok(transfer::footer(format)) }));
+
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"workspace-{}.{}\"", access.workspace_id, format.extension()),
+        ),
+    ];
+    Ok((StatusCode::OK, headers, Body::from_stream(body)))
+}
+
+/// Rows are imported one by one, a failing row is reported and doesn't stop the others.
+pub async fn import_todos(
+    access: WorkspaceAccess,
+    State(state): State<AppState>,
+    Query(query): Query<TransferQuery>,
+    body: String,
+) -> Result<impl IntoResponse, AppError> {
+    let rows = transfer::parse(query.format, &body)
+        .map_err(|e| AppError::BadRequest(format!("Unreadable {} document: {:#}", query.format.extension(), e)))?;
+
+    let mut labels: HashMap<String, i32> = state.label_repository
+        .all_by_workspace(access.workspace_id, access.user.id)
+        .await?
+        .into_iter()
+        .map(|label| (label.name, label.id))
+        .collect();
+    // todo id and whether it is a subtask, for each imported row
+    let mut imported: HashMap<usize, (i32, bool)> = HashMap::new();
+    let mut results = vec![];
+    for row in rows {
+        let parent_id = match row.parent.map(|parent| imported.get(&parent)) {
+            None => Ok(None),
+            Some(Some((parent_id, false))) => Ok(Some(*parent_id)),
+            Some(Some((_, true))) => Err(FieldError::new(
+                "parent_id",
+                "too_deep",
+                Some("Subtasks nest a single level deep".to_string()),
+            )),
+            Some(None) => Err(FieldError::new(
+                "parent_id",
+                "parent_not_imported",
+                Some("The parent row was not imported".to_string()),
+            )),
+        };
+        let outcome = match (row.record, parent_id) {
+            (Ok(record), Ok(parent_id)) => import_record(&state, &access, &mut labels, record, parent_id).await?,
+            (Err(error), _) | (_, Err(error)) => Err(vec![error]),
+        };
+        results.push(match outcome {
+            Ok(todo) => {
+                imported.insert(row.row, (todo.id, todo.parent_id.is_some()));
+                ImportRowResult { row: row.row, status: ImportStatus::Created, todo_id: Some(todo.id), errors: vec![] }
+            }
+            Err(errors) => ImportRowResult { row: row.row, status: ImportStatus::Failed, todo_id: None, errors },
+        });
+    }
+
+    let created = results.iter().filter(|result| result.status == ImportStatus::Created).count();
+    let report = ImportReport {
+        created,
+        failed: results.len() - created,
+        rows: results,
+    };
+    Ok((StatusCode::OK, Json(report)))
+}
+
+// the outer error aborts the import, the inner one only fails the row
+async fn import_record(
+    state: &AppState,
+    access: &WorkspaceAccess,
+    labels: &mut HashMap<String, i32>,
+    record: TodoRecord,
+    parent_id: Option<i32>,
+) -> Result<Result<TodoEntity, Vec<FieldError>>, AppError> {
+    let mut payload = CreateTodo::new(record.text, vec![]);
+    payload.due_at = record.due_at;
+    payload.start_at = record.start_at;
+    payload.parent_id = parent_id;
+    payload.completed = record.completed;
+    if let Err(errors) = payload.validate() {
+        return Ok(Err(field_errors(&errors)));
+    }
+
+    // missing labels are created in the todo's transaction, a row that fails leaves none behind
+    let mut missing: Vec<CreateLabel> = record.labels
+        .iter()
+        .filter(|name| !labels.contains_key(*name))
+        .map(|name| CreateLabel::new(name.clone()))
+        .collect();
+    missing.sort_by(|a, b| a.name.cmp(&b.name));
+    missing.dedup();
+    for label in &missing {
+        if let Err(errors) = label.validate() {
+            let errors = field_errors(&errors)
+                .into_iter()
+                .map(|error| FieldError { field: "labels".to_string(), ..error })
+                .collect();
+            return Ok(Err(errors));
+        }
+    }
+    payload.label_ids = record.labels.iter().filter_map(|name| labels.get(name).copied()).collect();
+    payload.label_ids.sort_unstable();
+    payload.label_ids.dedup();
+    payload.new_labels = missing;
+
+    let todo = match state.todo_repository.create(access.user.id, access.workspace_id, payload).await {
+        Ok(todo) => todo,
+        Err(e) => {
+            // a full column or a label deleted meanwhile only concern this row
+            return match AppError::from(e) {
+                AppError::Internal(e) => Err(AppError::Internal(e)),
+                AppError::Validation(errors) => Ok(Err(errors)),
+                error => Ok(Err(vec![FieldError::new("__all__", error.code(), Some(error.to_string()))])),
+            };
+        }
+    };
+    labels.extend(todo.labels.iter().map(|label| (label.name.clone(), label.id)));
+    Ok(Ok(todo))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        errors::ErrorBody,
+        models::{
+            label::{CreateLabel, Label},
+            status::Status,
+            todo::{CreateTodo, TodoPage, UpdateTodo},
+            transfer::{ImportReport, ImportStatus, TodoRecord},
+            user::{CreateUser, User},
+            workspace::CreateWorkspace,
+        },
+        repositories::{
+            label::test_utils::LabelRepositoryForMemory,
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            todo::test_utils::TodoRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        response::Response,
+        Router,
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{label::LabelRepository, todo::TodoRepository, user::UserRepository, workspace::WorkspaceRepository};
+
+    const TEST_SUB: &str = "auth0|test_sub";
+
+    fn build_req(method: Method, path: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn res_to_string(res: Response) -> String {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    async fn res_to_report(res: Response) -> ImportReport {
+        serde_json::from_str(&res_to_string(res).await).expect("cannot convert ImportReport instance")
+    }
+
+    fn summary(report: &ImportReport) -> Vec<(usize, ImportStatus, Vec<String>)> {
+        report.rows
+            .iter()
+            .map(|row| (row.row, row.status, row.errors.iter().map(|error| error.code.clone()).collect()))
+            .collect()
+    }
+
+    // the label repository knows "errands", labels created by an import resolve through the todo repository
+    async fn setup(todo_repository: TodoRepositoryForMemory) -> Router {
+        let user_repository = UserRepositoryForMemory::new();
+        user_repository
+            .create(CreateUser::new(TEST_SUB.to_string(), "test_user".to_string(), "test@example.com".to_string()))
+            .await
+            .unwrap();
+        let workspace_repository = WorkspaceRepositoryForMemory::new(vec![
+            User::new(1, TEST_SUB.to_string(), Some("test_user".to_string()), Some("test@example.com".to_string())),
+        ]);
+        workspace_repository
+            .create(1, CreateWorkspace::new("home".to_string(), false, vec![]))
+            .await
+            .unwrap();
+        let label_repository = LabelRepositoryForMemory::new();
+        label_repository.create_in_workspace(1, 1, CreateLabel::new("errands".to_string())).await.unwrap();
+
+        TestApp {
+            label_repository,
+            workspace_repository,
+            todo_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build()
+    }
+
+    fn labels(names: &[&str]) -> Vec<Label> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Label::new(i as i32 + 1, name.to_string(), 1).in_workspace(1))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_export_workspace_todos() {
+        let todo_repository = TodoRepositoryForMemory::new(labels(&["errands", "home office"]));
+        let mut payload = CreateTodo::new("buy milk, eggs".to_string(), vec![1, 2]);
+        payload.due_at = Some("2026-06-01T09:00:00Z".parse().unwrap());
+        let parent = todo_repository.create(1, 1, payload).await.unwrap();
+        let mut payload = CreateTodo::new("find the list".to_string(), vec![]);
+        payload.parent_id = Some(parent.id);
+        todo_repository.create(1, 1, payload).await.unwrap();
+        let paid = todo_repository.create(1, 1, CreateTodo::new("pay rent".to_string(), vec![])).await.unwrap();
+        todo_repository
+            .update(paid.id, 1, UpdateTodo { completed: Some(true), ..Default::default() })
+            .await
+            .unwrap();
+        let app = setup(todo_repository).await;
+
+        let res = app.clone().oneshot(build_req(Method::GET, "/workspaces/1/export", "")).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!("application/json", res.headers()[header::CONTENT_TYPE]);
+        assert_eq!("attachment; filename=\"workspace-1.json\"", res.headers()[header::CONTENT_DISPOSITION]);
+        let records: Vec<TodoRecord> = serde_json::from_str(&res_to_string(res).await).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(vec!["errands".to_string(), "home office".to_string()], records[0].labels);
+        assert_eq!("find the list", records[0].subtasks[0].text);
+        assert!(records[1].completed);
+
+        let res = app.clone().oneshot(build_req(Method::GET, "/workspaces/1/export?format=csv", "")).await.unwrap();
+        assert_eq!("attachment; filename=\"workspace-1.csv\"", res.headers()[header::CONTENT_DISPOSITION]);
+        assert_eq!(
+            "id,parent_id,text,completed,labels,due_at,start_at\n\
+             1,,\"buy milk, eggs\",false,errands;home office,2026-06-01T09:00:00Z,\n\
+             2,1,find the list,false,,,\n\
+             3,,pay rent,true,,,\n",
+            res_to_string(res).await
+        );
+
+        let res = app.clone().oneshot(build_req(Method::GET, "/workspaces/1/export?format=markdown", "")).await.unwrap();
+        assert_eq!(
+            "# home\n\n\
+             - [ ] buy milk, eggs #errands #home-office due:2026-06-01T09:00:00Z\n  \
+             - [ ] find the list\n\
+             - [x] pay rent\n",
+            res_to_string(res).await
+        );
+
+        // not a member of the other workspace
+        let res = app.oneshot(build_req(Method::GET, "/workspaces/2/export", "")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_import_todos_row_by_row() {
+        let app = setup(TodoRepositoryForMemory::new(labels(&["errands", "bakery", "travel"]))).await;
+
+        let markdown = "# groceries\n\
+            - [ ] buy bread #errands #bakery due:2026-06-03T09:00:00Z\n  \
+            - [x] check the pantry\n\
+            - [ ] #errands\n  \
+            - [ ] orphan step\n\
+            - [ ] bake due:tomorrow\n\
+            some notes\n\
+            - [x] water plants\n";
+        let res = app.clone().oneshot(build_req(Method::POST, "/workspaces/1/import?format=markdown", markdown)).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let report = res_to_report(res).await;
+        assert_eq!((3, 3), (report.created, report.failed));
+        assert_eq!(
+            vec![
+                (2, ImportStatus::Created, vec![]),
+                (3, ImportStatus::Created, vec![]),
+                (4, ImportStatus::Failed, vec!["length".to_string()]),
+                (5, ImportStatus::Failed, vec!["parent_not_imported".to_string()]),
+                (6, ImportStatus::Failed, vec!["invalid_date".to_string()]),
+                (8, ImportStatus::Created, vec![]),
+            ],
+            summary(&report)
+        );
+
+        let res = app.clone().oneshot(build_req(Method::GET, "/workspaces/1/todos", "")).await.unwrap();
+        let page: TodoPage = serde_json::from_str(&res_to_string(res).await).unwrap();
+        let bread = page.items.iter().find(|todo| todo.text == "buy bread").unwrap();
+        assert_eq!(vec![1, 2], bread.labels.iter().map(|label| label.id).collect::<Vec<_>>());
+        assert!(bread.children[0].completed);
+        assert!(page.items.iter().any(|todo| todo.text == "water plants" && todo.completed));
+
+        let json = r#"[
+            {"text": "plan trip", "labels": ["travel"], "subtasks": [{"text": "pack", "subtasks": [{"text": "socks"}]}]},
+            {"completed": true}
+        ]"#;
+        let res = app.clone().oneshot(build_req(Method::POST, "/workspaces/1/import", json)).await.unwrap();
+        assert_eq!(
+            vec![
+                (1, ImportStatus::Created, vec![]),
+                (2, ImportStatus::Created, vec![]),
+                (3, ImportStatus::Failed, vec!["too_deep".to_string()]),
+                (4, ImportStatus::Failed, vec!["invalid_row".to_string()]),
+            ],
+            summary(&res_to_report(res).await)
+        );
+
+        let csv = "id,parent_id,text,labels\n10,,book flights,travel\n11,10,compare prices,\n12,99,lost,\n";
+        let res = app.clone().oneshot(build_req(Method::POST, "/workspaces/1/import?format=csv", csv)).await.unwrap();
+        assert_eq!(
+            vec![
+                (1, ImportStatus::Created, vec![]),
+                (2, ImportStatus::Created, vec![]),
+                (3, ImportStatus::Failed, vec!["unknown_parent".to_string()]),
+            ],
+            summary(&res_to_report(res).await)
+        );
+
+        // a document that isn't in the format at all fails as a whole
+        let res = app.oneshot(build_req(Method::POST, "/workspaces/1/import?format=csv", "name\nfoo\n")).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let body: ErrorBody = serde_json::from_str(&res_to_string(res).await).unwrap();
+        assert_eq!("bad_request", body.code);
+    }
+
+    #[tokio::test]
+    async fn should_fail_rows_the_repository_turns_down() {
+        let app = setup(TodoRepositoryForMemory::new(labels(&["errands", "travel"]))).await;
+        let res = app.clone().oneshot(build_req(Method::GET, "/workspaces/1/statuses", "")).await.unwrap();
+        let statuses: Vec<Status> = serde_json::from_str(&res_to_string(res).await).unwrap();
+        let done = statuses.iter().find(|status| status.is_terminal).unwrap();
+        let req = Request::builder()
+            .uri(format!("/workspaces/1/statuses/{}", done.id))
+            .method(Method::PATCH)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header("X-Test-Sub", TEST_SUB)
+            .body(Body::from(r#"{ "wip_limit": 1 }"#))
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+
+        // the done column is full after the first row, the rows after it are still imported
+        let json = r#"[
+            {"text": "file taxes", "completed": true},
+            {"text": "renew passport", "labels": ["travel"], "completed": true},
+            {"text": "plan trip", "labels": ["travel"]}
+        ]"#;
+        let res = app.clone().oneshot(build_req(Method::POST, "/workspaces/1/import", json)).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!(
+            vec![
+                (1, ImportStatus::Created, vec![]),
+                (2, ImportStatus::Failed, vec!["conflict".to_string()]),
+                (3, ImportStatus::Created, vec![]),
+            ],
+            summary(&res_to_report(res).await)
+        );
+
+        let res = app.oneshot(build_req(Method::GET, "/workspaces/1/todos", "")).await.unwrap();
+        let page: TodoPage = serde_json::from_str(&res_to_string(res).await).unwrap();
+        let texts: Vec<(&str, bool)> = page.items.iter().map(|todo| (todo.text.as_str(), todo.completed)).collect();
+        assert_eq!(vec![("plan trip", false), ("file taxes", true)], texts);
+    }
+}
\ No newline at end of file
@@ -7,20 +7,25 @@ use axum::{
 
 use crate::{
     AppState,
+    errors::AppError,
     middlewares::auth::AuthenticatedUser,
     models::user::{CreateUser, UpdateUser},
 };
 use super::ValidatedJson;
 
 pub async fn create_user(
-    _user: AuthenticatedUser,
+    auth_user: AuthenticatedUser,
     State(state): State<AppState>,
     ValidatedJson(payload): ValidatedJson<CreateUser>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
+    // nobody signs up under somebody else's subject, and only the issuer verifies an email
+    let payload = match auth_user.verified_email {
+        Some(email) => CreateUser { sub: auth_user.sub, email, ..payload }.verified(),
+        None => CreateUser { sub: auth_user.sub, ..payload },
+    };
     let user = state.user_repository
         .create(payload)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
 
     Ok((StatusCode::CREATED, Json(user)))
 }
@@ -28,11 +33,10 @@ pub async fn create_user(
 pub async fn find_me(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
     Ok((StatusCode::OK, Json(user)))
 }
@@ -41,11 +45,10 @@ pub async fn update_user(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
     ValidatedJson(payload): ValidatedJson<UpdateUser>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .update_name(auth_user.sub, payload)
-        .await
-        .or(Err(StatusCode::NOT_FOUND))?;
+        .await?;
 
     Ok((StatusCode::OK, Json(user)))
 }
@@ -54,14 +57,12 @@ pub async fn update_user(
 mod test {
     use super::*;
     use crate::{
-        create_app,
+        handlers::test_utils::TestApp,
         models::{
             label::Label,
             user::{CreateUser, User},
         },
         repositories::{
-            label::test_utils::LabelRepositoryForMemory,
-            workspace::test_utils::WorkspaceRepositoryForMemory,
             todo::test_utils::TodoRepositoryForMemory,
             user::test_utils::UserRepositoryForMemory,
         },
@@ -113,6 +114,9 @@ mod test {
                 id,
                 name: String::from("test label"),
                 user_id,
+                workspace_id: None,
+                color: None,
+                icon: None,
             }],
             vec![id],
         )
@@ -136,13 +140,11 @@ mod test {
                 TEST_SUB
             ),
         );
-        let res = create_app(
-            LabelRepositoryForMemory::new(),
-            WorkspaceRepositoryForMemory::new(),
-            TodoRepositoryForMemory::new(labels.clone()),
-            UserRepositoryForMemory::new(),
-            String::new(),
-            )
+        let res = TestApp {
+            todo_repository: TodoRepositoryForMemory::new(labels.clone()),
+            ..Default::default()
+        }
+        .build()
             .oneshot(req)
             .await
             .unwrap();
@@ -171,13 +173,12 @@ mod test {
             .expect("failed create user");
 
         let req = build_req_with_empty(Method::GET, "/users/me");
-        let res = create_app(
-            LabelRepositoryForMemory::new(),
-            WorkspaceRepositoryForMemory::new(),
-            TodoRepositoryForMemory::new(labels.clone()),
+        let res = TestApp {
+            todo_repository: TodoRepositoryForMemory::new(labels.clone()),
             user_repository,
-            String::new(),
-        )
+            ..Default::default()
+        }
+        .build()
         .oneshot(req)
         .await
         .unwrap();
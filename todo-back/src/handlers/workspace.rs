@@ -1,29 +1,34 @@
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use crate::{
     AppState,
+    errors::AppError,
     middlewares::auth::AuthenticatedUser,
-    models::workspace::CreateWorkspace,
+    models::{
+        user::User,
+        workspace::{
+            AddMember, CreateWorkspace, TransferOwnership, UpdateMember, UpdateWorkspace,
+            WorkspaceEntity, WorkspaceMember, WorkspaceRole,
+        },
+    },
 };
 use super::ValidatedJson;
 
 pub async fn all_workspace(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
 
     let workspaces = state.workspace_repository
         .all_by_user(user.id)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
 
     Ok(Json(workspaces))
 }
@@ -32,17 +37,440 @@ pub async fn create_workspace(
     auth_user: AuthenticatedUser,
     State(state): State<AppState>,
     ValidatedJson(payload): ValidatedJson<CreateWorkspace>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse, AppError> {
     let user = state.user_repository
         .find_by_sub(auth_user.sub.clone())
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
 
     let workspace = state.workspace_repository
         .create(user.id, payload)
-        .await
-        .or(Err(StatusCode::INTERNAL_SERVER_ERROR))?;
+        .await?;
 
     Ok((StatusCode::CREATED, Json(workspace)))
 }
 
+pub async fn update_workspace(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+    ValidatedJson(payload): ValidatedJson<UpdateWorkspace>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user, role, _workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if !role.can_manage() {
+        return Err(AppError::Forbidden("Only owners and admins edit the workspace".to_string()));
+    }
+
+    let workspace = state.workspace_repository
+        .update(workspace_id, user.id, payload)
+        .await?;
+
+    Ok((StatusCode::OK, Json(workspace)))
+}
+
+pub async fn delete_workspace(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let (user, role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if workspace.is_personal || role != WorkspaceRole::Owner {
+        return Err(AppError::Forbidden("Only the owner deletes a shared workspace".to_string()));
+    }
+
+    state.workspace_repository
+        .delete(workspace_id, user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_workspace_as_member(
+    state: &AppState,
+    sub: String,
+    workspace_id: i32,
+) -> Result<(User, WorkspaceRole, WorkspaceEntity), AppError> {
+    let user = state.user_repository
+        .find_by_sub(sub)
+        .await?;
+
+    let workspace = state.workspace_repository
+        .find(workspace_id)
+        .await?;
+
+    let role = workspace.role_of(user.id).ok_or(AppError::Forbidden("Not a member of this workspace".to_string()))?;
+
+    Ok((user, role, workspace))
+}
+
+pub async fn all_member(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    let (_user, _role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    Ok((StatusCode::OK, Json(workspace.users)))
+}
+
+pub async fn add_member(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+    ValidatedJson(payload): ValidatedJson<AddMember>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user, role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if workspace.is_personal || !role.can_manage() {
+        return Err(AppError::Forbidden("Only owners and admins manage members of a shared workspace".to_string()));
+    }
+
+    // people without an account or a verified email yet get an invitation to accept once they sign up
+    let invitee = match state.user_repository.find_by_email(payload.email.clone()).await.map_err(AppError::from) {
+        Ok(invitee) => invitee,
+        Err(AppError::NotFound(_)) => {
+            let invitation = state.workspace_repository
+                .create_invitation(workspace_id, user.id, payload.email, payload.role)
+                .await?;
+
+            return Ok((StatusCode::ACCEPTED, Json(invitation)).into_response());
+        }
+        Err(e) => return Err(e),
+    };
+
+    if workspace.role_of(invitee.id).is_some() {
+        return Err(AppError::Conflict("Already a member of this workspace".to_string()));
+    }
+
+    state.workspace_repository
+        .add_member(workspace_id, user.id, invitee.id, payload.role)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(WorkspaceMember::new(invitee, payload.role))).into_response())
+}
+
+pub async fn update_member(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path((workspace_id, user_id)): Path<(i32, i32)>,
+    ValidatedJson(payload): ValidatedJson<UpdateMember>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user, role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if workspace.is_personal || !role.can_manage() {
+        return Err(AppError::Forbidden("Only owners and admins manage members of a shared workspace".to_string()));
+    }
+    let target_role = workspace.role_of(user_id).ok_or(AppError::NotFound("Not a member of this workspace".to_string()))?;
+    // the owner's role only changes through a transfer
+    if target_role == WorkspaceRole::Owner {
+        return Err(AppError::Conflict("The owner's role only changes through a transfer".to_string()));
+    }
+    // nobody grants or revokes a rank equal to or above their own
+    if target_role >= role || payload.role >= role {
+        return Err(AppError::Forbidden("Can not grant or revoke a role at or above your own".to_string()));
+    }
+
+    state.workspace_repository
+        .update_role(workspace_id, user.id, user_id, payload.role)
+        .await?;
+
+    let workspace = state.workspace_repository
+        .find(workspace_id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(workspace.users)))
+}
+
+pub async fn remove_member(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path((workspace_id, user_id)): Path<(i32, i32)>,
+) -> Result<StatusCode, AppError> {
+    let (user, role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if workspace.is_personal || !role.can_manage() {
+        return Err(AppError::Forbidden("Only owners and admins manage members of a shared workspace".to_string()));
+    }
+    let target_role = workspace.role_of(user_id).ok_or(AppError::NotFound("Not a member of this workspace".to_string()))?;
+    // the owner has to hand the workspace over before leaving it
+    if target_role == WorkspaceRole::Owner {
+        return Err(AppError::Conflict("The owner has to transfer the workspace first".to_string()));
+    }
+    if target_role >= role {
+        return Err(AppError::Forbidden("Can not remove a member at or above your own role".to_string()));
+    }
+
+    state.workspace_repository
+        .remove_member(workspace_id, user.id, user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn leave_workspace(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let (user, role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if workspace.is_personal {
+        return Err(AppError::Forbidden("Can not leave a personal workspace".to_string()));
+    }
+    if role == WorkspaceRole::Owner {
+        return Err(AppError::Conflict("The owner has to transfer the workspace first".to_string()));
+    }
+
+    state.workspace_repository
+        .remove_member(workspace_id, user.id, user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn transfer_ownership(
+    auth_user: AuthenticatedUser,
+    State(state): State<AppState>,
+    Path(workspace_id): Path<i32>,
+    ValidatedJson(payload): ValidatedJson<TransferOwnership>,
+) -> Result<impl IntoResponse, AppError> {
+    let (user, role, workspace) = find_workspace_as_member(&state, auth_user.sub, workspace_id).await?;
+
+    if workspace.is_personal || role != WorkspaceRole::Owner {
+        return Err(AppError::Forbidden("Only the owner transfers a shared workspace".to_string()));
+    }
+    if workspace.role_of(payload.user_id).is_none() {
+        return Err(AppError::BadRequest("The new owner has to be a member".to_string()));
+    }
+
+    let workspace = state.workspace_repository
+        .transfer_ownership(workspace_id, user.id, payload.user_id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(workspace)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        handlers::test_utils::TestApp,
+        errors::ErrorBody,
+        models::{
+            user::{CreateUser, User},
+            workspace::{CreateWorkspace, WorkspaceEntity, WorkspaceMember, WorkspaceRole},
+        },
+        repositories::{
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            user::test_utils::UserRepositoryForMemory,
+        },
+    };
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use tower::ServiceExt;
+    use crate::repositories::{user::UserRepository, workspace::WorkspaceRepository};
+
+    const OWNER_SUB: &str = "auth0|owner_sub";
+    const MEMBER_SUB: &str = "auth0|member_sub";
+
+    fn build_req_with_json(sub: &str, path: &str, method: Method, json_body: String) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header("X-Test-Sub", sub)
+            .body(Body::from(json_body))
+            .unwrap()
+    }
+
+    fn build_req_with_empty(sub: &str, method: Method, path: &str) -> Request<Body> {
+        Request::builder()
+            .uri(path)
+            .method(method)
+            .header("X-Test-Sub", sub)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    // owner (1) and member (2) share workspace 1, the owner also has the personal workspace 2,
+    // outsider (3) is registered but belongs to neither
+    async fn setup() -> (Router, Vec<User>) {
+        let user_repository = UserRepositoryForMemory::new();
+        let mut users = vec![];
+        for (sub, name) in [(OWNER_SUB, "owner"), (MEMBER_SUB, "member"), ("auth0|outsider_sub", "outsider")] {
+            let user = user_repository
+                .create(CreateUser::new(sub.to_string(), name.to_string(), format!("{}@example.com", name)).verified())
+                .await
+                .expect("failed to seed user");
+            users.push(user);
+        }
+        user_repository
+            .create(CreateUser::new("auth0|unverified_sub".to_string(), "unverified".to_string(), "unverified@example.com".to_string()))
+            .await
+            .expect("failed to seed user");
+        let workspace_repository = WorkspaceRepositoryForMemory::new(users.clone());
+        workspace_repository
+            .create(1, CreateWorkspace::new("team".to_string(), false, vec!["member@example.com".to_string()]))
+            .await
+            .expect("failed to seed workspace");
+        workspace_repository
+            .create(1, CreateWorkspace::new("owner's workspace".to_string(), true, vec![]))
+            .await
+            .expect("failed to seed personal workspace");
+
+        let app = TestApp {
+            workspace_repository,
+            user_repository,
+            ..Default::default()
+        }
+        .build();
+        (app, users)
+    }
+
+    async fn res_to_members(res: axum::response::Response) -> Vec<WorkspaceMember> {
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).expect("cannot convert WorkspaceMember list instance")
+    }
+
+    #[tokio::test]
+    async fn should_manage_members() {
+        let (app, users) = setup().await;
+
+        // only admins and the owner invite, by email
+        let req = build_req_with_json(MEMBER_SUB, "/workspaces/1/members", Method::POST, r#"{ "email": "outsider@example.com" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/members", Method::POST, r#"{ "email": "outsider@example.com", "role": "viewer" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CREATED, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/members", Method::POST, r#"{ "email": "outsider@example.com" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!("conflict", error.code);
+
+        // unregistered and unverified emails are invited instead
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/members", Method::POST, r#"{ "email": "nobody@example.com" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::ACCEPTED, res.status());
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/members", Method::POST, r#"{ "email": "unverified@example.com" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::ACCEPTED, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/members", Method::POST, r#"{ "email": "outsider@example.com", "role": "owner" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!("owner_not_assignable", error.details[0].code);
+
+        // list
+        let res = app.clone().oneshot(build_req_with_empty(MEMBER_SUB, Method::GET, "/workspaces/1/members")).await.unwrap();
+        let expected = vec![
+            WorkspaceMember::new(users[0].clone(), WorkspaceRole::Owner),
+            WorkspaceMember::new(users[1].clone(), WorkspaceRole::Editor),
+            WorkspaceMember::new(users[2].clone(), WorkspaceRole::Viewer),
+        ];
+        assert_eq!(expected, res_to_members(res).await);
+
+        // change roles
+        let req = build_req_with_json(MEMBER_SUB, "/workspaces/1/members/3", Method::PATCH, r#"{ "role": "editor" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/members/2", Method::PATCH, r#"{ "role": "admin" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!(WorkspaceRole::Admin, res_to_members(res).await[1].role);
+
+        // an admin can't touch other admins or the owner
+        let req = build_req_with_json(MEMBER_SUB, "/workspaces/1/members/1", Method::PATCH, r#"{ "role": "viewer" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(MEMBER_SUB, Method::DELETE, "/workspaces/1/members/1")).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        // admins and the owner remove members
+        let res = app.clone().oneshot(build_req_with_empty("auth0|outsider_sub", Method::DELETE, "/workspaces/1/members/2")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(MEMBER_SUB, Method::DELETE, "/workspaces/1/members/3")).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(OWNER_SUB, Method::DELETE, "/workspaces/1/members/1")).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        // the owner can't leave without transferring ownership first
+        let res = app.clone().oneshot(build_req_with_empty(OWNER_SUB, Method::POST, "/workspaces/1/leave")).await.unwrap();
+        assert_eq!(StatusCode::CONFLICT, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/owner", Method::PUT, r#"{ "user_id": 3 }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1/owner", Method::PUT, r#"{ "user_id": 2 }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let workspace: WorkspaceEntity = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(Some(2), workspace.owner_id);
+        assert_eq!(Some(WorkspaceRole::Admin), workspace.role_of(1));
+
+        let res = app.clone().oneshot(build_req_with_empty(OWNER_SUB, Method::POST, "/workspaces/1/leave")).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+
+        let res = app.oneshot(build_req_with_empty(OWNER_SUB, Method::GET, "/workspaces/1/members")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_protect_personal_workspace() {
+        let (app, _users) = setup().await;
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/2/members", Method::POST, r#"{ "email": "member@example.com" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = app.clone().oneshot(build_req_with_empty(OWNER_SUB, Method::DELETE, "/workspaces/2/members/1")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = app.clone().oneshot(build_req_with_empty(OWNER_SUB, Method::POST, "/workspaces/2/leave")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let res = app.oneshot(build_req_with_empty(OWNER_SUB, Method::DELETE, "/workspaces/2")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+    }
+
+    #[tokio::test]
+    async fn should_rename_and_delete_workspace() {
+        let (app, _users) = setup().await;
+
+        let req = build_req_with_json(MEMBER_SUB, "/workspaces/1", Method::PATCH, r#"{ "name": "renamed" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1", Method::PATCH, r#"{ "name": "" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+
+        let req = build_req_with_json(OWNER_SUB, "/workspaces/1", Method::PATCH, r#"{ "name": "renamed" }"#.to_string());
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        let bytes = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let workspace: WorkspaceEntity = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!("renamed", workspace.name);
+
+        // only the owner deletes
+        let res = app.clone().oneshot(build_req_with_empty(MEMBER_SUB, Method::DELETE, "/workspaces/1")).await.unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, res.status());
+        let res = app.clone().oneshot(build_req_with_empty(OWNER_SUB, Method::DELETE, "/workspaces/1")).await.unwrap();
+        assert_eq!(StatusCode::NO_CONTENT, res.status());
+
+        let res = app.oneshot(build_req_with_empty(MEMBER_SUB, Method::GET, "/workspaces/1/members")).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+    }
+}
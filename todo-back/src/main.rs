@@ -1,3 +1,4 @@
+mod errors;
 mod middlewares;
 mod handlers;
 mod models;
@@ -10,12 +11,13 @@ use axum::{
         header::{AUTHORIZATION, CONTENT_TYPE},
         HeaderValue,
     },
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
 };
 use std::net::SocketAddr;
 use std::{
     env,
     sync::Arc,
+    time::Duration,
 };
 use sqlx::PgPool;
 use tower_http::cors::{Any, CorsLayer};
@@ -23,17 +25,43 @@ use dotenvy::dotenv;
 use tokio::net::TcpListener;
 
 use handlers::{
-    label::{all_label, create_label, delete_label},
-    workspace::{all_workspace, create_workspace},
-    todo::{all_todo, create_todo, delete_todo, update_todo, recommend_todos},
+    access_token::{all_access_tokens, create_access_token, revoke_access_token},
+    activity::all_activity,
+    calendar::{calendar_feed, create_calendar_feed, export_calendar, find_calendar_feed, revoke_calendar_feed},
+    event::stream_events,
+    invitation::{accept_invitation, all_invitation, decline_invitation},
+    label::{
+        all_label, all_workspace_label, create_label, create_workspace_label, delete_label,
+        delete_workspace_label, merge_label, merge_workspace_label, update_label,
+        update_workspace_label,
+    },
+    search::search,
+    status::{all_status, board, create_status, delete_status, update_status},
+    workspace::{
+        add_member, all_member, all_workspace, create_workspace, leave_workspace, remove_member,
+        transfer_ownership, update_member, update_workspace, delete_workspace,
+    },
+    transfer::{export_todos, import_todos},
+    todo::{
+        accept_recommendations, all_assigned_todo, all_todo, all_trash, create_todo, delete_todo, move_todo, parse_todo,
+        recommend_todos, reject_recommendations, restore_todo, update_todo,
+    },
     user::{create_user, find_me, update_user},
 };
+use models::todo::DEFAULT_TRASH_RETENTION_DAYS;
 use repositories::{
+    activity::ActivityRepositoryForDb,
+    event::EventRepositoryForDb,
     label::LabelRepositoryForDb,
     workspace::WorkspaceRepositoryForDb,
     todo::TodoRepositoryForDb,
     user::UserRepositoryForDb,
 };
+use services::{
+    llm::{self, LlmProvider},
+    oidc::OidcVerifier,
+    trash,
+};
 
 #[tokio::main]
 async fn main() {
@@ -56,14 +84,33 @@ async fn main() {
         .await
         .expect("failed to run database migrations");
 
-    let gemini_api_key = env::var("GROQ_API_KEY").unwrap_or_default();
+    let oidc = OidcVerifier::from_env().expect("invalid OIDC configuration");
+    if oidc.is_empty() {
+        tracing::warn!("no trusted OIDC issuer configured, only access tokens will be accepted");
+    }
+
+    let llm = llm::from_env().expect("invalid LLM configuration");
+
+    let trash_retention_days: i64 = env::var("TRASH_RETENTION_DAYS")
+        .map(|days| days.parse().expect("TRASH_RETENTION_DAYS must be a number"))
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+    tokio::spawn(trash::purge_trash(
+        TodoRepositoryForDb::new(pool.clone()),
+        chrono::Duration::days(trash_retention_days),
+        Duration::from_secs(60 * 60),
+    ));
 
     let app = create_app(
         LabelRepositoryForDb::new(pool.clone()),
         WorkspaceRepositoryForDb::new(pool.clone()),
         TodoRepositoryForDb::new(pool.clone()),
         UserRepositoryForDb::new(pool.clone()),
-        gemini_api_key,
+        ActivityRepositoryForDb::new(pool.clone()),
+        EventRepositoryForDb::new(pool.clone())
+            .await
+            .expect("failed to listen for workspace events"),
+        oidc,
+        llm,
     );
     let port: u16 = env::var("PORT")
         .unwrap_or_else(|_| "3000".to_string())
@@ -85,22 +132,32 @@ pub struct AppState {
     pub workspace_repository: Arc<dyn repositories::workspace::WorkspaceRepository>,
     pub todo_repository: Arc<dyn repositories::todo::TodoRepository>,
     pub user_repository: Arc<dyn repositories::user::UserRepository>,
-    pub gemini_api_key: String,
+    pub activity_repository: Arc<dyn repositories::activity::ActivityRepository>,
+    pub event_repository: Arc<dyn repositories::event::EventRepository>,
+    pub oidc: Arc<OidcVerifier>,
+    pub llm: Arc<dyn LlmProvider>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_app(
     label_repository: impl repositories::label::LabelRepository,
     workspace_repository: impl repositories::workspace::WorkspaceRepository,
     todo_repository: impl repositories::todo::TodoRepository,
     user_repository: impl repositories::user::UserRepository,
-    gemini_api_key: String,
+    activity_repository: impl repositories::activity::ActivityRepository,
+    event_repository: impl repositories::event::EventRepository,
+    oidc: OidcVerifier,
+    llm: Arc<dyn LlmProvider>,
 ) -> Router {
     let state = AppState {
         label_repository: Arc::new(label_repository),
         workspace_repository: Arc::new(workspace_repository),
         todo_repository: Arc::new(todo_repository),
         user_repository: Arc::new(user_repository),
-        gemini_api_key,
+        activity_repository: Arc::new(activity_repository),
+        event_repository: Arc::new(event_repository),
+        oidc: Arc::new(oidc),
+        llm,
     };
 
     Router::new()
@@ -109,7 +166,11 @@ pub fn create_app(
             "/labels",
             post(create_label).get(all_label),
         )
-        .route("/labels/{id}", delete(delete_label))
+        .route(
+            "/labels/{id}",
+            delete(delete_label).patch(update_label),
+        )
+        .route("/labels/{id}/merge", post(merge_label))
         .route(
             "/users",
             post(create_user),
@@ -119,11 +180,68 @@ pub fn create_app(
             get(find_me)
                 .patch(update_user),
         )
+        .route(
+            "/users/me/calendar-feed",
+            get(find_calendar_feed)
+                .post(create_calendar_feed)
+                .delete(revoke_calendar_feed),
+        )
+        .route(
+            "/users/me/tokens",
+            get(all_access_tokens).post(create_access_token),
+        )
+        .route("/users/me/tokens/{token_id}", delete(revoke_access_token))
+        .route("/users/me/assigned", get(all_assigned_todo))
+        .route("/feeds/{token}/calendar.ics", get(calendar_feed))
+        .route("/search", get(search))
+        .route("/invitations", get(all_invitation))
+        .route("/invitations/{token}/accept", post(accept_invitation))
+        .route("/invitations/{token}/decline", post(decline_invitation))
         .route(
             "/workspaces",
             post(create_workspace).get(all_workspace),
         )
+        .route(
+            "/workspaces/{id}",
+            delete(delete_workspace).patch(update_workspace),
+        )
+        .route(
+            "/workspaces/{id}/members",
+            post(add_member).get(all_member),
+        )
+        .route(
+            "/workspaces/{id}/members/{user_id}",
+            delete(remove_member).patch(update_member),
+        )
+        .route("/workspaces/{id}/leave", post(leave_workspace))
+        .route("/workspaces/{id}/owner", put(transfer_ownership))
+        .route(
+            "/workspaces/{id}/labels",
+            post(create_workspace_label).get(all_workspace_label),
+        )
+        .route(
+            "/workspaces/{id}/labels/{label_id}",
+            delete(delete_workspace_label).patch(update_workspace_label),
+        )
+        .route("/workspaces/{id}/labels/{label_id}/merge", post(merge_workspace_label))
+        .route(
+            "/workspaces/{id}/statuses",
+            post(create_status).get(all_status),
+        )
+        .route(
+            "/workspaces/{id}/statuses/{status_id}",
+            delete(delete_status).patch(update_status),
+        )
+        .route("/workspaces/{id}/board", get(board))
+        .route("/workspaces/{id}/activity", get(all_activity))
+        .route("/workspaces/{id}/events", get(stream_events))
+        .route("/workspaces/{id}/calendar.ics", get(export_calendar))
+        .route("/workspaces/{id}/export", get(export_todos))
+        .route("/workspaces/{id}/import", post(import_todos))
         .route("/workspaces/{id}/todos/recommend", post(recommend_todos))
+        .route("/workspaces/{id}/todos/parse", post(parse_todo))
+        .route("/workspaces/{id}/recommendations/accept", post(accept_recommendations))
+        .route("/workspaces/{id}/recommendations/reject", post(reject_recommendations))
         .route(
             "/workspaces/{id}/todos",
             post(create_todo).get(all_todo),
@@ -132,6 +250,9 @@ pub fn create_app(
             "/workspaces/{id}/todos/{todo_id}",
             delete(delete_todo).patch(update_todo),
         )
+        .route("/workspaces/{id}/todos/{todo_id}/move", post(move_todo))
+        .route("/workspaces/{id}/todos/{todo_id}/restore", post(restore_todo))
+        .route("/workspaces/{id}/trash", get(all_trash))
         .with_state(state)
         .layer(
             CorsLayer::new()
@@ -1,92 +1,47 @@
 use axum::{
     extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
+    http::request::Parts,
+};
+use crate::{
+    AppState,
+    errors::AppError,
+    models::access_token::ACCESS_TOKEN_PREFIX,
 };
-use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
-use serde::{Deserialize, Serialize};
-use std::env;
-use std::sync::OnceLock;
-use tokio::sync::RwLock;
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Claims {
-    pub sub: String,
-    pub exp: usize,
-    pub iss: String,
-    pub aud: serde_json::Value,
-}
 
 #[derive(Debug)]
 pub struct AuthenticatedUser {
+    /// The subject users are looked up by, qualified by the issuer for JWTs.
     pub sub: String,
+    /// The email the issuer vouches for, `None` when it doesn't or for access tokens.
+    pub verified_email: Option<String>,
+    /// Set when the caller signed in with a personal access token instead of a JWT.
+    pub access_token_id: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
-struct JwksResponse {
-    keys: Vec<Jwk>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Jwk {
-    kid: String,
-    n: String,
-    e: String,
-}
-
-static JWKS_CACHE: OnceLock<RwLock<Vec<Jwk>>> = OnceLock::new();
-
-fn get_jwks_cache() -> &'static RwLock<Vec<Jwk>> {
-    JWKS_CACHE.get_or_init(|| RwLock::new(Vec::new()))
-}
-
-async fn fetch_jwks(domain: &str) -> Result<Vec<Jwk>, StatusCode> {
-    let url = format!("https://{}/.well-known/jwks.json", domain);
-    let response = reqwest::get(&url)
+// read-only tokens only get through on safe methods
+async fn authenticate_access_token(parts: &Parts, state: &AppState, token: String) -> Result<AuthenticatedUser, AppError> {
+    let (user, access_token) = state.user_repository
+        .find_by_access_token(token)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let jwks: JwksResponse = response
-        .json()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    Ok(jwks.keys)
-}
-
-async fn get_decoding_key(domain: &str, kid: &str) -> Result<DecodingKey, StatusCode> {
-    let cache = get_jwks_cache();
+        .map_err(|e| AppError::from(e).not_found_as(AppError::Unauthorized))?;
 
-    {
-        let keys = cache.read().await;
-        if let Some(jwk) = keys.iter().find(|k| k.kid == kid) {
-            return DecodingKey::from_rsa_components(&jwk.n, &jwk.e)
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    if !parts.method.is_safe() && !access_token.can_write() {
+        return Err(AppError::Forbidden("This access token is read-only".to_string()));
     }
 
-    let keys = fetch_jwks(domain).await?;
-    let jwk = keys
-        .iter()
-        .find(|k| k.kid == kid)
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-    let decoding_key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    {
-        let mut cached = cache.write().await;
-        *cached = keys;
-    }
-
-    Ok(decoding_key)
+    Ok(AuthenticatedUser {
+        sub: user.sub,
+        verified_email: None,
+        access_token_id: Some(access_token.id),
+    })
 }
 
-impl<S> FromRequestParts<S> for AuthenticatedUser 
-where
-    S: Send + Sync,
-{
-    type Rejection = StatusCode;
+impl FromRequestParts<AppState> for AuthenticatedUser {
+    type Rejection = AppError;
 
     async fn from_request_parts(
         parts: &mut Parts,
-        _state: &S,
+        state: &AppState,
     ) -> Result<Self, Self::Rejection> {
         #[cfg(test)]
         {
@@ -95,42 +50,313 @@ where
                 .get("X-Test-Sub")
                 .and_then(|v| v.to_str().ok())
             {
+                let test_email = parts
+                    .headers
+                    .get("X-Test-Email")
+                    .and_then(|v| v.to_str().ok());
                 return Ok(AuthenticatedUser {
                     sub: test_sub.to_string(),
+                    verified_email: test_email.map(str::to_string),
+                    access_token_id: None,
                 });
             }
         }
 
-        let domain = env::var("AUTH0_DOMAIN")
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        let audience = env::var("AUTH0_AUDIENCE")
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
         let auth_header = parts
             .headers
             .get("Authorization")
             .and_then(|v| v.to_str().ok())
-            .ok_or(StatusCode::UNAUTHORIZED)?;
+            .ok_or(AppError::Unauthorized)?;
 
         let token = auth_header
             .strip_prefix("Bearer ")
-            .ok_or(StatusCode::UNAUTHORIZED)?;
-
-        let header = decode_header(token)
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
-        let kid = header.kid.ok_or(StatusCode::UNAUTHORIZED)?;
-
-        let decoding_key = get_decoding_key(&domain, &kid).await?;
+            .ok_or(AppError::Unauthorized)?;
 
-        let mut validation = Validation::new(Algorithm::RS256);
-        validation.set_audience(&[&audience]);
-        validation.set_issuer(&[format!("https://{}/", domain)]);
+        if token.starts_with(ACCESS_TOKEN_PREFIX) {
+            let token = token.to_string();
+            return authenticate_access_token(parts, state, token).await;
+        }
 
-        let token_data = decode::<Claims>(token, &decoding_key, &validation)
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        let claims = state.oidc.verify(token).await?;
 
         Ok(AuthenticatedUser {
-            sub: token_data.claims.sub,
+            sub: claims.subject,
+            verified_email: claims.email.filter(|_| claims.email_verified),
+            access_token_id: None,
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+    use axum::{
+        body::Body,
+        extract::State,
+        http::{header, Request, StatusCode},
+        routing::get,
+        Json, Router,
+    };
+    use chrono::Utc;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+    use crate::{
+        create_app,
+        errors::AppError,
+        models::user::CreateUser,
+        repositories::{
+            activity::test_utils::ActivityRepositoryForMemory,
+            event::test_utils::EventRepositoryForMemory,
+            label::test_utils::LabelRepositoryForMemory,
+            workspace::test_utils::WorkspaceRepositoryForMemory,
+            todo::test_utils::TodoRepositoryForMemory,
+            user::{test_utils::UserRepositoryForMemory, UserRepository},
+        },
+        services::{
+            llm::MockLlm,
+            oidc::{OidcVerifier, TrustedIssuer},
+        },
+    };
+
+    const SUB: &str = "oidc|test_sub";
+    const AUDIENCE: &str = "https://todo-back.test";
+    const KEY_1: (&str, &str, &str) = (
+        "key-1",
+        include_str!("../../fixtures/oidc/key-1.pem"),
+        include_str!("../../fixtures/oidc/key-1.jwk.json"),
+    );
+    const KEY_2: (&str, &str, &str) = (
+        "key-2",
+        include_str!("../../fixtures/oidc/key-2.pem"),
+        include_str!("../../fixtures/oidc/key-2.jwk.json"),
+    );
+
+    // an issuer on a local port, serving discovery and whatever keys the test publishes
+    #[derive(Clone)]
+    struct StubIssuer {
+        url: String,
+        keys: Arc<Mutex<Vec<Value>>>,
+        discovery_hits: Arc<AtomicUsize>,
+        jwks_hits: Arc<AtomicUsize>,
+        // answers the JWKS with 500 while set
+        failing: Arc<AtomicBool>,
+    }
+
+    impl StubIssuer {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let stub = StubIssuer {
+                url: format!("http://{}", listener.local_addr().unwrap()),
+                keys: Arc::default(),
+                discovery_hits: Arc::default(),
+                jwks_hits: Arc::default(),
+                failing: Arc::default(),
+            };
+            let app = Router::new()
+                .route("/.well-known/openid-configuration", get(discovery))
+                .route("/jwks", get(jwks))
+                .with_state(stub.clone());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            stub.publish(KEY_1);
+            stub
+        }
+
+        fn publish(&self, (_, _, jwk): (&str, &str, &str)) {
+            self.keys.lock().unwrap().push(serde_json::from_str(jwk).unwrap());
+        }
+
+        fn trusted(&self) -> TrustedIssuer {
+            TrustedIssuer::new(self.url.clone(), AUDIENCE.to_string())
+        }
+
+        // what users signing in through this issuer are stored under
+        fn subject(&self) -> String {
+            format!("{}|{}", self.url, SUB)
+        }
+
+        fn sign(&self, (kid, pem, _): (&str, &str, &str), audience: &str, expires_in: i64) -> String {
+            let mut header = Header::new(Algorithm::RS256);
+            header.kid = Some(kid.to_string());
+            let claims = json!({
+                "sub": SUB,
+                "iss": format!("{}/", self.url),
+                "aud": audience,
+                "exp": Utc::now().timestamp() + expires_in,
+            });
+            encode(&header, &claims, &EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap()).unwrap()
+        }
+
+        fn hits(&self) -> (usize, usize) {
+            (self.discovery_hits.load(Ordering::SeqCst), self.jwks_hits.load(Ordering::SeqCst))
+        }
+    }
+
+    async fn discovery(State(stub): State<StubIssuer>) -> Json<Value> {
+        stub.discovery_hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({ "issuer": stub.url, "jwks_uri": format!("{}/jwks", stub.url) }))
+    }
+
+    async fn jwks(State(stub): State<StubIssuer>) -> Result<Json<Value>, StatusCode> {
+        stub.jwks_hits.fetch_add(1, Ordering::SeqCst);
+        if stub.failing.load(Ordering::SeqCst) {
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Ok(Json(json!({ "keys": *stub.keys.lock().unwrap() })))
+    }
+
+    fn build_req_with_bearer(token: &str) -> Request<Body> {
+        Request::builder()
+            .uri("/users/me")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_accept_tokens_of_every_trusted_issuer() {
+        let (first, second, untrusted) = (StubIssuer::start().await, StubIssuer::start().await, StubIssuer::start().await);
+        let user_repository = UserRepositoryForMemory::new();
+        for issuer in [&first, &second] {
+            user_repository
+                .create(CreateUser::new(issuer.subject(), "oidc_user".to_string(), "oidc@example.com".to_string()))
+                .await
+                .unwrap();
+        }
+        let app = create_app(
+            LabelRepositoryForMemory::new(),
+            WorkspaceRepositoryForMemory::new(vec![]),
+            TodoRepositoryForMemory::new(vec![]),
+            user_repository,
+            ActivityRepositoryForMemory::new(),
+            EventRepositoryForMemory::new(),
+            OidcVerifier::new(vec![first.trusted(), second.trusted()]),
+            Arc::new(MockLlm::default()),
+        );
+
+        for issuer in [&first, &second] {
+            for _ in 0..2 {
+                let res = app.clone().oneshot(build_req_with_bearer(&issuer.sign(KEY_1, AUDIENCE, 300))).await.unwrap();
+                assert_eq!(StatusCode::OK, res.status());
+            }
+            // discovered once, then served from the cache
+            assert_eq!((1, 1), issuer.hits());
+        }
+
+        for token in [
+            untrusted.sign(KEY_1, AUDIENCE, 300),
+            first.sign(KEY_1, "https://someone-else.test", 300),
+            first.sign(KEY_1, AUDIENCE, -300),
+            // signed with a key the issuer never published
+            first.sign(("key-1", KEY_2.1, KEY_2.2), AUDIENCE, 300),
+        ] {
+            let res = app.clone().oneshot(build_req_with_bearer(&token)).await.unwrap();
+            assert_eq!(StatusCode::UNAUTHORIZED, res.status());
+        }
+        assert_eq!((0, 0), untrusted.hits());
+    }
+
+    #[tokio::test]
+    async fn should_keep_users_of_issuers_apart() {
+        let (first, second) = (StubIssuer::start().await, StubIssuer::start().await);
+        let user_repository = UserRepositoryForMemory::new();
+        user_repository
+            .create(CreateUser::new(first.subject(), "first_user".to_string(), "first@example.com".to_string()))
+            .await
+            .unwrap();
+        let app = create_app(
+            LabelRepositoryForMemory::new(),
+            WorkspaceRepositoryForMemory::new(vec![]),
+            TodoRepositoryForMemory::new(vec![]),
+            user_repository,
+            ActivityRepositoryForMemory::new(),
+            EventRepositoryForMemory::new(),
+            OidcVerifier::new(vec![first.trusted(), second.trusted()]),
+            Arc::new(MockLlm::default()),
+        );
+
+        let res = app.clone().oneshot(build_req_with_bearer(&first.sign(KEY_1, AUDIENCE, 300))).await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        // the same `sub` from another issuer is somebody else
+        let res = app.oneshot(build_req_with_bearer(&second.sign(KEY_1, AUDIENCE, 300))).await.unwrap();
+        assert_eq!(StatusCode::NOT_FOUND, res.status());
+
+        let verifier = OidcVerifier::new(vec![first.trusted(), second.trusted().with_bare_subjects()]);
+        assert_eq!(first.subject(), verifier.verify(&first.sign(KEY_1, AUDIENCE, 300)).await.unwrap().subject);
+        assert_eq!(SUB, verifier.verify(&second.sign(KEY_1, AUDIENCE, 300)).await.unwrap().subject);
+    }
+
+    #[tokio::test]
+    async fn should_refresh_keys_by_ttl_and_rate_limit_unknown_kids() {
+        let issuer = StubIssuer::start().await;
+        let verifier = OidcVerifier::new(vec![issuer.trusted()]).refetch_interval(Duration::from_millis(200));
+
+        assert_eq!(SUB, verifier.verify(&issuer.sign(KEY_1, AUDIENCE, 300)).await.unwrap().sub);
+        assert_eq!((1, 1), issuer.hits());
+
+        // a rotated key right after a fetch has to wait for the interval
+        issuer.publish(KEY_2);
+        let rotated = issuer.sign(KEY_2, AUDIENCE, 300);
+        assert!(matches!(verifier.verify(&rotated).await, Err(AppError::Unauthorized)));
+        assert_eq!((1, 1), issuer.hits());
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(verifier.verify(&rotated).await.is_ok());
+        assert_eq!((1, 2), issuer.hits());
+
+        // made up kids don't get to hammer the issuer
+        let made_up = issuer.sign(("key-3", KEY_1.1, KEY_1.2), AUDIENCE, 300);
+        for _ in 0..3 {
+            assert!(matches!(verifier.verify(&made_up).await, Err(AppError::Unauthorized)));
+        }
+        assert_eq!((1, 2), issuer.hits());
+
+        // once the keys are stale they are fetched again, discovery isn't repeated
+        let verifier = OidcVerifier::new(vec![issuer.trusted()])
+            .jwks_ttl(Duration::ZERO)
+            .refetch_interval(Duration::ZERO);
+        for _ in 0..2 {
+            assert!(verifier.verify(&issuer.sign(KEY_1, AUDIENCE, 300)).await.is_ok());
+        }
+        assert_eq!((2, 4), issuer.hits());
+    }
+
+    #[tokio::test]
+    async fn should_rate_limit_refreshes_while_the_issuer_is_down() {
+        let issuer = StubIssuer::start().await;
+        let verifier = OidcVerifier::new(vec![issuer.trusted()])
+            .jwks_ttl(Duration::from_millis(100))
+            .refetch_interval(Duration::from_millis(300));
+        let token = issuer.sign(KEY_1, AUDIENCE, 300);
+        assert!(verifier.verify(&token).await.is_ok());
+        assert_eq!((1, 1), issuer.hits());
+
+        // stale keys keep working, the failed refresh isn't retried on every request
+        issuer.failing.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        for _ in 0..5 {
+            assert!(verifier.verify(&token).await.is_ok());
+        }
+        assert_eq!((1, 2), issuer.hits());
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        for _ in 0..5 {
+            assert!(verifier.verify(&token).await.is_ok());
+        }
+        assert_eq!((1, 3), issuer.hits());
+
+        // once it is back the keys are fresh again and it is left alone until they go stale
+        issuer.failing.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        for _ in 0..5 {
+            assert!(verifier.verify(&token).await.is_ok());
+        }
+        assert_eq!((1, 4), issuer.hits());
+    }
+}
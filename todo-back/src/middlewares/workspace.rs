@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+use crate::{
+    AppState,
+    errors::AppError,
+    models::{user::User, workspace::WorkspaceRole},
+};
+use super::auth::AuthenticatedUser;
+
+/// The caller's membership in the workspace addressed by the `{id}` path segment.
+///
+/// Non-members are rejected with 403, and so are viewers on anything but a safe
+/// (read-only) method, so handlers only need to check rules beyond that.
+#[derive(Debug)]
+pub struct WorkspaceAccess {
+    pub user: User,
+    pub workspace_id: i32,
+    pub role: WorkspaceRole,
+}
+
+impl FromRequestParts<AppState> for WorkspaceAccess {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthenticatedUser::from_request_parts(parts, state).await?;
+
+        let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| AppError::BadRequest(rejection.body_text()))?;
+        let workspace_id = params
+            .get("id")
+            .and_then(|id| id.parse::<i32>().ok())
+            .ok_or(AppError::BadRequest("Invalid workspace id".to_string()))?;
+
+        let user = state.user_repository
+            .find_by_sub(auth_user.sub)
+            .await?;
+
+        let role = state.workspace_repository
+            .find_role(workspace_id, user.id)
+            .await?
+            .ok_or(AppError::Forbidden("Not a member of this workspace".to_string()))?;
+
+        if !parts.method.is_safe() && !role.can_write() {
+            return Err(AppError::Forbidden("Viewers can not change this workspace".to_string()));
+        }
+
+        Ok(WorkspaceAccess {
+            user,
+            workspace_id,
+            role,
+        })
+    }
+}
@@ -1,4 +1,13 @@
 pub mod label;
 pub mod workspace;
 pub mod todo;
-pub mod user;
\ No newline at end of file
+pub mod user;
+pub mod search;
+pub mod activity;
+pub mod event;
+pub mod calendar;
+pub mod transfer;
+pub mod access_token;
+pub mod recommendation;
+pub mod quick_add;
+pub mod status;
\ No newline at end of file
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+/// Starts every personal access token, it tells them apart from JWTs in the Authorization header.
+pub const ACCESS_TOKEN_PREFIX: &str = "tdp_";
+/// How much of a token is kept in the clear, enough to tell tokens apart in a list.
+pub const TOKEN_HINT_LENGTH: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    Read,
+    Write,
+}
+
+/// A token scripts send instead of a JWT, it acts as the user who created it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub token_hint: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    /// Only handed out when the token is created, just its hash is stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl AccessToken {
+    pub fn can_write(&self) -> bool {
+        self.scopes.contains(&TokenScope::Write)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_create_access_token"))]
+pub struct CreateAccessToken {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    pub name: String,
+    /// Read only unless asked for more.
+    #[serde(default = "default_scopes")]
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    pub scopes: Vec<TokenScope>,
+    /// Tokens without an expiry stay valid until revoked.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CreateAccessToken {
+    pub fn new(name: String, scopes: Vec<TokenScope>, expires_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            name,
+            scopes,
+            expires_at,
+        }
+    }
+}
+
+fn default_scopes() -> Vec<TokenScope> {
+    vec![TokenScope::Read]
+}
+
+fn validate_create_access_token(payload: &CreateAccessToken) -> Result<(), ValidationError> {
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ValidationError {
+            message: Some("expires_at must be in the future".into()),
+            ..ValidationError::new("expires_in_past")
+        });
+    }
+    Ok(())
+}
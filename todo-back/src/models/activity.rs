@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+pub const DEFAULT_ACTIVITY_LIMIT: i64 = 50;
+pub const MAX_ACTIVITY_LIMIT: i64 = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "activity_target", rename_all = "lowercase")]
+pub enum ActivityTarget {
+    Todo,
+    Label,
+    Workspace,
+    Status,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "activity_action", rename_all = "lowercase")]
+pub enum ActivityAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, FromRow)]
+pub struct ActivityEntity {
+    pub id: i64,
+    pub workspace_id: Option<i32>,
+    pub actor_id: Option<i32>,
+    pub target: ActivityTarget,
+    pub target_id: i32,
+    pub action: ActivityAction,
+    /// Snapshot of the target before the change, `None` when it was created.
+    pub before: Option<Value>,
+    /// Snapshot of the target after the change, `None` when it was deleted.
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewActivity {
+    pub workspace_id: Option<i32>,
+    pub actor_id: i32,
+    pub target: ActivityTarget,
+    pub target_id: i32,
+    pub action: ActivityAction,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl NewActivity {
+    /// Builds the event from the snapshots around a change, the action follows from which side is missing.
+    pub fn new<T: Serialize>(
+        workspace_id: Option<i32>,
+        actor_id: i32,
+        target: ActivityTarget,
+        target_id: i32,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Self {
+        let action = match (before, after) {
+            (None, _) => ActivityAction::Create,
+            (Some(_), Some(_)) => ActivityAction::Update,
+            (Some(_), None) => ActivityAction::Delete,
+        };
+        Self {
+            workspace_id,
+            actor_id,
+            target,
+            target_id,
+            action,
+            before: before.map(|value| serde_json::to_value(value).unwrap()),
+            after: after.map(|value| serde_json::to_value(value).unwrap()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone, PartialEq, Eq)]
+pub struct ActivityQuery {
+    /// Only events older than this id, taken from `next_cursor` of the previous page.
+    pub cursor: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl ActivityQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).clamp(1, MAX_ACTIVITY_LIMIT)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ActivityPage {
+    pub items: Vec<ActivityEntity>,
+    pub next_cursor: Option<i64>,
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The secret feed URL of a user, calendar clients poll it without signing in.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CalendarFeed {
+    pub created_at: DateTime<Utc>,
+    /// Only handed out when the feed is created, just its hash is stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Calendar apps without task support only show events, so todos can be exported as either.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarComponent {
+    #[default]
+    Todo,
+    Event,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarQuery {
+    #[serde(default)]
+    pub component: CalendarComponent,
+}
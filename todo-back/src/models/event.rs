@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use super::activity::ActivityAction;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventTarget {
+    Todo,
+    Label,
+    Member,
+    Status,
+}
+
+impl EventTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventTarget::Todo => "todo",
+            EventTarget::Label => "label",
+            EventTarget::Member => "member",
+            EventTarget::Status => "status",
+        }
+    }
+}
+
+/// Tells subscribers of a workspace what changed, they fetch the current state themselves.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WorkspaceEvent {
+    pub workspace_id: i32,
+    pub target: EventTarget,
+    pub target_id: i32,
+    pub action: ActivityAction,
+    pub actor_id: i32,
+}
+
+impl WorkspaceEvent {
+    pub fn new(workspace_id: i32, target: EventTarget, target_id: i32, action: ActivityAction, actor_id: i32) -> Self {
+        Self {
+            workspace_id,
+            target,
+            target_id,
+            action,
+            actor_id,
+        }
+    }
+
+    /// The event that ends the stream of `user_id`, who is no longer a member.
+    pub fn removes(&self, user_id: i32) -> bool {
+        self.target == EventTarget::Member && self.action == ActivityAction::Delete && self.target_id == user_id
+    }
+}
@@ -1,12 +1,18 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+use super::todo::double_option;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
 pub struct Label {
     pub id: i32,
     pub name: String,
     pub user_id: i32,
+    /// Shared with everybody in the workspace when set, otherwise only `user_id` sees it.
+    pub workspace_id: Option<i32>,
+    /// `#rrggbb`
+    pub color: Option<String>,
+    pub icon: Option<String>,
 }
 
 impl Label {
@@ -15,27 +21,77 @@ impl Label {
             id,
             name,
             user_id,
+            workspace_id: None,
+            color: None,
+            icon: None,
         }
     }
+
+    pub fn in_workspace(mut self, workspace_id: i32) -> Self {
+        self.workspace_id = Some(workspace_id);
+        self
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Validate)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Validate)]
 pub struct CreateLabel {
     #[validate(length(min = 1, message = "Can not be empty"))]
     #[validate(length(max = 100, message = "Over text length"))]
     pub name: String,
+    #[serde(default)]
+    #[validate(custom = "validate_color")]
+    pub color: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 1, max = 32))]
+    pub icon: Option<String>,
 }
 
 impl CreateLabel {
     pub fn new(name: String) -> Self {
         Self {
             name,
+            color: None,
+            icon: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, sqlx::FromRow)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
 pub struct UpdateLabel {
-    pub id: i32,
-    pub name: String,
-}
\ No newline at end of file
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    pub name: Option<String>,
+    // `None` leaves the value untouched, `Some(None)` (an explicit null) clears it.
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(custom = "validate_color")]
+    pub color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(length(min = 1, max = 32))]
+    pub icon: Option<Option<String>>,
+}
+
+/// Retags the todos of the label in the path with `into`, the label itself goes away.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Validate)]
+pub struct MergeLabel {
+    pub into: i32,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteLabelQuery {
+    /// Takes the label off its todos first, without it a label still in use is not deleted.
+    #[serde(default)]
+    pub detach: bool,
+}
+
+fn validate_color(color: &str) -> Result<(), ValidationError> {
+    let is_hex = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !is_hex {
+        return Err(ValidationError {
+            message: Some("color must be a #rrggbb code".into()),
+            ..ValidationError::new("invalid_color")
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use super::todo::{Priority, Recurrence};
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq, Validate)]
+pub struct QuickAddQuery {
+    /// Reads due date, labels, priority and recurrence out of the text before the todo is created.
+    #[serde(default)]
+    pub parse: bool,
+    /// Offset from UTC in minutes in which "tomorrow" or "9am" are meant.
+    #[validate(range(min = -840, max = 840))]
+    pub tz_offset: Option<i32>,
+}
+
+impl QuickAddQuery {
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        let offset = self
+            .tz_offset
+            .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+            .unwrap_or(FixedOffset::east_opt(0).unwrap());
+        Utc::now().with_timezone(&offset)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct ParseTodo {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over text length"))]
+    pub text: String,
+}
+
+/// A `#label` of the text, `id` is missing for labels that would be created.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LabelMatch {
+    pub name: String,
+    pub id: Option<i32>,
+}
+
+/// What creating a todo from the text would store, nothing is saved to produce it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct QuickAddPreview {
+    pub text: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub labels: Vec<LabelMatch>,
+    pub priority: Option<Priority>,
+    pub recurrence: Option<Recurrence>,
+}
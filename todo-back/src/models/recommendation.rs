@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+/// How long a recommendation can be accepted or rejected after it was made.
+pub const RECOMMENDATION_TTL_MINUTES: i64 = 30;
+/// How many dismissed recommendations are shown to the model so it doesn't suggest them again.
+pub const REJECTED_IN_PROMPT: i64 = 20;
+/// How many open todos are shown to the model as the current list, taken in list order.
+/// Fits in a single page, see `MAX_PAGE_SIZE`.
+pub const TODOS_IN_PROMPT: i64 = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "recommendation_status", rename_all = "lowercase")]
+pub enum RecommendationStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
+pub struct Recommendation {
+    pub id: i32,
+    pub workspace_id: i32,
+    /// The member it was made for, the only one who answers it.
+    pub user_id: i32,
+    pub text: String,
+    pub status: RecommendationStatus,
+    /// The todo an accepted recommendation became.
+    pub todo_id: Option<i32>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Recommendation {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct RecommendationIds {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    pub ids: Vec<i32>,
+}
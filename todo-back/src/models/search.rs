@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use super::label::Label;
+
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+pub const MAX_SEARCH_LIMIT: i64 = 100;
+
+#[derive(Debug, Default, Deserialize, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+impl SearchQuery {
+    pub fn terms(&self) -> Vec<String> {
+        self.q.split_whitespace().map(str::to_lowercase).collect()
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchHit {
+    pub id: i32,
+    pub text: String,
+    pub completed: bool,
+    pub labels: Vec<Label>,
+    pub workspace_id: i32,
+    /// `text`, HTML-escaped, with the matched fragments wrapped in `<mark>` tags.
+    pub highlight: String,
+    pub rank: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WorkspaceSearchResult {
+    pub workspace_id: i32,
+    pub workspace_name: String,
+    pub hits: Vec<SearchHit>,
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+use super::todo::{double_option, TodoPage};
+
+/// Columns a new workspace starts with, in board order, and whether they are terminal.
+pub const DEFAULT_STATUSES: [(&str, bool); 4] = [("backlog", false), ("doing", false), ("review", false), ("done", true)];
+
+/// A column of the workspace's board, todos in a terminal one count as completed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
+pub struct Status {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub name: String,
+    /// Place on the board, from left to right.
+    pub position: i32,
+    /// How many top level todos the column takes at most, no limit when unset.
+    pub wip_limit: Option<i32>,
+    pub is_terminal: bool,
+}
+
+impl Status {
+    pub fn new(id: i32, workspace_id: i32, name: String, position: i32, is_terminal: bool) -> Self {
+        Self {
+            id,
+            workspace_id,
+            name,
+            position,
+            wip_limit: None,
+            is_terminal,
+        }
+    }
+
+    /// Whether the column is over its WIP limit holding `count` todos.
+    pub fn is_over_limit(&self, count: usize) -> bool {
+        self.wip_limit.is_some_and(|limit| count > limit as usize)
+    }
+}
+
+/// The leftmost of the `statuses` that is terminal or not, where todos go when completed or reopened.
+pub fn first_of_kind(statuses: &[Status], is_terminal: bool) -> Option<&Status> {
+    statuses
+        .iter()
+        .filter(|status| status.is_terminal == is_terminal)
+        .min_by_key(|status| (status.position, status.id))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct CreateStatus {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 50, message = "Over text length"))]
+    pub name: String,
+    #[serde(default)]
+    #[validate(range(min = 1))]
+    pub wip_limit: Option<i32>,
+    #[serde(default)]
+    pub is_terminal: bool,
+}
+
+impl CreateStatus {
+    pub fn new(name: String, is_terminal: bool) -> Self {
+        Self {
+            name,
+            wip_limit: None,
+            is_terminal,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct UpdateStatus {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 50, message = "Over text length"))]
+    pub name: Option<String>,
+    // `None` leaves the value untouched, `Some(None)` (an explicit null) clears it.
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(range(min = 1))]
+    pub wip_limit: Option<Option<i32>>,
+    pub is_terminal: Option<bool>,
+    /// Index on the board to move the column to, the other columns shift over.
+    #[validate(range(min = 0))]
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteStatusQuery {
+    /// Column the todos still in the deleted one go to, without it a column holding todos is not deleted.
+    pub move_to: Option<i32>,
+}
+
+/// A column of the board with the first page of its top level todos.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BoardColumn {
+    pub status: Status,
+    pub todos: TodoPage,
+}
\ No newline at end of file
@@ -1,17 +1,38 @@
-use validator::Validate;
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use validator::{Validate, ValidationError};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use super::{
-    label::Label,
+    label::{CreateLabel, Label},
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct TodoEntity {
     pub id: i32,
     pub text: String,
+    /// Follows the status, true while the todo sits in a terminal column.
     pub completed: bool,
+    pub status_id: i32,
     pub labels: Vec<Label>,
+    /// Creator of the todo, the members responsible for it are the assignees.
     pub user_id: i32,
+    pub assignee_ids: Vec<i32>,
     pub workspace_id: i32,
+    pub due_at: Option<DateTime<Utc>>,
+    pub start_at: Option<DateTime<Utc>>,
+    pub parent_id: Option<i32>,
+    /// Keeps `completed` in sync with the children: done once all of them are.
+    pub auto_complete: bool,
+    pub children: Vec<TodoEntity>,
+    pub progress: TodoProgress,
+    /// Set while the todo sits in the trash, children trashed along with it share the timestamp.
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+    /// Id of the first occurrence, shared by every occurrence of a recurring todo.
+    pub series_id: Option<i32>,
+    pub priority: Option<Priority>,
+    /// Rank among the todos sharing its parent, lists go by it unless sorted otherwise.
+    pub position: String,
 }
 
 impl TodoEntity {
@@ -20,19 +41,244 @@ impl TodoEntity {
             id,
             text,
             completed: false,
+            status_id: 0,
             labels,
             user_id,
+            assignee_ids: vec![],
             workspace_id,
+            due_at: None,
+            start_at: None,
+            parent_id: None,
+            auto_complete: false,
+            children: vec![],
+            progress: TodoProgress::default(),
+            deleted_at: None,
+            recurrence: None,
+            series_id: None,
+            priority: None,
+            position: String::new(),
         }
     }
+
+    /// Whether `other` is another todo of the same list, the ones it can be placed next to.
+    pub fn is_sibling(&self, other: &TodoEntity) -> bool {
+        self.id != other.id
+            && self.workspace_id == other.workspace_id
+            && self.parent_id == other.parent_id
+            && other.deleted_at.is_none()
+    }
+
+    pub fn with_children(self, children: Vec<TodoEntity>) -> Self {
+        let progress = TodoProgress {
+            completed: children.iter().filter(|child| child.completed).count(),
+            total: children.len(),
+        };
+        Self {
+            children,
+            progress,
+            ..self
+        }
+    }
+
+    /// The occurrence following this one of a recurring todo, `None` once the series has ended.
+    /// Subtasks stay with the occurrence they were added to.
+    pub fn next_occurrence(&self) -> Option<CreateTodo> {
+        let recurrence = self.recurrence.clone()?;
+        let due_at = self.due_at.unwrap_or_else(Utc::now);
+        let next_due_at = recurrence.next_after(due_at)?;
+        Some(CreateTodo {
+            text: self.text.clone(),
+            label_ids: self.labels.iter().map(|label| label.id).collect(),
+            assignee_ids: self.assignee_ids.clone(),
+            due_at: Some(next_due_at),
+            start_at: self.start_at.map(|start_at| start_at + (next_due_at - due_at)),
+            parent_id: None,
+            auto_complete: self.auto_complete,
+            recurrence: Some(recurrence),
+            priority: self.priority,
+            status_id: None,
+            new_labels: vec![],
+            completed: false,
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TodoProgress {
+    pub completed: usize,
+    pub total: usize,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "todo_priority", rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// When the next occurrence of a recurring todo is due, a subset of an iCalendar RRULE.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_recurrence"))]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Every how many days, weeks or months.
+    #[serde(default = "default_interval")]
+    #[validate(range(min = 1, max = 365))]
+    pub interval: u32,
+    /// Weekly only, the weekday of the current occurrence when empty.
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    /// Monthly only, the day the series is first due on when missing. Shorter months use their last day.
+    #[serde(default)]
+    #[validate(range(min = 1, max = 31))]
+    pub month_day: Option<u32>,
+    /// No occurrence is generated past this point.
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    /// Offset from UTC in minutes in which weekdays and days of the month are counted.
+    #[serde(default)]
+    #[validate(range(min = -840, max = 840))]
+    pub tz_offset: i32,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+fn validate_recurrence(recurrence: &Recurrence) -> Result<(), ValidationError> {
+    if !recurrence.weekdays.is_empty() && recurrence.frequency != Frequency::Weekly {
+        return Err(ValidationError {
+            message: Some("weekdays only apply to weekly recurrences".into()),
+            ..ValidationError::new("weekdays_not_weekly")
+        });
+    }
+    if recurrence.month_day.is_some() && recurrence.frequency != Frequency::Monthly {
+        return Err(ValidationError {
+            message: Some("month_day only applies to monthly recurrences".into()),
+            ..ValidationError::new("month_day_not_monthly")
+        });
+    }
+    Ok(())
+}
+
+impl Recurrence {
+    /// Pins a monthly rule without `month_day` to the day the series is first due,
+    /// so that a month too short for it doesn't move every later occurrence.
+    pub fn anchored(mut self, due_at: Option<DateTime<Utc>>) -> Self {
+        if self.frequency == Frequency::Monthly && self.month_day.is_none() {
+            let offset = FixedOffset::east_opt(self.tz_offset * 60);
+            self.month_day = due_at.zip(offset).map(|(due_at, offset)| due_at.with_timezone(&offset).day());
+        }
+        self
+    }
+
+    /// Returns when the occurrence following one due `at` is due, keeping its time of day.
+    /// `None` once the rule has run past `until`.
+    pub fn next_after(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let offset = FixedOffset::east_opt(self.tz_offset * 60)?;
+        let local = at.with_timezone(&offset);
+        let date = local.date_naive();
+        let interval = self.interval.max(1);
+        let next_date = match self.frequency {
+            Frequency::Daily => Some(date + Duration::days(interval as i64)),
+            Frequency::Weekly => {
+                let weekdays = if self.weekdays.is_empty() {
+                    vec![date.weekday()]
+                } else {
+                    self.weekdays.clone()
+                };
+                // weeks start on Monday, only every `interval`th week counting from this one qualifies
+                let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (1..=7 * (interval as i64 + 1))
+                    .map(|days| date + Duration::days(days))
+                    .find(|candidate| {
+                        ((*candidate - week_start).num_days() / 7) % interval as i64 == 0
+                            && weekdays.contains(&candidate.weekday())
+                    })
+            }
+            Frequency::Monthly => {
+                let day = self.month_day.unwrap_or(date.day());
+                let first_of_month = date.with_day(1)?;
+                [0, interval]
+                    .into_iter()
+                    .filter_map(|months| first_of_month.checked_add_months(Months::new(months)))
+                    .filter_map(|first| first.with_day(day.min(days_in_month(first)?)))
+                    .find(|candidate| *candidate > date)
+            }
+        }?;
+        let next = offset
+            .from_local_datetime(&next_date.and_time(local.time()))
+            .single()?
+            .with_timezone(&Utc);
+        self.until.is_none_or(|until| next <= until).then_some(next)
+    }
+}
+
+fn days_in_month(first_of_month: NaiveDate) -> Option<u32> {
+    first_of_month
+        .checked_add_months(Months::new(1))?
+        .pred_opt()
+        .map(|last| last.day())
+}
+
+/// Which occurrences of a recurring todo an edit or a deletion applies to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesScope {
+    #[default]
+    This,
+    Future,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SeriesQuery {
+    #[serde(default)]
+    pub scope: SeriesScope,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_create_todo"))]
 pub struct CreateTodo {
     #[validate(length(min = 1, message = "Can not be empty"))]
     #[validate(length(max = 100, message = "Over text length"))]
     pub text: String,
     pub label_ids: Vec<i32>,
+    /// Members of the workspace responsible for the todo.
+    #[serde(default)]
+    pub assignee_ids: Vec<i32>,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub start_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+    #[serde(default)]
+    pub auto_complete: bool,
+    #[serde(default)]
+    #[validate]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// Column to start in, the first open one of the workspace when missing.
+    #[serde(default)]
+    pub status_id: Option<i32>,
+    /// Labels a quick add names that the workspace doesn't have yet, created along with the todo.
+    #[serde(skip)]
+    pub new_labels: Vec<CreateLabel>,
+    /// Starts the todo in the leftmost terminal column instead of an open one when no status is given,
+    /// for imported todos that were already done.
+    #[serde(skip)]
+    pub completed: bool,
 }
 
 impl CreateTodo {
@@ -40,20 +286,519 @@ impl CreateTodo {
         Self {
             text,
             label_ids,
+            assignee_ids: vec![],
+            due_at: None,
+            start_at: None,
+            parent_id: None,
+            auto_complete: false,
+            recurrence: None,
+            priority: None,
+            status_id: None,
+            new_labels: vec![],
+            completed: false,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
-pub struct RecommendedTodo {
-    pub text: String,
+fn validate_create_todo(payload: &CreateTodo) -> Result<(), ValidationError> {
+    // the next occurrence is scheduled from the due date
+    if payload.recurrence.is_some() && payload.due_at.is_none() {
+        return Err(ValidationError {
+            message: Some("recurrence requires due_at".into()),
+            ..ValidationError::new("recurrence_without_due")
+        });
+    }
+    validate_dates(payload.start_at, payload.due_at)
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_update_todo_dates"))]
 pub struct UpdateTodo {
     #[validate(length(min = 1, message = "Can not be empty"))]
     #[validate(length(max = 100, message = "Over text length"))]
     pub text: Option<String>,
+    /// Moves the todo to the first terminal or open column, unless `status_id` is given too.
     pub completed: Option<bool>,
+    pub status_id: Option<i32>,
     pub label_ids: Option<Vec<i32>>,
-}
\ No newline at end of file
+    /// Replaces the assignees, an empty list unassigns everybody.
+    pub assignee_ids: Option<Vec<i32>>,
+    // `None` leaves the value untouched, `Some(None)` (an explicit null) clears it.
+    #[serde(default, deserialize_with = "double_option")]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub start_at: Option<Option<DateTime<Utc>>>,
+    pub auto_complete: Option<bool>,
+    /// An explicit null stops the todo from repeating.
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate]
+    pub recurrence: Option<Option<Recurrence>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub priority: Option<Option<Priority>>,
+}
+
+impl UpdateTodo {
+    /// Checks the dates the todo ends up with, the payload alone may carry just one of them.
+    pub fn validate_dates_of(&self, todo: &TodoEntity) -> Result<(), ValidationError> {
+        validate_dates(self.start_at.unwrap_or(todo.start_at), self.due_at.unwrap_or(todo.due_at))
+    }
+}
+
+fn validate_update_todo_dates(payload: &UpdateTodo) -> Result<(), ValidationError> {
+    match (payload.start_at, payload.due_at) {
+        (Some(start_at), Some(due_at)) => validate_dates(start_at, due_at),
+        _ => Ok(()),
+    }
+}
+
+fn validate_dates(
+    start_at: Option<DateTime<Utc>>,
+    due_at: Option<DateTime<Utc>>,
+) -> Result<(), ValidationError> {
+    match (start_at, due_at) {
+        (Some(start_at), Some(due_at)) if start_at > due_at => Err(ValidationError {
+            message: Some("start_at must not be after due_at".into()),
+            ..ValidationError::new("start_after_due")
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Places a todo right before or right after one of its siblings, exactly one of the two is set.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_move_todo"))]
+pub struct MoveTodo {
+    #[serde(default)]
+    pub before: Option<i32>,
+    #[serde(default)]
+    pub after: Option<i32>,
+}
+
+impl MoveTodo {
+    pub fn anchor_id(&self) -> i32 {
+        self.before.or(self.after).unwrap_or_default()
+    }
+}
+
+fn validate_move_todo(payload: &MoveTodo) -> Result<(), ValidationError> {
+    if payload.before.is_some() == payload.after.is_some() {
+        return Err(ValidationError {
+            message: Some("exactly one of before and after is required".into()),
+            ..ValidationError::new("before_or_after_required")
+        });
+    }
+    Ok(())
+}
+
+const RANK_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+// the leading digits are counted up and down at either end of a list, the rest only splits gaps
+const RANK_HEAD: u32 = 6;
+
+/// A position sorting byte-wise between `before` and `after`, a missing side is the end of the list.
+/// Positions never end in `0`, so there is always room for another one between two of them.
+pub fn rank_between(before: Option<&str>, after: Option<&str>) -> String {
+    match (before, after) {
+        (None, None) => rank_from_head(RANK_DIGITS.len().pow(RANK_HEAD) as i64 / 2),
+        (Some(before), None) => step_rank(before, 1).unwrap_or_else(|| split_ranks(before, None)),
+        (None, Some(after)) => step_rank(after, -1).unwrap_or_else(|| split_ranks("", Some(after))),
+        (Some(before), Some(after)) => split_ranks(before, Some(after)),
+    }
+}
+
+fn rank_digit(c: u8) -> i64 {
+    RANK_DIGITS.iter().position(|digit| *digit == c).unwrap_or_default() as i64
+}
+
+fn rank_from_head(head: i64) -> String {
+    let base = RANK_DIGITS.len() as i64;
+    let mut digits: Vec<u8> = (0..RANK_HEAD)
+        .scan(head, |rest, _| {
+            let digit = RANK_DIGITS[(*rest % base) as usize];
+            *rest /= base;
+            Some(digit)
+        })
+        .collect();
+    digits.reverse();
+    digits.push(b'i');
+    String::from_utf8(digits).unwrap()
+}
+
+// Counts the head of `rank` up or down by one, `None` once the head runs out of room.
+fn step_rank(rank: &str, by: i64) -> Option<String> {
+    let base = RANK_DIGITS.len() as i64;
+    let head = rank
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(RANK_HEAD as usize)
+        .fold(0, |head, c| head * base + rank_digit(c));
+    let head = head + by;
+    (0..base.pow(RANK_HEAD)).contains(&head).then(|| rank_from_head(head))
+}
+
+// Digit by digit, follows `before` until there is a digit left between it and `after`.
+fn split_ranks(before: &str, after: Option<&str>) -> String {
+    let base = RANK_DIGITS.len() as i64;
+    let (before, after) = (before.as_bytes(), after.map(str::as_bytes));
+    let mut bounded = after.is_some();
+    let mut rank = vec![];
+    for i in 0.. {
+        let low = before.get(i).map_or(0, |c| rank_digit(*c));
+        let high = match after {
+            Some(after) if bounded => after.get(i).map_or(base, |c| rank_digit(*c)),
+            _ => base,
+        };
+        if high - low >= 2 {
+            rank.push(RANK_DIGITS[((low + high) / 2) as usize]);
+            break;
+        }
+        rank.push(RANK_DIGITS[low as usize]);
+        if low < high {
+            bounded = false;
+        }
+    }
+    String::from_utf8(rank).unwrap()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DueFilter {
+    Overdue,
+    Today,
+    ThisWeek,
+}
+
+impl DueFilter {
+    /// Returns the `[from, to)` range of `due_at` matching this filter.
+    /// Day and week boundaries are taken in the caller's UTC offset, weeks start on Monday.
+    pub fn bounds(&self, now: DateTime<FixedOffset>) -> (Option<DateTime<Utc>>, DateTime<Utc>) {
+        let start_of_today = now
+            .timezone()
+            .from_local_datetime(&now.date_naive().and_time(NaiveTime::MIN))
+            .unwrap();
+        match self {
+            DueFilter::Overdue => (None, now.with_timezone(&Utc)),
+            DueFilter::Today => (
+                Some(start_of_today.with_timezone(&Utc)),
+                (start_of_today + Duration::days(1)).with_timezone(&Utc),
+            ),
+            DueFilter::ThisWeek => {
+                let days_from_monday = now.weekday().num_days_from_monday() as i64;
+                let start_of_week = start_of_today - Duration::days(days_from_monday);
+                (
+                    Some(start_of_week.with_timezone(&Utc)),
+                    (start_of_week + Duration::weeks(1)).with_timezone(&Utc),
+                )
+            }
+        }
+    }
+
+    /// Overdue only makes sense for todos that are still open.
+    pub fn excludes_completed(&self) -> bool {
+        matches!(self, DueFilter::Overdue)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoSort {
+    /// The order the todos were arranged in by hand.
+    #[default]
+    Position,
+    Created,
+    DueAt,
+    Text,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Position right after the last todo of a page, handed back to clients as an opaque string.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TodoCursor {
+    pub id: i32,
+    pub due_at: Option<DateTime<Utc>>,
+    pub text: String,
+    #[serde(default)]
+    pub position: String,
+}
+
+impl TodoCursor {
+    pub fn after(todo: &TodoEntity) -> Self {
+        Self {
+            id: todo.id,
+            due_at: todo.due_at,
+            text: todo.text.clone(),
+            position: todo.position.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap())
+    }
+
+    pub fn decode(cursor: &str) -> anyhow::Result<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// How long trashed todos are kept before the background purge deletes them for good.
+pub const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Default, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct TodoQuery {
+    pub due: Option<DueFilter>,
+    /// Offset from UTC in minutes used to decide where "today" and "this week" start.
+    #[validate(range(min = -840, max = 840))]
+    pub tz_offset: Option<i32>,
+    pub completed: Option<bool>,
+    pub status_id: Option<i32>,
+    /// Comma separated, a todo has to carry every listed label.
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub label_ids: Vec<i32>,
+    pub user_id: Option<i32>,
+    pub assignee_id: Option<i32>,
+    /// `false` lists the todos nobody is assigned to.
+    pub assigned: Option<bool>,
+    pub q: Option<String>,
+    #[serde(default)]
+    pub sort: TodoSort,
+    pub order: Option<SortOrder>,
+    #[serde(default, deserialize_with = "cursor")]
+    pub cursor: Option<TodoCursor>,
+    pub limit: Option<i64>,
+}
+
+impl TodoQuery {
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        let offset = self
+            .tz_offset
+            .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+            .unwrap_or(FixedOffset::east_opt(0).unwrap());
+        Utc::now().with_timezone(&offset)
+    }
+
+    /// Newest first when sorting by creation, top/soonest/alphabetical first otherwise.
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or(match self.sort {
+            TodoSort::Created => SortOrder::Desc,
+            TodoSort::Position | TodoSort::DueAt | TodoSort::Text => SortOrder::Asc,
+        })
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn search_text(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+}
+
+fn comma_separated<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let mut ids = value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| id.parse::<i32>().map_err(de::Error::custom))
+        .collect::<Result<Vec<_>, _>>()?;
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(ids)
+}
+
+fn cursor<'de, D>(deserializer: D) -> Result<Option<TodoCursor>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    TodoCursor::decode(&value)
+        .map(Some)
+        .map_err(|_| de::Error::custom("invalid cursor"))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TodoPage {
+    pub items: Vec<TodoEntity>,
+    pub next_cursor: Option<String>,
+    pub total: i64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const JST: i32 = 9 * 60;
+
+    fn rule(frequency: Frequency) -> Recurrence {
+        Recurrence {
+            frequency,
+            interval: 1,
+            weekdays: vec![],
+            month_day: None,
+            until: None,
+            tz_offset: 0,
+        }
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    // follows the series from `first` for `count` occurrences
+    fn occurrences(recurrence: &Recurrence, first: &str, count: usize) -> Vec<DateTime<Utc>> {
+        std::iter::successors(Some(at(first)), |due_at| recurrence.next_after(*due_at))
+            .skip(1)
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn should_repeat_daily() {
+        let recurrence = Recurrence { interval: 3, ..rule(Frequency::Daily) };
+        assert_eq!(
+            vec![at("2026-06-04T09:00:00Z"), at("2026-06-07T09:00:00Z")],
+            occurrences(&recurrence, "2026-06-01T09:00:00Z", 2)
+        );
+    }
+
+    #[test]
+    fn should_repeat_on_weekdays() {
+        // the weekday of the current occurrence without any given
+        let recurrence = rule(Frequency::Weekly);
+        assert_eq!(vec![at("2026-06-08T09:00:00Z")], occurrences(&recurrence, "2026-06-01T09:00:00Z", 1));
+
+        let recurrence = Recurrence { weekdays: vec![Weekday::Mon, Weekday::Thu], ..rule(Frequency::Weekly) };
+        assert_eq!(
+            vec![at("2026-06-04T09:00:00Z"), at("2026-06-08T09:00:00Z"), at("2026-06-11T09:00:00Z")],
+            occurrences(&recurrence, "2026-06-01T09:00:00Z", 3)
+        );
+
+        // every other week counts from the week of the current occurrence
+        let recurrence = Recurrence { interval: 2, ..recurrence };
+        assert_eq!(
+            vec![at("2026-06-04T09:00:00Z"), at("2026-06-15T09:00:00Z"), at("2026-06-18T09:00:00Z")],
+            occurrences(&recurrence, "2026-06-01T09:00:00Z", 3)
+        );
+
+        // Tuesday 08:00 in Tokyo is still Monday in UTC
+        let recurrence = Recurrence { weekdays: vec![Weekday::Tue], tz_offset: JST, ..rule(Frequency::Weekly) };
+        assert_eq!(vec![at("2026-06-08T23:00:00Z")], occurrences(&recurrence, "2026-06-01T23:00:00Z", 1));
+    }
+
+    #[test]
+    fn should_keep_the_anchor_day_across_month_ends() {
+        let recurrence = rule(Frequency::Monthly).anchored(Some(at("2026-01-31T09:00:00Z")));
+        assert_eq!(Some(31), recurrence.month_day);
+        assert_eq!(
+            vec![
+                at("2026-02-28T09:00:00Z"),
+                at("2026-03-31T09:00:00Z"),
+                at("2026-04-30T09:00:00Z"),
+                at("2026-05-31T09:00:00Z"),
+            ],
+            occurrences(&recurrence, "2026-01-31T09:00:00Z", 4)
+        );
+        assert_eq!(
+            vec![at("2028-02-29T09:00:00Z"), at("2028-03-31T09:00:00Z")],
+            occurrences(&recurrence, "2028-01-31T09:00:00Z", 2)
+        );
+
+        let recurrence = Recurrence { interval: 3, ..recurrence };
+        assert_eq!(
+            vec![at("2026-04-30T09:00:00Z"), at("2026-07-31T09:00:00Z")],
+            occurrences(&recurrence, "2026-01-31T09:00:00Z", 2)
+        );
+
+        // the day is the one in the rule's time zone, a given one is kept
+        let recurrence = Recurrence { tz_offset: JST, ..rule(Frequency::Monthly) };
+        assert_eq!(Some(1), recurrence.clone().anchored(Some(at("2026-01-31T20:00:00Z"))).month_day);
+        let recurrence = Recurrence { month_day: Some(15), ..recurrence };
+        assert_eq!(Some(15), recurrence.anchored(Some(at("2026-01-31T20:00:00Z"))).month_day);
+        // only monthly rules have a day of the month
+        assert_eq!(None, rule(Frequency::Weekly).anchored(Some(at("2026-01-31T09:00:00Z"))).month_day);
+    }
+
+    #[test]
+    fn should_stop_after_until() {
+        let recurrence = Recurrence { until: Some(at("2026-06-03T09:00:00Z")), ..rule(Frequency::Daily) };
+        assert_eq!(
+            vec![at("2026-06-02T09:00:00Z"), at("2026-06-03T09:00:00Z")],
+            occurrences(&recurrence, "2026-06-01T09:00:00Z", 5)
+        );
+        assert_eq!(None, recurrence.next_after(at("2026-06-03T09:00:00Z")));
+    }
+
+    // ranks are compared byte by byte, as under `COLLATE "C"`
+    fn assert_between(before: Option<&str>, after: Option<&str>) -> String {
+        let rank = rank_between(before, after);
+        assert!(before.is_none_or(|before| before < rank.as_str()), "{:?} < {}", before, rank);
+        assert!(after.is_none_or(|after| rank.as_str() < after), "{} < {:?}", rank, after);
+        assert!(!rank.ends_with('0'), "{}", rank);
+        rank
+    }
+
+    #[test]
+    fn should_rank_around_the_ends() {
+        let middle = assert_between(None, None);
+        assert_between(None, Some(&middle));
+        assert_between(Some(&middle), None);
+
+        // the head runs out of room
+        assert_between(None, Some("000000i"));
+        assert_between(None, Some("0000001"));
+        assert_between(Some("zzzzzzi"), None);
+        assert_between(Some("zzzzzzzzz"), None);
+    }
+
+    #[test]
+    fn should_rank_between_adjacent_ranks() {
+        for (before, after) in [("abc1", "abc2"), ("abci", "abcj"), ("000000i", "000001"), ("abcz", "abd1"), ("abc", "abc1")] {
+            assert_between(Some(before), Some(after));
+        }
+    }
+
+    #[test]
+    fn should_rank_repeated_inserts_at_the_same_spot() {
+        let first = assert_between(None, None);
+        let last = assert_between(Some(&first), None);
+
+        let mut ranks = vec![first.clone(), last.clone()];
+        for _ in 0..200 {
+            let rank = assert_between(Some(&first), Some(&ranks[1]));
+            ranks.insert(1, rank);
+        }
+        for _ in 0..200 {
+            let rank = assert_between(Some(&ranks[ranks.len() - 2]), Some(&last));
+            ranks.insert(ranks.len() - 1, rank);
+        }
+        for _ in 0..200 {
+            let rank = assert_between(None, Some(&ranks[0]));
+            ranks.insert(0, rank);
+        }
+
+        let mut sorted = ranks.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(ranks, sorted);
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::errors::FieldError;
+use super::todo::TodoEntity;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferFormat {
+    #[default]
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl TransferFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TransferFormat::Json => "application/json",
+            TransferFormat::Csv => "text/csv; charset=utf-8",
+            TransferFormat::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TransferFormat::Json => "json",
+            TransferFormat::Csv => "csv",
+            TransferFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TransferQuery {
+    #[serde(default)]
+    pub format: TransferFormat,
+}
+
+/// A todo as it leaves or enters a workspace, labels go by name so they survive the move.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TodoRecord {
+    pub text: String,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub start_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<TodoRecord>,
+}
+
+impl From<&TodoEntity> for TodoRecord {
+    fn from(todo: &TodoEntity) -> Self {
+        Self {
+            text: todo.text.clone(),
+            completed: todo.completed,
+            labels: todo.labels.iter().map(|label| label.name.clone()).collect(),
+            due_at: todo.due_at,
+            start_at: todo.start_at,
+            subtasks: todo.children.iter().map(TodoRecord::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Created,
+    Failed,
+}
+
+/// Outcome of a single row. Rows count todos in document order for JSON, data rows for CSV and lines for Markdown.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImportRowResult {
+    pub row: usize,
+    pub status: ImportStatus,
+    pub todo_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    pub created: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResult>,
+}
@@ -8,25 +8,35 @@ pub struct User {
     pub sub: String,
     pub name: Option<String>,
     pub email: Option<String>,
+    /// Whether the issuer vouched for `email`, only then is it trusted to answer invitations.
+    pub email_verified: bool,
 }
 
 impl User {
     pub fn new(id: i32, sub: String, name: Option<String>, email: Option<String>) -> Self {
-        Self { id, sub, name, email }
+        Self { id, sub, name, email, email_verified: false }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Validate)]
 pub struct CreateUser {
-    #[validate(length(min = 1, message = "Can not be empty"))]
+    /// Taken from the caller's credentials, whatever the body says.
+    #[serde(default)]
     pub sub: String,
     pub name: String,
     pub email: String,
+    /// Only set from the caller's token, never from the body.
+    #[serde(skip)]
+    pub email_verified: bool,
 }
 
 impl CreateUser {
     pub fn new(sub: String, name: String, email: String) -> Self {
-        Self { sub, name, email }
+        Self { sub, name, email, email_verified: false }
+    }
+
+    pub fn verified(self) -> Self {
+        Self { email_verified: true, ..self }
     }
 }
 
@@ -1,26 +1,73 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 use super::user::User;
 
+/// Declared from the least to the most privileged, so roles compare by rank.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "workspace_role", rename_all = "lowercase")]
+pub enum WorkspaceRole {
+    Viewer,
+    #[default]
+    Editor,
+    Admin,
+    Owner,
+}
+
+impl WorkspaceRole {
+    /// Viewers only read, everybody else creates and edits todos.
+    pub fn can_write(&self) -> bool {
+        *self >= WorkspaceRole::Editor
+    }
+
+    /// Admins and the owner manage members and other people's todos.
+    pub fn can_manage(&self) -> bool {
+        *self >= WorkspaceRole::Admin
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    #[serde(flatten)]
+    pub user: User,
+    pub role: WorkspaceRole,
+}
+
+impl WorkspaceMember {
+    pub fn new(user: User, role: WorkspaceRole) -> Self {
+        Self { user, role }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
 pub struct WorkspaceEntity {
     pub id: i32,
     pub name: String,
     pub is_personal: bool,
-    pub users: Vec<User>,
+    pub owner_id: Option<i32>,
+    pub users: Vec<WorkspaceMember>,
 }
 
 impl WorkspaceEntity {
-    pub fn new(id: i32, name: String, is_personal: bool, users: Vec<User>) -> Self {
+    pub fn new(id: i32, name: String, is_personal: bool, owner_id: Option<i32>, users: Vec<WorkspaceMember>) -> Self {
         Self {
             id,
             name,
             is_personal,
+            owner_id,
             users,
         }
     }
+
+    pub fn role_of(&self, user_id: i32) -> Option<WorkspaceRole> {
+        self.users
+            .iter()
+            .find(|member| member.user.id == user_id)
+            .map(|member| member.role)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
@@ -43,3 +90,84 @@ impl CreateWorkspace {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct UpdateWorkspace {
+    #[validate(length(min = 1, message = "Can not be empty"))]
+    #[validate(length(max = 100, message = "Over name length"))]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_add_member_role"))]
+pub struct AddMember {
+    #[validate(email(message = "Invalid email"))]
+    pub email: String,
+    #[serde(default)]
+    pub role: WorkspaceRole,
+}
+
+fn validate_add_member_role(payload: &AddMember) -> Result<(), ValidationError> {
+    validate_assignable_role(payload.role)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+#[validate(schema(function = "validate_update_member_role"))]
+pub struct UpdateMember {
+    pub role: WorkspaceRole,
+}
+
+fn validate_update_member_role(payload: &UpdateMember) -> Result<(), ValidationError> {
+    validate_assignable_role(payload.role)
+}
+
+// ownership only changes hands through a transfer
+fn validate_assignable_role(role: WorkspaceRole) -> Result<(), ValidationError> {
+    if role == WorkspaceRole::Owner {
+        return Err(ValidationError {
+            message: Some("owner can only be assigned by transferring ownership".into()),
+            ..ValidationError::new("owner_not_assignable")
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Validate)]
+pub struct TransferOwnership {
+    pub user_id: i32,
+}
+
+/// How long an invitation stays acceptable; re-inviting the same email extends it.
+pub const INVITATION_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "invitation_status", rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, FromRow)]
+pub struct WorkspaceInvitation {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub workspace_name: String,
+    pub email: String,
+    pub role: WorkspaceRole,
+    pub token: String,
+    pub status: InvitationStatus,
+    pub invited_by: Option<i32>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WorkspaceInvitation {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    pub fn is_for(&self, email: &str) -> bool {
+        self.email.eq_ignore_ascii_case(email)
+    }
+}
@@ -2,15 +2,47 @@ pub mod label;
 pub mod workspace;
 pub mod todo;
 pub mod user;
+pub mod activity;
+pub mod event;
+pub mod status;
 
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
-enum RepositoryError {
+pub enum RepositoryError {
     #[error("Unexpected Error: [{0}]")]
     Unexpected(String),
     #[error("Not Found, id is {0}")]
     NotFound(i32),
     #[error("Duplicate data, id is {0}")]
     Duplicate(i32),
-}
\ No newline at end of file
+    #[error("Label is not available in this workspace, id is {0}")]
+    UnknownLabel(i32),
+    #[error("Still in use, id is {0}")]
+    InUse(i32),
+    #[error("Status is not available in this workspace, id is {0}")]
+    UnknownStatus(i32),
+    #[error("Status is over its WIP limit, id is {0}")]
+    WipLimit(i32),
+    #[error("A workspace needs an open and a terminal status, id is {0}")]
+    LastStatus(i32),
+    #[error("User is not a member of this workspace, id is {0}")]
+    UnknownAssignee(i32),
+    #[error("Todo would start after it is due, id is {0}")]
+    StartAfterDue(i32),
+}
+
+fn generate_secret_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect()
+}
+
+// 秘密のトークンは平文で保存せず、ハッシュで照合する
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,203 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool};
+use crate::models::activity::{ActivityEntity, ActivityPage, ActivityQuery, NewActivity};
+
+/// Appends an event on the connection of the change it describes, so both commit or roll back together.
+pub(super) async fn record(conn: &mut PgConnection, activity: NewActivity) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+insert into activities (workspace_id, actor_id, target, target_id, action, before, after)
+values ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(activity.workspace_id)
+    .bind(activity.actor_id)
+    .bind(activity.target)
+    .bind(activity.target_id)
+    .bind(activity.action)
+    .bind(activity.before)
+    .bind(activity.after)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+pub trait ActivityRepository: Send + Sync + 'static {
+    async fn all_by_workspace(&self, workspace_id: i32, query: ActivityQuery) -> anyhow::Result<ActivityPage>;
+}
+
+#[derive(Debug, Clone)]
+pub struct ActivityRepositoryForDb {
+    pool: PgPool,
+}
+
+impl ActivityRepositoryForDb {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ActivityRepository for ActivityRepositoryForDb {
+    async fn all_by_workspace(&self, workspace_id: i32, query: ActivityQuery) -> anyhow::Result<ActivityPage> {
+        let limit = query.limit();
+        let mut items = sqlx::query_as::<_, ActivityEntity>(
+            r#"
+select * from activities
+where workspace_id = $1 and ($2::bigint is null or id < $2)
+order by id desc
+limit $3
+            "#,
+        )
+        .bind(workspace_id)
+        .bind(query.cursor)
+        .bind(limit + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_next = items.len() as i64 > limit;
+        items.truncate(limit as usize);
+        let next_cursor = if has_next { items.last().map(|activity| activity.id) } else { None };
+
+        Ok(ActivityPage { items, next_cursor })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "database-test")]
+mod test {
+    use super::*;
+    use crate::models::{
+        activity::{ActivityAction, ActivityTarget},
+        todo::{CreateTodo, UpdateTodo},
+        user::CreateUser,
+        workspace::CreateWorkspace,
+    };
+    use crate::repositories::{
+        todo::{TodoRepository, TodoRepositoryForDb},
+        user::{UserRepository, UserRepositoryForDb},
+        workspace::{WorkspaceRepository, WorkspaceRepositoryForDb},
+    };
+    use dotenvy::dotenv;
+    use std::env;
+
+    #[tokio::test]
+    async fn activity_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_activity_user".to_string(), "test_activity_user".to_string(), "activity_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(user.id, CreateWorkspace::new("test_activity_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let todo_repository = TodoRepositoryForDb::new(pool.clone());
+        let todo = todo_repository
+            .create(user.id, workspace.id, CreateTodo::new("audited".to_string(), vec![]))
+            .await
+            .expect("Failed to create test todo");
+        todo_repository
+            .update(todo.id, user.id, UpdateTodo { completed: Some(true), ..Default::default() })
+            .await
+            .expect("Failed to update test todo");
+        todo_repository.delete(todo.id, user.id).await.expect("Failed to delete test todo");
+
+        let repository = ActivityRepositoryForDb::new(pool.clone());
+        let page = repository
+            .all_by_workspace(workspace.id, ActivityQuery { cursor: None, limit: Some(2) })
+            .await
+            .expect("[all_by_workspace] returned Err");
+        assert_eq!(
+            vec![ActivityAction::Delete, ActivityAction::Update],
+            page.items.iter().map(|activity| activity.action).collect::<Vec<_>>()
+        );
+        let update = &page.items[1];
+        assert_eq!((ActivityTarget::Todo, todo.id, Some(user.id)), (update.target, update.target_id, update.actor_id));
+        assert_eq!(Some(false), update.before.as_ref().and_then(|before| before["completed"].as_bool()));
+        assert_eq!(Some(true), update.after.as_ref().and_then(|after| after["completed"].as_bool()));
+        assert!(page.items[0].after.is_none());
+
+        let page = repository
+            .all_by_workspace(workspace.id, ActivityQuery { cursor: page.next_cursor, limit: Some(2) })
+            .await
+            .expect("[all_by_workspace] returned Err");
+        assert_eq!(
+            vec![(ActivityTarget::Todo, ActivityAction::Create), (ActivityTarget::Workspace, ActivityAction::Create)],
+            page.items.iter().map(|activity| (activity.target, activity.action)).collect::<Vec<_>>()
+        );
+        assert_eq!(None, page.next_cursor);
+
+        // the log can't be rewritten
+        let res = sqlx::query("delete from activities where workspace_id = $1")
+            .bind(workspace.id)
+            .execute(&pool)
+            .await;
+        assert!(res.is_err());
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use std::sync::{Arc, RwLock};
+    use chrono::Utc;
+    use super::*;
+
+    #[derive(Debug, Clone, Default)]
+    pub struct ActivityRepositoryForMemory {
+        store: Arc<RwLock<Vec<ActivityEntity>>>,
+    }
+
+    impl ActivityRepositoryForMemory {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn record(&self, activity: NewActivity) -> ActivityEntity {
+            let mut store = self.store.write().unwrap();
+            let entity = ActivityEntity {
+                id: (store.len() + 1) as i64,
+                workspace_id: activity.workspace_id,
+                actor_id: Some(activity.actor_id),
+                target: activity.target,
+                target_id: activity.target_id,
+                action: activity.action,
+                before: activity.before,
+                after: activity.after,
+                created_at: Utc::now(),
+            };
+            store.push(entity.clone());
+            entity
+        }
+    }
+
+    #[async_trait]
+    impl ActivityRepository for ActivityRepositoryForMemory {
+        async fn all_by_workspace(&self, workspace_id: i32, query: ActivityQuery) -> anyhow::Result<ActivityPage> {
+            let store = self.store.read().unwrap();
+            let limit = query.limit() as usize;
+            let mut items: Vec<ActivityEntity> = store
+                .iter()
+                .rev()
+                .filter(|activity| activity.workspace_id == Some(workspace_id))
+                .filter(|activity| query.cursor.is_none_or(|cursor| activity.id < cursor))
+                .take(limit + 1)
+                .cloned()
+                .collect();
+
+            let has_next = items.len() > limit;
+            items.truncate(limit);
+            let next_cursor = if has_next { items.last().map(|activity| activity.id) } else { None };
+
+            Ok(ActivityPage { items, next_cursor })
+        }
+    }
+}
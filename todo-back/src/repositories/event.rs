@@ -0,0 +1,179 @@
+use std::time::Duration;
+use sqlx::{postgres::PgListener, PgConnection, PgPool};
+use tokio::sync::broadcast;
+use crate::models::event::WorkspaceEvent;
+
+const CHANNEL: &str = "workspace_events";
+// 購読者が追いつけない分は古いものから捨てる
+const CAPACITY: usize = 256;
+
+/// Queues the event on the connection of the change, Postgres only delivers it once that commits.
+pub(super) async fn notify(conn: &mut PgConnection, event: WorkspaceEvent) -> anyhow::Result<()> {
+    sqlx::query("select pg_notify($1, $2)")
+        .bind(CHANNEL)
+        .bind(serde_json::to_string(&event)?)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub trait EventRepository: Send + Sync + 'static {
+    fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent>;
+}
+
+/// Relays the notifications of every server instance to the subscribers of this one.
+#[derive(Debug, Clone)]
+pub struct EventRepositoryForDb {
+    sender: broadcast::Sender<WorkspaceEvent>,
+}
+
+impl EventRepositoryForDb {
+    pub async fn new(pool: PgPool) -> anyhow::Result<Self> {
+        let mut listener = PgListener::connect_with(&pool).await?;
+        listener.listen(CHANNEL).await?;
+
+        let (sender, _) = broadcast::channel(CAPACITY);
+        tokio::spawn(relay(listener, sender.clone()));
+        Ok(Self { sender })
+    }
+}
+
+async fn relay(mut listener: PgListener, sender: broadcast::Sender<WorkspaceEvent>) {
+    loop {
+        match listener.recv().await {
+            Ok(notification) => match serde_json::from_str::<WorkspaceEvent>(notification.payload()) {
+                // nobody subscribed on this instance is fine
+                Ok(event) => {
+                    let _ = sender.send(event);
+                }
+                Err(e) => tracing::warn!("ignored malformed workspace event: {}", e),
+            },
+            // the listener reconnects by itself, whatever was sent in between is lost
+            Err(e) => {
+                tracing::error!("failed to receive workspace events: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+impl EventRepository for EventRepositoryForDb {
+    fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "database-test")]
+mod test {
+    use super::*;
+    use crate::models::{
+        activity::ActivityAction,
+        event::EventTarget,
+        todo::{CreateTodo, UpdateTodo},
+        user::CreateUser,
+        workspace::{CreateWorkspace, WorkspaceRole},
+    };
+    use crate::repositories::{
+        todo::{TodoRepository, TodoRepositoryForDb},
+        user::{UserRepository, UserRepositoryForDb},
+        workspace::{WorkspaceRepository, WorkspaceRepositoryForDb},
+    };
+    use dotenvy::dotenv;
+    use std::env;
+
+    #[tokio::test]
+    async fn event_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let owner = user_repository
+            .create(CreateUser::new("auth0|test_event_owner".to_string(), "test_event_owner".to_string(), "event_owner@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let member = user_repository
+            .create(CreateUser::new("auth0|test_event_member".to_string(), "test_event_member".to_string(), "event_member@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace_repository = WorkspaceRepositoryForDb::new(pool.clone());
+        let workspace = workspace_repository
+            .create(owner.id, CreateWorkspace::new("test_event_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let repository = EventRepositoryForDb::new(pool.clone()).await.expect("[new] returned Err");
+        let mut receiver = repository.subscribe();
+
+        let todo_repository = TodoRepositoryForDb::new(pool.clone());
+        let todo = todo_repository
+            .create(owner.id, workspace.id, CreateTodo::new("notified".to_string(), vec![]))
+            .await
+            .expect("Failed to create test todo");
+        todo_repository
+            .update(todo.id, owner.id, UpdateTodo { completed: Some(true), ..Default::default() })
+            .await
+            .expect("Failed to update test todo");
+        workspace_repository
+            .add_member(workspace.id, owner.id, member.id, WorkspaceRole::Editor)
+            .await
+            .expect("Failed to add test member");
+
+        let mut events = vec![];
+        while events.len() < 3 {
+            let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+                .await
+                .expect("no workspace event arrived")
+                .unwrap();
+            // other tests share the channel
+            if event.workspace_id == workspace.id {
+                events.push(event);
+            }
+        }
+        assert_eq!(
+            vec![
+                WorkspaceEvent::new(workspace.id, EventTarget::Todo, todo.id, ActivityAction::Create, owner.id),
+                WorkspaceEvent::new(workspace.id, EventTarget::Todo, todo.id, ActivityAction::Update, owner.id),
+                WorkspaceEvent::new(workspace.id, EventTarget::Member, member.id, ActivityAction::Create, owner.id),
+            ],
+            events
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    pub struct EventRepositoryForMemory {
+        sender: broadcast::Sender<WorkspaceEvent>,
+    }
+
+    impl Default for EventRepositoryForMemory {
+        fn default() -> Self {
+            let (sender, _) = broadcast::channel(CAPACITY);
+            Self { sender }
+        }
+    }
+
+    impl EventRepositoryForMemory {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn publish(&self, event: WorkspaceEvent) {
+            let _ = self.sender.send(event);
+        }
+    }
+
+    impl EventRepository for EventRepositoryForMemory {
+        fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+            self.sender.subscribe()
+        }
+    }
+}
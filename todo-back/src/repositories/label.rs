@@ -1,13 +1,150 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
-use crate::models::label::{CreateLabel, Label};
-use super::RepositoryError;
+use sqlx::{FromRow, PgConnection, PgPool};
+use crate::models::{
+    activity::{ActivityTarget, NewActivity},
+    event::{EventTarget, WorkspaceEvent},
+    label::{CreateLabel, Label, UpdateLabel},
+};
+use super::{activity, event, RepositoryError};
 
 #[async_trait]
 pub trait LabelRepository: Send + Sync + 'static {
     async fn create(&self, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label>;
     async fn all(&self, user_id: i32) -> anyhow::Result<Vec<Label>>;
-    async fn delete(&self, id: i32, user_id: i32) -> anyhow::Result<()>;
+    async fn update(&self, id: i32, user_id: i32, payload: UpdateLabel) -> anyhow::Result<Label>;
+    /// Moves the todos tagged with `id` over to `into_id` and deletes `id`, returns the label merged into.
+    async fn merge(&self, id: i32, into_id: i32, user_id: i32) -> anyhow::Result<Label>;
+    /// Fails with `InUse` while todos carry the label, unless it is to be detached from them.
+    async fn delete(&self, id: i32, user_id: i32, detach: bool) -> anyhow::Result<()>;
+    async fn create_in_workspace(&self, workspace_id: i32, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label>;
+    /// Labels shared in the workspace, plus the user's own ones when it is their personal workspace.
+    async fn all_by_workspace(&self, workspace_id: i32, user_id: i32) -> anyhow::Result<Vec<Label>>;
+    async fn update_in_workspace(&self, workspace_id: i32, id: i32, actor_id: i32, payload: UpdateLabel) -> anyhow::Result<Label>;
+    async fn merge_in_workspace(&self, workspace_id: i32, id: i32, into_id: i32, actor_id: i32) -> anyhow::Result<Label>;
+    async fn delete_in_workspace(&self, workspace_id: i32, id: i32, actor_id: i32, detach: bool) -> anyhow::Result<()>;
+}
+
+// Labels a todo of the workspace may carry when `$2` puts them on it.
+const VISIBLE_LABELS: &str = r#"
+select labels.* from labels
+            inner join workspaces on workspaces.id = $1
+where labels.workspace_id = $1
+   or (labels.workspace_id is null and labels.user_id = $2 and workspaces.is_personal)
+"#;
+
+/// Fails with `UnknownLabel` for the first of `label_ids` the user can't put on a todo of the workspace.
+pub async fn check_visible(
+    conn: &mut PgConnection,
+    workspace_id: i32,
+    user_id: i32,
+    label_ids: &[i32],
+) -> anyhow::Result<()> {
+    if label_ids.is_empty() {
+        return Ok(());
+    }
+    let unknown = sqlx::query_scalar::<_, i32>(&format!(
+        r#"
+select ids.id from unnest($3::int4[]) as ids(id)
+where ids.id not in (select visible.id from ({VISIBLE_LABELS}) visible)
+limit 1
+        "#,
+    ))
+    .bind(workspace_id)
+    .bind(user_id)
+    .bind(label_ids)
+    .fetch_optional(conn)
+    .await?;
+
+    match unknown {
+        Some(id) => Err(RepositoryError::UnknownLabel(id).into()),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct UpsertedLabelFromRow {
+    #[sqlx(flatten)]
+    label: Label,
+    inserted: bool,
+}
+
+// A shared label only concerns its workspace, a personal one is announced in every workspace
+// its user takes part in.
+async fn record_label_change(
+    conn: &mut PgConnection,
+    user_id: i32,
+    before: Option<&Label>,
+    after: Option<&Label>,
+) -> anyhow::Result<()> {
+    let Some(label) = after.or(before) else {
+        return Ok(());
+    };
+    let activity = NewActivity::new(label.workspace_id, user_id, ActivityTarget::Label, label.id, before, after);
+    let action = activity.action;
+    activity::record(&mut *conn, activity).await?;
+
+    let workspace_ids = match label.workspace_id {
+        Some(workspace_id) => vec![workspace_id],
+        None => {
+            sqlx::query_scalar::<_, i32>("select workspace_id from workspace_users where user_id = $1")
+                .bind(user_id)
+                .fetch_all(&mut *conn)
+                .await?
+        }
+    };
+    for workspace_id in workspace_ids {
+        event::notify(conn, WorkspaceEvent::new(workspace_id, EventTarget::Label, label.id, action, user_id)).await?;
+    }
+    Ok(())
+}
+
+/// Creates the label, or finds the one of the same name, inside the caller's transaction.
+pub async fn upsert(conn: &mut PgConnection, workspace_id: Option<i32>, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+    // `xmax = 0` tells a freshly inserted row apart from an existing label hit by the upsert,
+    // only one of the two unique indexes applies to a row
+    let conflict_target = match workspace_id {
+        Some(_) => "(workspace_id, name) where workspace_id is not null",
+        None => "(user_id, name) where workspace_id is null",
+    };
+    let UpsertedLabelFromRow { label, inserted } = sqlx::query_as::<_, UpsertedLabelFromRow>(&format!(
+        r#"
+insert into labels (name, user_id, workspace_id, color, icon)
+values($1, $2, $3, $4, $5)
+on conflict {conflict_target} do update set name = excluded.name
+returning labels.*, (xmax = 0) as inserted
+        "#,
+    ))
+    .bind(payload.name.clone())
+    .bind(user_id)
+    .bind(workspace_id)
+    .bind(payload.color)
+    .bind(payload.icon)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if inserted {
+        record_label_change(conn, user_id, None, Some(&label)).await?;
+    }
+    Ok(label)
+}
+
+// Locks the label, the user's own one when no workspace is given.
+async fn find_label(conn: &mut PgConnection, id: i32, workspace_id: Option<i32>, user_id: i32) -> anyhow::Result<Label> {
+    let label = sqlx::query_as::<_, Label>(
+        r#"
+select * from labels
+where id = $1 and workspace_id is not distinct from $2 and ($2 is not null or user_id = $3)
+for update
+        "#,
+    )
+    .bind(id)
+    .bind(workspace_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or(RepositoryError::NotFound(id))?;
+
+    Ok(label)
 }
 
 #[derive(Debug, Clone)]
@@ -19,32 +156,122 @@ impl LabelRepositoryForDb {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
-}
 
-#[async_trait]
-impl LabelRepository for LabelRepositoryForDb {
-    async fn create(&self, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+    async fn upsert(&self, workspace_id: Option<i32>, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+        let mut tx = self.pool.begin().await?;
+
+        let label = upsert(&mut tx, workspace_id, user_id, payload).await?;
+
+        tx.commit().await?;
+        Ok(label)
+    }
+
+    async fn update_label(&self, id: i32, workspace_id: Option<i32>, actor_id: i32, payload: UpdateLabel) -> anyhow::Result<Label> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_label = find_label(&mut tx, id, workspace_id, actor_id).await?;
         let label = sqlx::query_as::<_, Label>(
             r#"
-insert into labels (name, user_id)
-values($1, $2)
-on conflict (user_id, name) do update set name = excluded.name
+update labels set name = $1, color = $2, icon = $3
+where id = $4
 returning *
         "#,
         )
-        .bind(payload.name.clone())
-        .bind(user_id)
-        .fetch_one(&self.pool)
-        .await?;
+        .bind(payload.name.unwrap_or(old_label.name.clone()))
+        .bind(payload.color.unwrap_or(old_label.color.clone()))
+        .bind(payload.icon.unwrap_or(old_label.icon.clone()))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => RepositoryError::Duplicate(id),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
 
+        record_label_change(&mut tx, actor_id, Some(&old_label), Some(&label)).await?;
+
+        tx.commit().await?;
         Ok(label)
     }
 
+    async fn merge_labels(&self, id: i32, into_id: i32, workspace_id: Option<i32>, actor_id: i32) -> anyhow::Result<Label> {
+        if id == into_id {
+            return Err(RepositoryError::Unexpected("a label can not be merged into itself".to_string()).into());
+        }
+        let mut tx = self.pool.begin().await?;
+
+        let label = find_label(&mut tx, id, workspace_id, actor_id).await?;
+        let into = find_label(&mut tx, into_id, workspace_id, actor_id).await?;
+
+        // todos carrying both labels keep the one they already have
+        sqlx::query(
+            r#"
+insert into todo_labels (todo_id, label_id)
+select todo_id, $2 from todo_labels where label_id = $1
+on conflict do nothing
+        "#,
+        )
+        .bind(id)
+        .bind(into.id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("delete from todo_labels where label_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("delete from labels where id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_label_change(&mut tx, actor_id, Some(&label), None).await?;
+
+        tx.commit().await?;
+        Ok(into)
+    }
+
+    async fn delete_label(&self, id: i32, workspace_id: Option<i32>, actor_id: i32, detach: bool) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let label = find_label(&mut tx, id, workspace_id, actor_id).await?;
+        if detach {
+            sqlx::query("delete from todo_labels where label_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            // trashed todos count too, they would come back without the label
+            let in_use = sqlx::query_scalar::<_, bool>("select exists (select 1 from todo_labels where label_id = $1)")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if in_use {
+                return Err(RepositoryError::InUse(id).into());
+            }
+        }
+        sqlx::query("delete from labels where id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        record_label_change(&mut tx, actor_id, Some(&label), None).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LabelRepository for LabelRepositoryForDb {
+    async fn create(&self, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+        self.upsert(None, user_id, payload).await
+    }
+
     async fn all(&self, user_id: i32) -> anyhow::Result<Vec<Label>> {
         let labels = sqlx::query_as::<_, Label>(
             r#"
 select * from labels
-where user_id = $1
+where user_id = $1 and workspace_id is null
 order by labels.id asc;
         "#,
         )
@@ -55,23 +282,42 @@ order by labels.id asc;
     Ok(labels)
     }
 
-    async fn delete(&self, id: i32, user_id: i32) -> anyhow::Result<()> {
-        sqlx::query(
-            r#"
-delete from labels
-where id = $1 and user_id = $2
-        "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
-            _ => RepositoryError::Unexpected(e.to_string()),
-        })?;
+    async fn update(&self, id: i32, user_id: i32, payload: UpdateLabel) -> anyhow::Result<Label> {
+        self.update_label(id, None, user_id, payload).await
+    }
 
-        Ok(())
+    async fn merge(&self, id: i32, into_id: i32, user_id: i32) -> anyhow::Result<Label> {
+        self.merge_labels(id, into_id, None, user_id).await
+    }
+
+    async fn delete(&self, id: i32, user_id: i32, detach: bool) -> anyhow::Result<()> {
+        self.delete_label(id, None, user_id, detach).await
+    }
+
+    async fn create_in_workspace(&self, workspace_id: i32, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+        self.upsert(Some(workspace_id), user_id, payload).await
+    }
+
+    async fn all_by_workspace(&self, workspace_id: i32, user_id: i32) -> anyhow::Result<Vec<Label>> {
+        let labels = sqlx::query_as::<_, Label>(&format!("{VISIBLE_LABELS} order by labels.id asc"))
+            .bind(workspace_id)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(labels)
+    }
+
+    async fn update_in_workspace(&self, workspace_id: i32, id: i32, actor_id: i32, payload: UpdateLabel) -> anyhow::Result<Label> {
+        self.update_label(id, Some(workspace_id), actor_id, payload).await
+    }
+
+    async fn merge_in_workspace(&self, workspace_id: i32, id: i32, into_id: i32, actor_id: i32) -> anyhow::Result<Label> {
+        self.merge_labels(id, into_id, Some(workspace_id), actor_id).await
+    }
+
+    async fn delete_in_workspace(&self, workspace_id: i32, id: i32, actor_id: i32, detach: bool) -> anyhow::Result<()> {
+        self.delete_label(id, Some(workspace_id), actor_id, detach).await
     }
 }
 
@@ -79,8 +325,12 @@ where id = $1 and user_id = $2
 #[cfg(feature = "database-test")]
 mod test {
     use super::*;
-    use crate::models::user::CreateUser;
-    use crate::repositories::user::{UserRepository, UserRepositoryForDb};
+    use crate::models::{todo::{CreateTodo, UpdateTodo}, user::CreateUser, workspace::CreateWorkspace};
+    use crate::repositories::{
+        todo::{TodoRepository, TodoRepositoryForDb},
+        user::{UserRepository, UserRepositoryForDb},
+        workspace::{WorkspaceRepository, WorkspaceRepositoryForDb},
+    };
     use dotenvy::dotenv;
     use sqlx::PgPool;
     use std::env;
@@ -119,10 +369,176 @@ mod test {
 
         // delete
         let _ = label_repository
-            .delete(label.id, test_user_id)
+            .delete(label.id, test_user_id, false)
             .await
             .expect("[delete] returned Err");
     }
+
+    #[tokio::test]
+    async fn workspace_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undifined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let test_user = user_repository
+            .create(CreateUser::new("auth0|test_workspace_label_user".to_string(), "test_workspace_label_user".to_string(), "workspace_label_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace_repository = WorkspaceRepositoryForDb::new(pool.clone());
+        let personal = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_label_personal_workspace".to_string(), true, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let shared = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_label_shared_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let other = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_label_other_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let label_repository = LabelRepositoryForDb::new(pool.clone());
+        let own = label_repository
+            .create(test_user.id, CreateLabel::new("test_label_own".to_string()))
+            .await
+            .expect("[create] returned Err");
+        let team = label_repository
+            .create_in_workspace(shared.id, test_user.id, CreateLabel::new("test_label_team".to_string()))
+            .await
+            .expect("[create_in_workspace] returned Err");
+        assert_eq!(Some(shared.id), team.workspace_id);
+        // the same name is the same label within a workspace, a new one in another
+        let again = label_repository
+            .create_in_workspace(shared.id, test_user.id, CreateLabel::new("test_label_team".to_string()))
+            .await
+            .expect("[create_in_workspace] returned Err");
+        assert_eq!(team, again);
+        let elsewhere = label_repository
+            .create_in_workspace(other.id, test_user.id, CreateLabel::new("test_label_team".to_string()))
+            .await
+            .expect("[create_in_workspace] returned Err");
+        assert_ne!(team.id, elsewhere.id);
+
+        // own labels only show up in the personal workspace
+        let labels = label_repository.all_by_workspace(shared.id, test_user.id).await.expect("[all_by_workspace] returned Err");
+        assert_eq!(vec![team.clone()], labels);
+        let labels = label_repository.all_by_workspace(personal.id, test_user.id).await.expect("[all_by_workspace] returned Err");
+        assert_eq!(vec![own.clone()], labels);
+        let labels = label_repository.all(test_user.id).await.expect("[all] returned Err");
+        assert!(!labels.contains(&team));
+
+        // todos only carry labels visible in their workspace
+        let todo_repository = TodoRepositoryForDb::new(pool.clone());
+        let todo = todo_repository
+            .create(test_user.id, shared.id, CreateTodo::new("test_label_todo".to_string(), vec![team.id]))
+            .await
+            .expect("Failed to create test todo");
+        for label_id in [own.id, elsewhere.id] {
+            let err = todo_repository
+                .create(test_user.id, shared.id, CreateTodo::new("test_label_todo".to_string(), vec![label_id]))
+                .await
+                .unwrap_err();
+            assert!(matches!(err.downcast_ref::<RepositoryError>(), Some(RepositoryError::UnknownLabel(id)) if *id == label_id));
+            let payload = UpdateTodo { label_ids: Some(vec![team.id, label_id]), ..UpdateTodo::default() };
+            assert!(todo_repository.update(todo.id, test_user.id, payload).await.is_err());
+        }
+        assert_eq!(vec![team.clone()], todo_repository.find(todo.id).await.unwrap().labels);
+        todo_repository
+            .create(test_user.id, personal.id, CreateTodo::new("test_label_todo".to_string(), vec![own.id]))
+            .await
+            .expect("Failed to create test todo");
+
+        // a label of another workspace can't be deleted through this one
+        assert!(label_repository.delete_in_workspace(shared.id, elsewhere.id, test_user.id, false).await.is_err());
+        label_repository
+            .delete_in_workspace(other.id, elsewhere.id, test_user.id, false)
+            .await
+            .expect("[delete_in_workspace] returned Err");
+        let labels = label_repository.all_by_workspace(other.id, test_user.id).await.unwrap();
+        assert!(labels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn update_merge_delete_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undifined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let test_user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_label_merge_user".to_string(), "test_label_merge_user".to_string(), "label_merge_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_label_merge_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let label_repository = LabelRepositoryForDb::new(pool.clone());
+        let create = |name: &str| CreateLabel::new(name.to_string());
+        let bug = label_repository.create_in_workspace(workspace.id, test_user.id, create("test_label_bug")).await.unwrap();
+        let defect = label_repository.create_in_workspace(workspace.id, test_user.id, create("test_label_defect")).await.unwrap();
+        let urgent = label_repository.create_in_workspace(workspace.id, test_user.id, create("test_label_urgent")).await.unwrap();
+
+        // update
+        let payload = UpdateLabel {
+            name: Some("test_label_issue".to_string()),
+            color: Some(Some("#ff8800".to_string())),
+            icon: Some(Some("🐞".to_string())),
+        };
+        let bug = label_repository
+            .update_in_workspace(workspace.id, bug.id, test_user.id, payload)
+            .await
+            .expect("[update_in_workspace] returned Err");
+        assert_eq!("test_label_issue", bug.name);
+        assert_eq!(Some("#ff8800".to_string()), bug.color);
+        let payload = UpdateLabel { icon: Some(None), ..UpdateLabel::default() };
+        let bug = label_repository.update_in_workspace(workspace.id, bug.id, test_user.id, payload).await.unwrap();
+        assert_eq!((Some("#ff8800".to_string()), None), (bug.color.clone(), bug.icon.clone()));
+        let payload = UpdateLabel { name: Some("test_label_defect".to_string()), ..UpdateLabel::default() };
+        let res = label_repository.update_in_workspace(workspace.id, bug.id, test_user.id, payload).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::Duplicate(_))));
+        // personal labels are looked up apart from the workspace's
+        assert!(label_repository.update(bug.id, test_user.id, UpdateLabel::default()).await.is_err());
+
+        let todo_repository = TodoRepositoryForDb::new(pool.clone());
+        let both = todo_repository
+            .create(test_user.id, workspace.id, CreateTodo::new("test_label_both".to_string(), vec![bug.id, defect.id]))
+            .await
+            .unwrap();
+        let one = todo_repository
+            .create(test_user.id, workspace.id, CreateTodo::new("test_label_one".to_string(), vec![defect.id, urgent.id]))
+            .await
+            .unwrap();
+        assert_eq!(Some("#ff8800".to_string()), both.labels[0].color);
+
+        // merge
+        let into = label_repository
+            .merge_in_workspace(workspace.id, defect.id, bug.id, test_user.id)
+            .await
+            .expect("[merge_in_workspace] returned Err");
+        assert_eq!(bug, into);
+        assert_eq!(vec![bug.clone()], todo_repository.find(both.id).await.unwrap().labels);
+        assert_eq!(vec![bug.clone(), urgent.clone()], todo_repository.find(one.id).await.unwrap().labels);
+        let labels = label_repository.all_by_workspace(workspace.id, test_user.id).await.unwrap();
+        assert_eq!(vec![bug.clone(), urgent.clone()], labels);
+        assert!(label_repository.merge_in_workspace(workspace.id, bug.id, bug.id, test_user.id).await.is_err());
+
+        // delete
+        let res = label_repository.delete_in_workspace(workspace.id, bug.id, test_user.id, false).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::InUse(id)) if *id == bug.id));
+        assert_eq!(vec![bug.clone()], todo_repository.find(both.id).await.unwrap().labels);
+        label_repository
+            .delete_in_workspace(workspace.id, bug.id, test_user.id, true)
+            .await
+            .expect("[delete_in_workspace] returned Err");
+        assert!(todo_repository.find(both.id).await.unwrap().labels.is_empty());
+        assert_eq!(vec![urgent.clone()], todo_repository.find(one.id).await.unwrap().labels);
+    }
 }
 
 #[cfg(test)]
@@ -156,30 +572,125 @@ pub mod test_utils {
         }
     }
 
-    #[async_trait]
-    impl LabelRepository for LabelRepositoryForMemory {
-        async fn create(&self, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+    impl LabelRepositoryForMemory {
+        // the user's own label when no workspace is given, like the database looks it up
+        fn find_in(store: &LabelData, id: i32, workspace_id: Option<i32>, user_id: i32) -> anyhow::Result<Label> {
+            store
+                .get(&id)
+                .filter(|label| label.workspace_id == workspace_id && (workspace_id.is_some() || label.user_id == user_id))
+                .cloned()
+                .ok_or(RepositoryError::NotFound(id).into())
+        }
+
+        fn insert(&self, workspace_id: Option<i32>, user_id: i32, payload: CreateLabel) -> Label {
             let mut store = self.write_store_ref();
+            if let Some(label) = store.values().find(|label| {
+                label.workspace_id == workspace_id
+                    && (workspace_id.is_some() || label.user_id == user_id)
+                    && label.name == payload.name
+            }) {
+                return label.clone();
+            }
             let id = (store.len() + 1) as i32;
-            let label = Label::new(id, payload.name.clone(), user_id);
+            let label = Label {
+                workspace_id,
+                color: payload.color,
+                icon: payload.icon,
+                ..Label::new(id, payload.name, user_id)
+            };
+            store.insert(id, label.clone());
+            label
+        }
+
+        fn update_label(&self, id: i32, workspace_id: Option<i32>, user_id: i32, payload: UpdateLabel) -> anyhow::Result<Label> {
+            let mut store = self.write_store_ref();
+            let label = Self::find_in(&store, id, workspace_id, user_id)?;
+            let label = Label {
+                name: payload.name.unwrap_or(label.name),
+                color: payload.color.unwrap_or(label.color),
+                icon: payload.icon.unwrap_or(label.icon),
+                ..label
+            };
             store.insert(id, label.clone());
             Ok(label)
         }
 
+        // todos live in the todo repository, they are not retagged here
+        fn merge_labels(&self, id: i32, into_id: i32, workspace_id: Option<i32>, user_id: i32) -> anyhow::Result<Label> {
+            anyhow::ensure!(id != into_id, RepositoryError::Unexpected("a label can not be merged into itself".to_string()));
+            let mut store = self.write_store_ref();
+            Self::find_in(&store, id, workspace_id, user_id)?;
+            let into = Self::find_in(&store, into_id, workspace_id, user_id)?;
+            store.remove(&id);
+            Ok(into)
+        }
+
+        // nothing is known about todos here, so a label is never in use
+        fn delete_label(&self, id: i32, workspace_id: Option<i32>, user_id: i32) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            Self::find_in(&store, id, workspace_id, user_id)?;
+            store.remove(&id);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl LabelRepository for LabelRepositoryForMemory {
+        async fn create(&self, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+            Ok(self.insert(None, user_id, payload))
+        }
+
         async fn all(&self, user_id: i32) -> anyhow::Result<Vec<Label>> {
             let store = self.read_store_ref();
             let labels = store
                 .values()
-                .filter(|label| label.user_id == user_id)
+                .filter(|label| label.user_id == user_id && label.workspace_id.is_none())
                 .cloned()
                 .collect();
             Ok(labels)
         }
 
-        async fn delete(&self, id: i32, _user_id: i32) -> anyhow::Result<()> {
-            let mut store = self.write_store_ref();
-            store.remove(&id).ok_or(RepositoryError::NotFound(id))?;
-            Ok(())
+        async fn update(&self, id: i32, user_id: i32, payload: UpdateLabel) -> anyhow::Result<Label> {
+            self.update_label(id, None, user_id, payload)
+        }
+
+        async fn merge(&self, id: i32, into_id: i32, user_id: i32) -> anyhow::Result<Label> {
+            self.merge_labels(id, into_id, None, user_id)
+        }
+
+        async fn delete(&self, id: i32, user_id: i32, _detach: bool) -> anyhow::Result<()> {
+            self.delete_label(id, None, user_id)
+        }
+
+        async fn create_in_workspace(&self, workspace_id: i32, user_id: i32, payload: CreateLabel) -> anyhow::Result<Label> {
+            Ok(self.insert(Some(workspace_id), user_id, payload))
+        }
+
+        // workspaces aren't known here, so the user's own labels count as visible everywhere
+        async fn all_by_workspace(&self, workspace_id: i32, user_id: i32) -> anyhow::Result<Vec<Label>> {
+            let store = self.read_store_ref();
+            let mut labels: Vec<Label> = store
+                .values()
+                .filter(|label| match label.workspace_id {
+                    Some(id) => id == workspace_id,
+                    None => label.user_id == user_id,
+                })
+                .cloned()
+                .collect();
+            labels.sort_by_key(|label| label.id);
+            Ok(labels)
+        }
+
+        async fn update_in_workspace(&self, workspace_id: i32, id: i32, actor_id: i32, payload: UpdateLabel) -> anyhow::Result<Label> {
+            self.update_label(id, Some(workspace_id), actor_id, payload)
+        }
+
+        async fn merge_in_workspace(&self, workspace_id: i32, id: i32, into_id: i32, actor_id: i32) -> anyhow::Result<Label> {
+            self.merge_labels(id, into_id, Some(workspace_id), actor_id)
+        }
+
+        async fn delete_in_workspace(&self, workspace_id: i32, id: i32, actor_id: i32, _detach: bool) -> anyhow::Result<()> {
+            self.delete_label(id, Some(workspace_id), actor_id)
         }
     }
 
@@ -206,8 +717,54 @@ pub mod test_utils {
             assert_eq!(vec![expected], label);
 
             // delete
-            let res = repository.delete(id, user_id).await;
+            let res = repository.delete(id, user_id, false).await;
             assert!(res.is_ok())
         }
+
+        #[tokio::test]
+        async fn workspace_label_scenario() {
+            let repository = LabelRepositoryForMemory::new();
+            let own = repository.create(1, CreateLabel::new("own".to_string())).await.unwrap();
+            let team = repository
+                .create_in_workspace(1, 1, CreateLabel::new("team".to_string()))
+                .await
+                .unwrap();
+            assert_eq!(Label::new(2, "team".to_string(), 1).in_workspace(1), team);
+            let again = repository
+                .create_in_workspace(1, 2, CreateLabel::new("team".to_string()))
+                .await
+                .unwrap();
+            assert_eq!(team, again);
+            let elsewhere = repository
+                .create_in_workspace(2, 1, CreateLabel::new("team".to_string()))
+                .await
+                .unwrap();
+
+            assert_eq!(vec![own.clone(), team.clone()], repository.all_by_workspace(1, 1).await.unwrap());
+            assert_eq!(vec![team.clone()], repository.all_by_workspace(1, 2).await.unwrap());
+            assert_eq!(vec![own], repository.all(1).await.unwrap());
+
+            assert!(repository.delete_in_workspace(1, elsewhere.id, 1, false).await.is_err());
+            repository.delete_in_workspace(2, elsewhere.id, 1, false).await.unwrap();
+            assert!(repository.all_by_workspace(2, 2).await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn update_merge_label_scenario() {
+            let repository = LabelRepositoryForMemory::new();
+            let bug = repository.create_in_workspace(1, 1, CreateLabel::new("bug".to_string())).await.unwrap();
+            let defect = repository.create_in_workspace(1, 1, CreateLabel::new("defect".to_string())).await.unwrap();
+
+            let payload = UpdateLabel { color: Some(Some("#00ff00".to_string())), ..UpdateLabel::default() };
+            let bug = repository.update_in_workspace(1, bug.id, 2, payload).await.unwrap();
+            assert_eq!(Label { color: Some("#00ff00".to_string()), ..Label::new(1, "bug".to_string(), 1).in_workspace(1) }, bug);
+            assert!(repository.update(bug.id, 1, UpdateLabel::default()).await.is_err());
+
+            assert!(repository.merge_in_workspace(1, bug.id, bug.id, 1).await.is_err());
+            assert!(repository.merge_in_workspace(2, defect.id, bug.id, 1).await.is_err());
+            let into = repository.merge_in_workspace(1, defect.id, bug.id, 1).await.unwrap();
+            assert_eq!(bug, into);
+            assert_eq!(vec![bug], repository.all_by_workspace(1, 1).await.unwrap());
+        }
     }
 }
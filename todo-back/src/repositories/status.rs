@@ -0,0 +1,260 @@
+use sqlx::{Executor, PgConnection, Postgres};
+use crate::models::{
+    activity::{ActivityTarget, NewActivity},
+    event::{EventTarget, WorkspaceEvent},
+    status::{first_of_kind, CreateStatus, Status, UpdateStatus, DEFAULT_STATUSES},
+};
+use super::{activity, event, RepositoryError};
+
+async fn record_status_change(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    before: Option<&Status>,
+    after: Option<&Status>,
+) -> anyhow::Result<()> {
+    let Some(status) = after.or(before) else {
+        return Ok(());
+    };
+    let activity = NewActivity::new(Some(status.workspace_id), actor_id, ActivityTarget::Status, status.id, before, after);
+    let event = WorkspaceEvent::new(status.workspace_id, EventTarget::Status, status.id, activity.action, actor_id);
+    activity::record(conn, activity).await?;
+    event::notify(conn, event).await
+}
+
+// A board without an open or without a terminal column can't complete or reopen todos.
+async fn check_kinds(conn: &mut PgConnection, workspace_id: i32, id: i32) -> anyhow::Result<()> {
+    let complete = sqlx::query_scalar::<_, Option<bool>>(
+        "select bool_or(is_terminal) and bool_or(not is_terminal) from statuses where workspace_id = $1",
+    )
+    .bind(workspace_id)
+    .fetch_one(conn)
+    .await?;
+
+    if complete != Some(true) {
+        return Err(RepositoryError::LastStatus(id).into());
+    }
+    Ok(())
+}
+
+/// Gives a new workspace the default columns.
+pub async fn create_defaults(conn: &mut PgConnection, workspace_id: i32) -> anyhow::Result<()> {
+    let (names, terminal): (Vec<&str>, Vec<bool>) = DEFAULT_STATUSES.into_iter().unzip();
+    sqlx::query(
+        r#"
+insert into statuses (workspace_id, name, position, is_terminal)
+select $1, name, (position - 1)::int4, is_terminal
+from unnest($2::text[], $3::bool[]) with ordinality as t(name, is_terminal, position)
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(names)
+    .bind(terminal)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// The columns of the workspace in board order.
+pub async fn all<'e, E>(executor: E, workspace_id: i32) -> anyhow::Result<Vec<Status>>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let statuses = sqlx::query_as::<_, Status>(
+        "select * from statuses where workspace_id = $1 order by position, id",
+    )
+    .bind(workspace_id)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(statuses)
+}
+
+// Locks the column until the transaction ends, so todos are counted against its WIP limit one at a time.
+async fn lock(conn: &mut PgConnection, workspace_id: i32, id: i32) -> anyhow::Result<Option<Status>> {
+    let status = sqlx::query_as::<_, Status>(
+        "select * from statuses where id = $1 and workspace_id = $2 for update",
+    )
+    .bind(id)
+    .bind(workspace_id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(status)
+}
+
+pub async fn find(conn: &mut PgConnection, workspace_id: i32, id: i32) -> anyhow::Result<Status> {
+    let status = lock(conn, workspace_id, id)
+        .await?
+        .ok_or(RepositoryError::NotFound(id))?;
+    Ok(status)
+}
+
+/// The column a todo is put in, `UnknownStatus` when `id` isn't one of the workspace.
+/// Without `id`, the leftmost terminal or open one depending on `is_terminal`.
+pub async fn resolve(conn: &mut PgConnection, workspace_id: i32, id: Option<i32>, is_terminal: bool) -> anyhow::Result<Status> {
+    let id = match id {
+        Some(id) => id,
+        None => {
+            let statuses = all(&mut *conn, workspace_id).await?;
+            first_of_kind(&statuses, is_terminal)
+                .ok_or(RepositoryError::LastStatus(workspace_id))?
+                .id
+        }
+    };
+    let status = lock(conn, workspace_id, id)
+        .await?
+        .ok_or(RepositoryError::UnknownStatus(id))?;
+    Ok(status)
+}
+
+/// Fails with `WipLimit` while the column holds more top level todos than it allows.
+pub async fn check_wip_limit(conn: &mut PgConnection, status: &Status) -> anyhow::Result<()> {
+    if status.wip_limit.is_none() {
+        return Ok(());
+    }
+    let count = sqlx::query_scalar::<_, i64>(
+        "select count(*) from todos where status_id = $1 and parent_id is null and deleted_at is null",
+    )
+    .bind(status.id)
+    .fetch_one(conn)
+    .await?;
+
+    if status.is_over_limit(count as usize) {
+        return Err(RepositoryError::WipLimit(status.id).into());
+    }
+    Ok(())
+}
+
+pub async fn create(conn: &mut PgConnection, workspace_id: i32, actor_id: i32, payload: CreateStatus) -> anyhow::Result<Status> {
+    let status = sqlx::query_as::<_, Status>(
+        r#"
+insert into statuses (workspace_id, name, position, wip_limit, is_terminal)
+select $1, $2, coalesce(max(position) + 1, 0), $3, $4 from statuses where workspace_id = $1
+returning *
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(payload.name)
+    .bind(payload.wip_limit)
+    .bind(payload.is_terminal)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => RepositoryError::Duplicate(workspace_id),
+        _ => RepositoryError::Unexpected(e.to_string()),
+    })?;
+
+    record_status_change(conn, actor_id, None, Some(&status)).await?;
+    Ok(status)
+}
+
+// 他の列の位置は詰めて振り直す
+async fn reorder(conn: &mut PgConnection, workspace_id: i32, id: i32, position: i32) -> anyhow::Result<()> {
+    let mut ids: Vec<i32> = all(&mut *conn, workspace_id)
+        .await?
+        .into_iter()
+        .map(|status| status.id)
+        .filter(|other| *other != id)
+        .collect();
+    ids.insert((position as usize).min(ids.len()), id);
+
+    sqlx::query(
+        r#"
+update statuses set position = (t.position - 1)::int4
+from unnest($1::int4[]) with ordinality as t(id, position)
+where statuses.id = t.id
+        "#,
+    )
+    .bind(ids)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn update(
+    conn: &mut PgConnection,
+    workspace_id: i32,
+    id: i32,
+    actor_id: i32,
+    payload: UpdateStatus,
+) -> anyhow::Result<Status> {
+    let old_status = find(&mut *conn, workspace_id, id).await?;
+    sqlx::query("update statuses set name = $1, wip_limit = $2, is_terminal = $3 where id = $4")
+        .bind(payload.name.unwrap_or(old_status.name.clone()))
+        .bind(payload.wip_limit.unwrap_or(old_status.wip_limit))
+        .bind(payload.is_terminal.unwrap_or(old_status.is_terminal))
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => RepositoryError::Duplicate(id),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
+
+    if let Some(position) = payload.position {
+        reorder(&mut *conn, workspace_id, id, position).await?;
+    }
+    if payload.is_terminal.is_some_and(|is_terminal| is_terminal != old_status.is_terminal) {
+        check_kinds(&mut *conn, workspace_id, id).await?;
+    }
+
+    let status = find(&mut *conn, workspace_id, id).await?;
+    record_status_change(conn, actor_id, Some(&old_status), Some(&status)).await?;
+    Ok(status)
+}
+
+/// Todos go to `move_to` first, `InUse` without it while the column holds any that aren't trashed,
+/// `WipLimit` when they don't fit in there. Trashed ones follow to the leftmost column of the same kind then.
+/// Returns the column the todos went to, their completion is left to the caller.
+pub async fn delete(
+    conn: &mut PgConnection,
+    workspace_id: i32,
+    id: i32,
+    actor_id: i32,
+    move_to: Option<i32>,
+) -> anyhow::Result<Status> {
+    let status = find(&mut *conn, workspace_id, id).await?;
+    let target = match move_to {
+        Some(move_to) if move_to != id => find(&mut *conn, workspace_id, move_to).await?,
+        Some(move_to) => return Err(RepositoryError::NotFound(move_to).into()),
+        None => {
+            let in_use = sqlx::query_scalar::<_, bool>(
+                "select exists (select 1 from todos where status_id = $1 and deleted_at is null)",
+            )
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await?;
+            if in_use {
+                return Err(RepositoryError::InUse(id).into());
+            }
+            let others: Vec<Status> = all(&mut *conn, workspace_id)
+                .await?
+                .into_iter()
+                .filter(|other| other.id != id)
+                .collect();
+            first_of_kind(&others, status.is_terminal)
+                .or(others.first())
+                .cloned()
+                .ok_or(RepositoryError::LastStatus(id))?
+        }
+    };
+
+    sqlx::query("update todos set status_id = $2 where status_id = $1")
+        .bind(id)
+        .bind(target.id)
+        .execute(&mut *conn)
+        .await?;
+    if move_to.is_some() {
+        check_wip_limit(&mut *conn, &target).await?;
+    }
+    sqlx::query("delete from statuses where id = $1")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    check_kinds(&mut *conn, workspace_id, id).await?;
+
+    record_status_change(conn, actor_id, Some(&status), None).await?;
+    Ok(target)
+}
\ No newline at end of file
@@ -1,21 +1,68 @@
 use async_trait::async_trait;
-use sqlx::{FromRow, PgPool};
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, Executor, FromRow, PgConnection, PgPool, Postgres, QueryBuilder};
 use crate::models::{
+    activity::{ActivityTarget, NewActivity},
+    event::{EventTarget, WorkspaceEvent},
     label::Label,
-    todo::{CreateTodo, TodoEntity, UpdateTodo}
+    recommendation::{Recommendation, RecommendationStatus, RECOMMENDATION_TTL_MINUTES},
+    search::{SearchHit, SearchQuery},
+    status::{CreateStatus, Status, UpdateStatus},
+    todo::{
+        rank_between, CreateTodo, MoveTodo, Priority, Recurrence, SortOrder, TodoCursor, TodoEntity, TodoPage, TodoQuery,
+        TodoSort, UpdateTodo,
+    },
 };
-use super::RepositoryError;
+use super::{activity, event, label, status, workspace, RepositoryError};
 
 #[derive(Debug, Clone, PartialEq, Eq, FromRow)]
 struct TodoWithLabelFromRow {
     id: i32,
     text: String,
     completed: bool,
+    status_id: i32,
     user_id: i32,
+    assignee_ids: Vec<i32>,
     workspace_id: i32,
+    due_at: Option<DateTime<Utc>>,
+    start_at: Option<DateTime<Utc>>,
+    parent_id: Option<i32>,
+    auto_complete: bool,
+    deleted_at: Option<DateTime<Utc>>,
+    recurrence: Option<Json<Recurrence>>,
+    series_id: Option<i32>,
+    priority: Option<Priority>,
+    position: String,
     label_id: Option<i32>,
     label_name: Option<String>,
     label_user_id: Option<i32>,
+    label_workspace_id: Option<i32>,
+    label_color: Option<String>,
+    label_icon: Option<String>,
+}
+
+impl TodoWithLabelFromRow {
+    // `None` for a todo without any label
+    fn label(&self) -> Option<Label> {
+        let (Some(id), Some(name), Some(user_id)) = (self.label_id, self.label_name.clone(), self.label_user_id) else {
+            return None;
+        };
+        Some(Label {
+            id,
+            name,
+            user_id,
+            workspace_id: self.label_workspace_id,
+            color: self.label_color.clone(),
+            icon: self.label_icon.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow)]
+struct SearchHitFromRow {
+    id: i32,
+    rank: f32,
+    highlight: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, FromRow)]
@@ -23,6 +70,7 @@ struct TodoFromRow {
     id: i32,
     text: String,
     completed: bool,
+    parent_id: Option<i32>,
 }
 
 fn fold_entities(rows: Vec<TodoWithLabelFromRow>) -> Vec<TodoEntity> {
@@ -32,211 +80,1144 @@ fn fold_entities(rows: Vec<TodoWithLabelFromRow>) -> Vec<TodoEntity> {
         let mut todos = accum.iter_mut();
         while let Some(todo) = todos.next() {
             if todo.id == row.id {
-                if let (Some(label_id), Some(label_name), Some(label_user_id)) =
-                    (row.label_id, row.label_name.clone(), row.label_user_id)
-                {
-                    todo.labels.push(Label {
-                        id: label_id,
-                        name: label_name,
-                        user_id: label_user_id,
-                    });
-                }
+                todo.labels.extend(row.label());
                 continue 'outer;
             }
         }
 
-        let labels = if let (Some(label_id), Some(label_name), Some(label_user_id)) =
-            (row.label_id, row.label_name.clone(), row.label_user_id)
-        {
-            vec![Label {
-                id: label_id,
-                name: label_name,
-                user_id: label_user_id,
-            }]
-        } else {
-            vec![]
-        };
+        let labels = row.label().into_iter().collect();
 
         accum.push(TodoEntity {
-            id: row.id,
-            text: row.text.clone(),
+            due_at: row.due_at,
+            start_at: row.start_at,
+            parent_id: row.parent_id,
+            auto_complete: row.auto_complete,
             completed: row.completed,
-            labels,
-            user_id: row.user_id,
-            workspace_id: row.workspace_id,
+            status_id: row.status_id,
+            assignee_ids: row.assignee_ids.clone(),
+            deleted_at: row.deleted_at,
+            recurrence: row.recurrence.clone().map(|Json(recurrence)| recurrence),
+            series_id: row.series_id,
+            priority: row.priority,
+            position: row.position.clone(),
+            ..TodoEntity::new(row.id, row.text.clone(), labels, row.user_id, row.workspace_id)
         });
     }
     accum
 }
 
-#[async_trait]
-pub trait TodoRepository: Send + Sync + 'static {
-    async fn create(&self, user_id: i32, workspace_id: i32, payload: CreateTodo) -> anyhow::Result<TodoEntity>;
-    async fn find(&self, id: i32) -> anyhow::Result<TodoEntity>;
-    async fn all_by_workspace(&self, workspace_id: i32) -> anyhow::Result<Vec<TodoEntity>>;
-    async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity>;
-    async fn delete(&self, id: i32) -> anyhow::Result<()>;
+// Moves every todo whose parent is in `todos` under that parent, children in position order.
+// A subtask trashed on its own stays apart from its parent.
+fn nest_children(todos: Vec<TodoEntity>) -> Vec<TodoEntity> {
+    let keys: Vec<(i32, Option<DateTime<Utc>>)> = todos.iter().map(|todo| (todo.id, todo.deleted_at)).collect();
+    let (mut children, parents): (Vec<TodoEntity>, Vec<TodoEntity>) = todos
+        .into_iter()
+        .partition(|todo| {
+            todo.parent_id
+                .is_some_and(|parent_id| keys.contains(&(parent_id, todo.deleted_at)))
+        });
+    children.sort_by(|a, b| a.position.cmp(&b.position).then(a.id.cmp(&b.id)));
+    parents
+        .into_iter()
+        .map(|parent| {
+            let (own, rest): (Vec<TodoEntity>, Vec<TodoEntity>) = children
+                .drain(..)
+                .partition(|child| child.parent_id == Some(parent.id));
+            children = rest;
+            parent.with_children(own)
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone)]
-pub struct TodoRepositoryForDb {
-    pool: PgPool,
+// Runs on the pool as well as inside a transaction, where activities snapshot the todo.
+// Trashed todos are loaded too, with the children that went to the trash together with them.
+async fn find_todo<'e, E>(executor: E, id: i32) -> anyhow::Result<TodoEntity>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let items = sqlx::query_as::<_, TodoWithLabelFromRow>(
+        r#"
+select todos.id, todos.text, todos.completed, todos.status_id, todos.user_id, todos.workspace_id,
+       todos.due_at, todos.start_at, todos.parent_id, todos.auto_complete, todos.deleted_at,
+       todos.recurrence, todos.series_id, todos.priority, todos.position,
+       array(select user_id from todo_assignees ta where ta.todo_id = todos.id order by user_id) as assignee_ids,
+       labels.id as label_id, labels.name as label_name, labels.user_id as label_user_id,
+       labels.workspace_id as label_workspace_id, labels.color as label_color, labels.icon as label_icon
+from todos
+            left outer join todos parent on parent.id = todos.parent_id
+            left outer join todo_labels tl on todos.id = tl.todo_id
+            left outer join labels on labels.id = tl.label_id
+where todos.id = $1
+   or (todos.parent_id = $1 and todos.deleted_at is not distinct from parent.deleted_at)
+order by todos.id, labels.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(executor)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
+        _ => RepositoryError::Unexpected(e.to_string()),
+    })?;
+
+    let todos = nest_children(fold_entities(items));
+    let todo = todos
+        .into_iter()
+        .find(|todo| todo.id == id)
+        .ok_or(RepositoryError::NotFound(id))?;
+    Ok(todo)
 }
 
-impl TodoRepositoryForDb {
-    pub fn new(pool: PgPool) -> Self {
-        TodoRepositoryForDb { pool }
+// Logs the change and announces it to the workspace, both inside the transaction making it.
+async fn record_todo_change(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    before: Option<&TodoEntity>,
+    after: Option<&TodoEntity>,
+) -> anyhow::Result<()> {
+    let Some(todo) = after.or(before) else {
+        return Ok(());
+    };
+    let activity = NewActivity::new(Some(todo.workspace_id), actor_id, ActivityTarget::Todo, todo.id, before, after);
+    let event = WorkspaceEvent::new(todo.workspace_id, EventTarget::Todo, todo.id, activity.action, actor_id);
+    activity::record(conn, activity).await?;
+    event::notify(conn, event).await
+}
+
+// Completes or reopens the todo, moving it to the leftmost terminal or open column
+// unless its column already is one.
+async fn set_completion(conn: &mut PgConnection, id: i32, completed: bool) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+update todos
+set completed = $2,
+    status_id = coalesce(
+        (select id from statuses where id = todos.status_id and is_terminal = $2),
+        (select id from statuses where workspace_id = todos.workspace_id and is_terminal = $2 order by position, id limit 1),
+        todos.status_id
+    )
+where id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(completed)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// 自動完了が有効な親の完了状態を子の状態に合わせる
+async fn sync_parent_completion(conn: &mut PgConnection, parent_id: i32) -> anyhow::Result<()> {
+    let completed = sqlx::query_scalar::<_, bool>(
+        r#"
+select not exists (
+    select 1 from todos children
+    where children.parent_id = todos.id and children.deleted_at is null and not children.completed
+)
+from todos
+where id = $1 and auto_complete
+  and exists (select 1 from todos children where children.parent_id = todos.id and children.deleted_at is null)
+        "#,
+    )
+    .bind(parent_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(completed) = completed {
+        set_completion(conn, parent_id, completed).await?;
     }
+    Ok(())
 }
 
-#[async_trait]
-impl TodoRepository for TodoRepositoryForDb {
-    async fn create(&self, user_id: i32, workspace_id: i32, payload: CreateTodo) -> anyhow::Result<TodoEntity> {
-        let mut tx = self.pool.begin().await?;
-        let row = sqlx::query_as::<_, TodoFromRow>(
+// Fails once a top level todo put its column over the WIP limit, subtasks don't count.
+async fn check_wip_limit(conn: &mut PgConnection, todo: &TodoEntity) -> anyhow::Result<()> {
+    if todo.parent_id.is_some() {
+        return Ok(());
+    }
+    let status = status::find(&mut *conn, todo.workspace_id, todo.status_id).await?;
+    status::check_wip_limit(conn, &status).await
+}
+
+// Everything `create` does, so other writes can create todos inside their own transaction.
+async fn create_todo(conn: &mut PgConnection, user_id: i32, workspace_id: i32, mut payload: CreateTodo) -> anyhow::Result<TodoEntity> {
+    label::check_visible(&mut *conn, workspace_id, user_id, &payload.label_ids).await?;
+    for new_label in std::mem::take(&mut payload.new_labels) {
+        let label = label::upsert(&mut *conn, Some(workspace_id), user_id, new_label).await?;
+        payload.label_ids.push(label.id);
+    }
+    payload.label_ids.sort_unstable();
+    payload.label_ids.dedup();
+    let parent_id = payload.parent_id;
+    // new todos go on top of the list, new subtasks below the existing ones
+    let position = match parent_id {
+        Some(_) => {
+            let last = sibling_position(&mut *conn, workspace_id, parent_id, None, SortOrder::Desc, None).await?;
+            rank_between(last.as_deref(), None)
+        }
+        None => {
+            let first = sibling_position(&mut *conn, workspace_id, parent_id, None, SortOrder::Asc, None).await?;
+            rank_between(None, first.as_deref())
+        }
+    };
+    let id = insert_todo(conn, user_id, workspace_id, payload, position, None).await?;
+
+    if let Some(parent_id) = parent_id {
+        sync_parent_completion(conn, parent_id).await?;
+    }
+
+    let todo = find_todo(&mut *conn, id).await?;
+    check_wip_limit(&mut *conn, &todo).await?;
+    record_todo_change(conn, user_id, None, Some(&todo)).await?;
+    Ok(todo)
+}
+
+// Locks the pending recommendations among `ids` made for the user in the workspace, in the order given.
+// Decided ones are duplicates, unknown, expired or somebody else's ones are not found.
+async fn pending_recommendations(
+    conn: &mut PgConnection,
+    workspace_id: i32,
+    user_id: i32,
+    ids: &[i32],
+) -> anyhow::Result<Vec<Recommendation>> {
+    let rows = sqlx::query_as::<_, Recommendation>(
+        r#"
+select id, workspace_id, user_id, text, status, todo_id, expires_at
+from recommendations
+where workspace_id = $1 and user_id = $2 and id = any($3)
+for update
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(user_id)
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut pending = vec![];
+    for id in ids {
+        let recommendation = rows
+            .iter()
+            .find(|row| row.id == *id && !row.is_expired())
+            .ok_or(RepositoryError::NotFound(*id))?;
+        if recommendation.status != RecommendationStatus::Pending {
+            return Err(RepositoryError::Duplicate(*id).into());
+        }
+        if !pending.contains(recommendation) {
+            pending.push(recommendation.clone());
+        }
+    }
+    Ok(pending)
+}
+
+// Position of the nearest sibling past `from` in `order`, of the first one in `order` without `from`.
+// Siblings share workspace and parent, trashed ones and `except` don't count.
+async fn sibling_position(
+    conn: &mut PgConnection,
+    workspace_id: i32,
+    parent_id: Option<i32>,
+    from: Option<&str>,
+    order: SortOrder,
+    except: Option<i32>,
+) -> anyhow::Result<Option<String>> {
+    let (cmp, dir) = match order {
+        SortOrder::Asc => (">", "asc"),
+        SortOrder::Desc => ("<", "desc"),
+    };
+    let mut query = QueryBuilder::new("select position from todos where workspace_id = ");
+    query
+        .push_bind(workspace_id)
+        .push(" and parent_id is not distinct from ")
+        .push_bind(parent_id)
+        .push(" and deleted_at is null and id is distinct from ")
+        .push_bind(except);
+    if let Some(from) = from {
+        query.push(format!(" and position {} ", cmp)).push_bind(from.to_string());
+    }
+    query.push(format!(" order by position {0}, id {0} limit 1", dir));
+    let position = query.build_query_scalar().fetch_optional(conn).await?;
+    Ok(position)
+}
+
+// Inserts the todo with its labels, a recurring todo without a series starts its own.
+// Without a status it goes to the leftmost open column, or terminal one when created completed.
+async fn insert_todo(
+    conn: &mut PgConnection,
+    user_id: i32,
+    workspace_id: i32,
+    payload: CreateTodo,
+    position: String,
+    series_id: Option<i32>,
+) -> anyhow::Result<i32> {
+    let status = status::resolve(&mut *conn, workspace_id, payload.status_id, payload.completed).await?;
+    let row = sqlx::query_as::<_, TodoFromRow>(
+        r#"
+insert into todos (text, completed, user_id, workspace_id, due_at, start_at, parent_id, auto_complete, recurrence, series_id, priority, position, status_id)
+values ($1, $12, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $13)
+returning id, text, completed, parent_id
+        "#,
+    )
+    .bind(payload.text.clone())
+    .bind(user_id)
+    .bind(workspace_id)
+    .bind(payload.due_at)
+    .bind(payload.start_at)
+    .bind(payload.parent_id)
+    .bind(payload.auto_complete)
+    .bind(payload.recurrence.clone().map(|recurrence| Json(recurrence.anchored(payload.due_at))))
+    .bind(series_id)
+    .bind(payload.priority)
+    .bind(position)
+    .bind(status.is_terminal)
+    .bind(status.id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if payload.recurrence.is_some() && series_id.is_none() {
+        sqlx::query("update todos set series_id = id where id = $1")
+            .bind(row.id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    sqlx::query(
+        r#"
+insert into todo_labels (todo_id, label_id)
+select $1, id
+from unnest ($2) as t(id);
+        "#,
+    )
+    .bind(row.id)
+    .bind(payload.label_ids)
+    .execute(&mut *conn)
+    .await?;
+    assign(&mut *conn, row.id, workspace_id, &payload.assignee_ids).await?;
+
+    Ok(row.id)
+}
+
+// Replaces who the todo is assigned to, the users have to be members of the workspace.
+async fn assign(conn: &mut PgConnection, id: i32, workspace_id: i32, user_ids: &[i32]) -> anyhow::Result<()> {
+    workspace::check_members(&mut *conn, workspace_id, user_ids).await?;
+    sqlx::query("delete from todo_assignees where todo_id = $1")
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        r#"
+insert into todo_assignees (todo_id, workspace_id, user_id)
+select $1, $2, id
+from unnest($3::int4[]) as t(id)
+on conflict do nothing
+        "#,
+    )
+    .bind(id)
+    .bind(workspace_id)
+    .bind(user_ids)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Ids of the occurrences of the todo's series coming after it, oldest first.
+async fn later_occurrences(conn: &mut PgConnection, id: i32) -> anyhow::Result<Vec<i32>> {
+    let ids = sqlx::query_scalar::<_, i32>(
+        r#"
+select later.id
+from todos
+            join todos later on later.series_id = todos.series_id
+where todos.id = $1 and later.id > todos.id and later.deleted_at is null
+order by later.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(ids)
+}
+
+// 繰り返しtodoの次の回を作る、次の回が既にあれば何もしない
+async fn advance_series(conn: &mut PgConnection, todo: &TodoEntity, actor_id: i32) -> anyhow::Result<()> {
+    let Some(series_id) = todo.series_id else {
+        return Ok(());
+    };
+    if !later_occurrences(&mut *conn, todo.id).await?.is_empty() {
+        return Ok(());
+    }
+    let Some(payload) = todo.next_occurrence() else {
+        return Ok(());
+    };
+
+    // the next occurrence takes the place right below the one it follows
+    let next_position =
+        sibling_position(&mut *conn, todo.workspace_id, todo.parent_id, Some(&todo.position), SortOrder::Asc, None).await?;
+    let position = rank_between(Some(&todo.position), next_position.as_deref());
+    let id = insert_todo(&mut *conn, todo.user_id, todo.workspace_id, payload, position, Some(series_id)).await?;
+    let next = find_todo(&mut *conn, id).await?;
+    record_todo_change(conn, actor_id, None, Some(&next)).await
+}
+
+// Completing an occurrence, directly or through the subtasks completing it, schedules the next one.
+async fn advance_completed_series(conn: &mut PgConnection, todo: &TodoEntity, actor_id: i32) -> anyhow::Result<()> {
+    let root = match todo.parent_id {
+        Some(parent_id) => find_todo(&mut *conn, parent_id).await?,
+        None => todo.clone(),
+    };
+    if root.completed {
+        advance_series(conn, &root, actor_id).await?;
+    }
+    Ok(())
+}
+
+// Completes or reopens the todos whose column changed its kind under them, each one the way `update` would.
+async fn sync_status_completion(conn: &mut PgConnection, status: &Status, actor_id: i32) -> anyhow::Result<()> {
+    let ids = sqlx::query_scalar::<_, i32>(
+        "select id from todos where status_id = $1 and completed != $2 and deleted_at is null order by id",
+    )
+    .bind(status.id)
+    .bind(status.is_terminal)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for id in ids {
+        let old_todo = find_todo(&mut *conn, id).await?;
+        // an auto completing parent may have been settled by its subtasks already
+        if old_todo.status_id != status.id || old_todo.completed == status.is_terminal {
+            continue;
+        }
+        sqlx::query("update todos set completed = $2 where id = $1")
+            .bind(id)
+            .bind(status.is_terminal)
+            .execute(&mut *conn)
+            .await?;
+        sync_parent_completion(&mut *conn, old_todo.parent_id.unwrap_or(id)).await?;
+
+        let todo = find_todo(&mut *conn, id).await?;
+        record_todo_change(&mut *conn, actor_id, Some(&old_todo), Some(&todo)).await?;
+        advance_completed_series(&mut *conn, &todo, actor_id).await?;
+    }
+
+    // trashed todos just follow, they come back matching their column
+    sqlx::query("update todos set completed = $2 where status_id = $1 and deleted_at is not null")
+        .bind(status.id)
+        .bind(status.is_terminal)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn update_todo(conn: &mut PgConnection, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+    let old_todo = find_todo(&mut *conn, id).await?;
+    if payload.validate_dates_of(&old_todo).is_err() {
+        return Err(RepositoryError::StartAfterDue(id).into());
+    }
+    if let Some(label_ids) = &payload.label_ids {
+        label::check_visible(&mut *conn, old_todo.workspace_id, actor_id, label_ids).await?;
+    }
+    let due_at = payload.due_at.unwrap_or(old_todo.due_at);
+    let recurrence = match payload.recurrence {
+        Some(recurrence) => recurrence.map(|recurrence| recurrence.anchored(due_at)),
+        None => old_todo.recurrence.clone(),
+    };
+    // 繰り返しを設定した時点で、そのtodoが系列の最初の回になる
+    sqlx::query(
+        r#"
+update todos set text=$1, completed=$2, due_at=$3, start_at=$4, auto_complete=$5, recurrence=$6,
+                 series_id = case when $6 is null then series_id else coalesce(series_id, id) end,
+                 priority=$8
+where id = $7
+returning *
+        "#,
+    )
+    .bind(payload.text.unwrap_or(old_todo.text.clone()))
+    .bind(payload.completed.unwrap_or(old_todo.completed))
+    .bind(due_at)
+    .bind(payload.start_at.unwrap_or(old_todo.start_at))
+    .bind(payload.auto_complete.unwrap_or(old_todo.auto_complete))
+    .bind(recurrence.map(Json))
+    .bind(id)
+    .bind(payload.priority.unwrap_or(old_todo.priority))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if let Some(labels) = payload.label_ids {
+        sqlx::query(
             r#"
-insert into todos (text, completed, user_id, workspace_id)
-values ($1, false, $2, $3)
-returning id, text, completed
-            "#,
+    delete from todo_labels where todo_id=$1
+        "#,
         )
-        .bind(payload.text.clone())
-        .bind(user_id)
-        .bind(workspace_id)
-        .fetch_one(&mut *tx)
+        .bind(id)
+        .execute(&mut *conn)
         .await?;
 
         sqlx::query(
             r#"
-insert into todo_labels (todo_id, label_id)
-select $1, id
-from unnest ($2) as t(id);
-            "#,
+    insert into todo_labels (todo_id, label_id)
+    select $1, id
+    from unnest($2) as t(id);
+        "#,
         )
-        .bind(row.id)
-        .bind(payload.label_ids)
-        .execute(&mut *tx)
+        .bind(id)
+        .bind(labels)
+        .execute(&mut *conn)
         .await?;
+    };
+    if let Some(assignee_ids) = payload.assignee_ids {
+        assign(&mut *conn, id, old_todo.workspace_id, &assignee_ids).await?;
+    }
 
-        tx.commit().await?;
+    // the status decides over completion, completing alone picks a matching status
+    match (payload.status_id, payload.completed) {
+        (Some(status_id), _) => {
+            let status = status::resolve(&mut *conn, old_todo.workspace_id, Some(status_id), false).await?;
+            sqlx::query("update todos set status_id = $2, completed = $3 where id = $1")
+                .bind(id)
+                .bind(status.id)
+                .bind(status.is_terminal)
+                .execute(&mut *conn)
+                .await?;
+        }
+        (None, Some(completed)) => set_completion(&mut *conn, id, completed).await?,
+        (None, None) => {}
+    }
 
-        let todo = self.find(row.id).await?;
-        Ok(todo)
+    sync_parent_completion(&mut *conn, old_todo.parent_id.unwrap_or(id)).await?;
+
+    let todo = find_todo(&mut *conn, id).await?;
+    if todo.status_id != old_todo.status_id {
+        check_wip_limit(&mut *conn, &todo).await?;
     }
+    record_todo_change(conn, actor_id, Some(&old_todo), Some(&todo)).await?;
+    Ok(todo)
+}
 
-    async fn find(&self, id: i32) -> anyhow::Result<TodoEntity> {
-        let items = sqlx::query_as::<_, TodoWithLabelFromRow>(
-            r#"
-select todos.id, todos.text, todos.completed, todos.user_id, todos.workspace_id,
-       labels.id as label_id, labels.name as label_name, labels.user_id as label_user_id
-from todos
-            left outer join todo_labels tl on todos.id = tl.todo_id
-            left outer join labels on labels.id = tl.label_id
-where todos.id = $1
-            "#,
-        )
+// Puts the todo next to the anchor by giving it a position between the anchor and the anchor's neighbor,
+// no other todo moves. The anchor has to be another sibling.
+async fn move_todo(conn: &mut PgConnection, id: i32, actor_id: i32, payload: MoveTodo) -> anyhow::Result<TodoEntity> {
+    let old_todo = find_todo(&mut *conn, id).await?;
+    if old_todo.deleted_at.is_some() {
+        return Err(RepositoryError::NotFound(id).into());
+    }
+    let anchor = find_todo(&mut *conn, payload.anchor_id()).await?;
+    if !old_todo.is_sibling(&anchor) {
+        return Err(RepositoryError::NotFound(anchor.id).into());
+    }
+
+    let (workspace_id, parent_id) = (old_todo.workspace_id, old_todo.parent_id);
+    let position = match payload.before {
+        Some(_) => {
+            let previous =
+                sibling_position(&mut *conn, workspace_id, parent_id, Some(&anchor.position), SortOrder::Desc, Some(id)).await?;
+            rank_between(previous.as_deref(), Some(&anchor.position))
+        }
+        None => {
+            let next =
+                sibling_position(&mut *conn, workspace_id, parent_id, Some(&anchor.position), SortOrder::Asc, Some(id)).await?;
+            rank_between(Some(&anchor.position), next.as_deref())
+        }
+    };
+    sqlx::query("update todos set position = $1 where id = $2")
+        .bind(position)
         .bind(id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
-            _ => RepositoryError::Unexpected(e.to_string()),
-        })?;
+        .execute(&mut *conn)
+        .await?;
+
+    let todo = find_todo(&mut *conn, id).await?;
+    record_todo_change(conn, actor_id, Some(&old_todo), Some(&todo)).await?;
+    Ok(todo)
+}
+
+// サブタスクごとゴミ箱へ移す、now()はトランザクション内で同じ値になる
+async fn trash_todo(conn: &mut PgConnection, id: i32, actor_id: i32) -> anyhow::Result<TodoEntity> {
+    let old_todo = find_todo(&mut *conn, id).await?;
+    if old_todo.deleted_at.is_some() {
+        return Err(RepositoryError::NotFound(id).into());
+    }
+
+    sqlx::query(
+        r#"
+update todos set deleted_at = now()
+where (id = $1 or parent_id = $1) and deleted_at is null
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+
+    if let Some(parent_id) = old_todo.parent_id {
+        sync_parent_completion(&mut *conn, parent_id).await?;
+    }
+    record_todo_change(conn, actor_id, Some(&old_todo), None).await?;
+    Ok(old_todo)
+}
+
+// Only top level todos are listed, their children come nested.
+// Without a workspace the todos of every workspace are, the query narrows them down.
+fn push_filters(builder: &mut QueryBuilder<'_, Postgres>, workspace_id: Option<i32>, query: &TodoQuery) {
+    builder.push(" where todos.parent_id is null and todos.deleted_at is null");
+    if let Some(workspace_id) = workspace_id {
+        builder.push(" and todos.workspace_id = ").push_bind(workspace_id);
+    }
+    if let Some(due) = query.due {
+        let (from, to) = due.bounds(query.now());
+        if let Some(from) = from {
+            builder.push(" and todos.due_at >= ").push_bind(from);
+        }
+        builder.push(" and todos.due_at < ").push_bind(to);
+        if due.excludes_completed() {
+            builder.push(" and not todos.completed");
+        }
+    }
+    if let Some(completed) = query.completed {
+        builder.push(" and todos.completed = ").push_bind(completed);
+    }
+    if let Some(status_id) = query.status_id {
+        builder.push(" and todos.status_id = ").push_bind(status_id);
+    }
+    if let Some(user_id) = query.user_id {
+        builder.push(" and todos.user_id = ").push_bind(user_id);
+    }
+    if let Some(assignee_id) = query.assignee_id {
+        builder
+            .push(" and todos.id in (select todo_id from todo_assignees where user_id = ")
+            .push_bind(assignee_id)
+            .push(")");
+    }
+    if let Some(assigned) = query.assigned {
+        builder
+            .push(" and exists (select 1 from todo_assignees where todo_id = todos.id) = ")
+            .push_bind(assigned);
+    }
+    if let Some(q) = query.search_text() {
+        let pattern = format!(
+            "%{}%",
+            q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        builder.push(" and todos.text ilike ").push_bind(pattern);
+    }
+    if !query.label_ids.is_empty() {
+        builder
+            .push(" and todos.id in (select todo_id from todo_labels where label_id = any(")
+            .push_bind(query.label_ids.clone())
+            .push(") group by todo_id having count(distinct label_id) = ")
+            .push_bind(query.label_ids.len() as i64)
+            .push(")");
+    }
+}
+
+#[async_trait]
+pub trait TodoRepository: Send + Sync + 'static {
+    async fn create(&self, user_id: i32, workspace_id: i32, payload: CreateTodo) -> anyhow::Result<TodoEntity>;
+    async fn find(&self, id: i32) -> anyhow::Result<TodoEntity>;
+    async fn all_by_workspace(&self, workspace_id: i32, query: TodoQuery) -> anyhow::Result<TodoPage>;
+    /// Top level todos assigned to the user, from every workspace.
+    async fn all_assigned(&self, user_id: i32, query: TodoQuery) -> anyhow::Result<TodoPage>;
+    async fn search(&self, workspace_ids: &[i32], query: SearchQuery) -> anyhow::Result<Vec<SearchHit>>;
+    async fn update(&self, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity>;
+    /// Places the todo right before or after one of its siblings.
+    async fn move_todo(&self, id: i32, actor_id: i32, payload: MoveTodo) -> anyhow::Result<TodoEntity>;
+    async fn delete(&self, id: i32, actor_id: i32) -> anyhow::Result<()>;
+    // "this and all future occurrences" counterparts of update and delete
+    async fn update_series(&self, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity>;
+    async fn delete_series(&self, id: i32, actor_id: i32) -> anyhow::Result<()>;
+    async fn find_trashed(&self, id: i32) -> anyhow::Result<TodoEntity>;
+    async fn all_trashed(&self, workspace_id: i32) -> anyhow::Result<Vec<TodoEntity>>;
+    async fn restore(&self, id: i32, actor_id: i32) -> anyhow::Result<TodoEntity>;
+    async fn purge_trashed(&self, deleted_before: DateTime<Utc>) -> anyhow::Result<u64>;
+    /// Keeps the texts a model came up with for a while, so they can be accepted by id.
+    async fn save_recommendations(&self, workspace_id: i32, user_id: i32, texts: Vec<String>) -> anyhow::Result<Vec<Recommendation>>;
+    /// Turns every recommendation made for the actor into a todo, or none of them if any can't be accepted.
+    async fn accept_recommendations(&self, workspace_id: i32, actor_id: i32, ids: &[i32]) -> anyhow::Result<Vec<TodoEntity>>;
+    async fn reject_recommendations(&self, workspace_id: i32, user_id: i32, ids: &[i32]) -> anyhow::Result<()>;
+    /// Texts of the latest recommendations the user rejected in the workspace, newest first.
+    async fn rejected_recommendations(&self, workspace_id: i32, user_id: i32, limit: i64) -> anyhow::Result<Vec<String>>;
+    /// Columns of the workspace's board, from left to right.
+    async fn all_statuses(&self, workspace_id: i32) -> anyhow::Result<Vec<Status>>;
+    async fn create_status(&self, workspace_id: i32, actor_id: i32, payload: CreateStatus) -> anyhow::Result<Status>;
+    async fn update_status(&self, workspace_id: i32, id: i32, actor_id: i32, payload: UpdateStatus) -> anyhow::Result<Status>;
+    /// Fails with `InUse` while todos are in the column, unless they are to be moved to `move_to`.
+    async fn delete_status(&self, workspace_id: i32, id: i32, actor_id: i32, move_to: Option<i32>) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct TodoRepositoryForDb {
+    pool: PgPool,
+}
 
-        let todos = fold_entities(items);
-        let todo = todos.first().ok_or(RepositoryError::NotFound(id))?;
-        Ok(todo.clone())
+impl TodoRepositoryForDb {
+    pub fn new(pool: PgPool) -> Self {
+        TodoRepositoryForDb { pool }
     }
 
-    async fn all_by_workspace(&self, workspace_id: i32) -> anyhow::Result<Vec<TodoEntity>> {
+    // Loads the todos with their labels and children, keeping the order of `ids`.
+    async fn find_by_ids(&self, ids: &[i32]) -> anyhow::Result<Vec<TodoEntity>> {
         let items = sqlx::query_as::<_, TodoWithLabelFromRow>(
             r#"
-select todos.id, todos.text, todos.completed, todos.user_id, todos.workspace_id,
-       labels.id as label_id, labels.name as label_name, labels.user_id as label_user_id
+select todos.id, todos.text, todos.completed, todos.status_id, todos.user_id, todos.workspace_id,
+       todos.due_at, todos.start_at, todos.parent_id, todos.auto_complete, todos.deleted_at,
+       todos.recurrence, todos.series_id, todos.priority, todos.position,
+       array(select user_id from todo_assignees ta where ta.todo_id = todos.id order by user_id) as assignee_ids,
+       labels.id as label_id, labels.name as label_name, labels.user_id as label_user_id,
+       labels.workspace_id as label_workspace_id, labels.color as label_color, labels.icon as label_icon
 from todos
+            left outer join todos parent on parent.id = todos.parent_id
             left outer join todo_labels tl on todos.id = tl.todo_id
             left outer join labels on labels.id = tl.label_id
-where todos.workspace_id = $1
+where todos.id = any($1)
+   or (todos.parent_id = any($1) and todos.deleted_at is not distinct from parent.deleted_at)
 order by todos.id desc, labels.id asc;
             "#,
         )
-        .bind(workspace_id)
+        .bind(ids)
         .fetch_all(&self.pool)
         .await?;
 
-        let todos = fold_entities(items);
+        let mut todos = nest_children(fold_entities(items));
+        todos.sort_by_key(|todo| ids.iter().position(|id| *id == todo.id));
         Ok(todos)
     }
 
-    async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+    // One page of the todos matching the query, in the workspace or in all of them.
+    async fn page(&self, workspace_id: Option<i32>, query: TodoQuery) -> anyhow::Result<TodoPage> {
+        let mut count = QueryBuilder::new("select count(*) from todos");
+        push_filters(&mut count, workspace_id, &query);
+        let total: i64 = count.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let order = query.order();
+        let (cmp, dir, nulls_as) = match order {
+            SortOrder::Asc => (">", "asc", "'infinity'"),
+            SortOrder::Desc => ("<", "desc", "'-infinity'"),
+        };
+        let mut page = QueryBuilder::new("select todos.id from todos");
+        push_filters(&mut page, workspace_id, &query);
+        if let Some(cursor) = query.cursor.clone() {
+            match query.sort {
+                TodoSort::Position => {
+                    page.push(format!(" and (todos.position, todos.id) {} (", cmp))
+                        .push_bind(cursor.position)
+                        .push(", ")
+                        .push_bind(cursor.id)
+                        .push(")");
+                }
+                TodoSort::Created => {
+                    page.push(format!(" and todos.id {} ", cmp)).push_bind(cursor.id);
+                }
+                TodoSort::DueAt => {
+                    page.push(format!(
+                        " and (coalesce(todos.due_at, {0}::timestamptz), todos.id) {1} (coalesce(",
+                        nulls_as, cmp
+                    ))
+                    .push_bind(cursor.due_at)
+                    .push(format!("::timestamptz, {}::timestamptz), ", nulls_as))
+                    .push_bind(cursor.id)
+                    .push(")");
+                }
+                TodoSort::Text => {
+                    page.push(format!(r#" and (todos.text collate "C", todos.id) {} ("#, cmp))
+                        .push_bind(cursor.text)
+                        .push(r#"::text collate "C", "#)
+                        .push_bind(cursor.id)
+                        .push(")");
+                }
+            }
+        }
+        match query.sort {
+            TodoSort::Position => page.push(format!(" order by todos.position {0}, todos.id {0}", dir)),
+            TodoSort::Created => page.push(format!(" order by todos.id {}", dir)),
+            TodoSort::DueAt => page.push(format!(
+                " order by coalesce(todos.due_at, {0}::timestamptz) {1}, todos.id {1}",
+                nulls_as, dir
+            )),
+            TodoSort::Text => page.push(format!(r#" order by todos.text collate "C" {0}, todos.id {0}"#, dir)),
+        };
+        let limit = query.limit();
+        page.push(" limit ").push_bind(limit + 1);
+        let mut ids: Vec<i32> = page.build_query_scalar().fetch_all(&self.pool).await?;
+
+        let has_next = ids.len() as i64 > limit;
+        ids.truncate(limit as usize);
+
+        let todos = self.find_by_ids(&ids).await?;
+        let next_cursor = if has_next {
+            todos.last().map(|todo| TodoCursor::after(todo).encode())
+        } else {
+            None
+        };
+
+        Ok(TodoPage {
+            items: todos,
+            next_cursor,
+            total,
+        })
+    }
+}
+
+#[async_trait]
+impl TodoRepository for TodoRepositoryForDb {
+    async fn create(&self, user_id: i32, workspace_id: i32, payload: CreateTodo) -> anyhow::Result<TodoEntity> {
         let mut tx = self.pool.begin().await?;
+        let todo = create_todo(&mut tx, user_id, workspace_id, payload).await?;
 
-        let old_todo = self.find(id).await?;
-        sqlx::query(
+        tx.commit().await?;
+        Ok(todo)
+    }
+
+    async fn find(&self, id: i32) -> anyhow::Result<TodoEntity> {
+        let todo = find_todo(&self.pool, id).await?;
+        if todo.deleted_at.is_some() {
+            return Err(RepositoryError::NotFound(id).into());
+        }
+        Ok(todo)
+    }
+
+    async fn all_by_workspace(&self, workspace_id: i32, query: TodoQuery) -> anyhow::Result<TodoPage> {
+        self.page(Some(workspace_id), query).await
+    }
+
+    async fn all_assigned(&self, user_id: i32, query: TodoQuery) -> anyhow::Result<TodoPage> {
+        // assignees are members, so their todos are never in a workspace they can't see
+        self.page(None, TodoQuery { assignee_id: Some(user_id), ..query }).await
+    }
+
+    async fn search(&self, workspace_ids: &[i32], query: SearchQuery) -> anyhow::Result<Vec<SearchHit>> {
+        let terms = query.terms();
+        if terms.is_empty() || workspace_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // every term is matched as a quoted prefix, so user input never reaches the tsquery parser,
+        // and the text is HTML-escaped before highlighting so only the <mark> tags are markup
+        let rows = sqlx::query_as::<_, SearchHitFromRow>(
             r#"
-update todos set text=$1, completed=$2
-where id = $3
-returning *
+select todos.id,
+       ts_rank(todos.search_vector, query) as rank,
+       ts_headline(
+           'simple',
+           replace(replace(replace(replace(replace(todos.text, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '"', '&quot;'), '''', '&#39;'),
+           query,
+           'StartSel=<mark>, StopSel=</mark>, HighlightAll=true'
+       ) as highlight
+from todos
+            cross join to_tsquery(
+                'simple',
+                array_to_string(array(select quote_literal(term) || ':*' from unnest($2::text[]) as t(term)), ' & ')
+            ) as query
+where todos.workspace_id = any($1)
+  and todos.deleted_at is null
+  and todos.search_vector @@ query
+order by rank desc, todos.id desc
+limit $3
             "#,
         )
-        .bind(payload.text.unwrap_or(old_todo.text))
-        .bind(payload.completed.unwrap_or(old_todo.completed))
-        .bind(id)
-        .fetch_one(&mut *tx)
+        .bind(workspace_ids)
+        .bind(&terms)
+        .bind(query.limit())
+        .fetch_all(&self.pool)
         .await?;
 
-        if let Some(labels) = payload.label_ids {
-            sqlx::query(
-                r#"
-    delete from todo_labels where todo_id=$1
-            "#,
-            )
-            .bind(id)
-            .execute(&mut *tx)
-            .await?;
+        let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+        let todos = self.find_by_ids(&ids).await?;
+        let hits = rows
+            .into_iter()
+            .filter_map(|row| {
+                // subtasks are hits of their own but come back nested under a matching parent
+                let todo = todos
+                    .iter()
+                    .flat_map(|todo| std::iter::once(todo).chain(todo.children.iter()))
+                    .find(|todo| todo.id == row.id)?
+                    .clone();
+                Some(SearchHit {
+                    id: todo.id,
+                    text: todo.text,
+                    completed: todo.completed,
+                    labels: todo.labels,
+                    workspace_id: todo.workspace_id,
+                    highlight: row.highlight,
+                    rank: row.rank,
+                })
+            })
+            .collect();
+        Ok(hits)
+    }
 
-            sqlx::query(
-                r#"
-    insert into todo_labels (todo_id, label_id)
-    select $1, id
-    from unnest($2) as t(id);
-            "#,
-            )
-            .bind(id)
-            .bind(labels)
-            .execute(&mut *tx)
-            .await?;
-        };
+    async fn update(&self, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+        let mut tx = self.pool.begin().await?;
 
-        tx.commit().await?;
-        let todo = self.find(id).await?;
+        let todo = update_todo(&mut tx, id, actor_id, payload).await?;
+        advance_completed_series(&mut tx, &todo, actor_id).await?;
 
+        tx.commit().await?;
         Ok(todo)
     }
 
-    async fn delete(&self, id: i32) -> anyhow::Result<()> {
+    async fn move_todo(&self, id: i32, actor_id: i32, payload: MoveTodo) -> anyhow::Result<TodoEntity> {
         let mut tx = self.pool.begin().await?;
 
-        sqlx::query("delete from todo_labels where todo_id = $1")
-            .bind(id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
-                _ => RepositoryError::Unexpected(e.to_string()),
-            })?;
-
-        sqlx::query("delete from todos where id = $1")
-            .bind(id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
-                _ => RepositoryError::Unexpected(e.to_string()),
-            })?;
+        let todo = move_todo(&mut tx, id, actor_id, payload).await?;
 
         tx.commit().await?;
-        Ok(())
+        Ok(todo)
+    }
+
+    async fn delete(&self, id: i32, actor_id: i32) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_todo = trash_todo(&mut tx, id, actor_id).await?;
+        // skipping an open occurrence keeps the series going
+        if old_todo.parent_id.is_none() && !old_todo.completed {
+            advance_series(&mut tx, &old_todo, actor_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_series(&self, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+        let mut tx = self.pool.begin().await?;
+
+        let later_ids = later_occurrences(&mut tx, id).await?;
+        // status and dates belong to a single occurrence, everything else carries over
+        let carried = UpdateTodo {
+            completed: None,
+            status_id: None,
+            due_at: None,
+            start_at: None,
+            ..payload.clone()
+        };
+        let todo = update_todo(&mut tx, id, actor_id, payload).await?;
+        for later_id in later_ids {
+            update_todo(&mut tx, later_id, actor_id, carried.clone()).await?;
+        }
+        advance_completed_series(&mut tx, &todo, actor_id).await?;
+
+        tx.commit().await?;
+        Ok(todo)
+    }
+
+    async fn delete_series(&self, id: i32, actor_id: i32) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let later_ids = later_occurrences(&mut tx, id).await?;
+        trash_todo(&mut tx, id, actor_id).await?;
+        for later_id in later_ids {
+            trash_todo(&mut tx, later_id, actor_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_trashed(&self, id: i32) -> anyhow::Result<TodoEntity> {
+        let todo = find_todo(&self.pool, id).await?;
+        if todo.deleted_at.is_none() {
+            return Err(RepositoryError::NotFound(id).into());
+        }
+        Ok(todo)
+    }
+
+    async fn all_trashed(&self, workspace_id: i32) -> anyhow::Result<Vec<TodoEntity>> {
+        // subtasks trashed along with their parent come nested, ones trashed on their own are listed
+        let ids = sqlx::query_scalar::<_, i32>(
+            r#"
+select todos.id
+from todos
+            left outer join todos parent on parent.id = todos.parent_id
+where todos.workspace_id = $1
+  and todos.deleted_at is not null
+  and todos.deleted_at is distinct from parent.deleted_at
+order by todos.deleted_at desc, todos.id desc
+            "#,
+        )
+        .bind(workspace_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let todos = self.find_by_ids(&ids).await?;
+        Ok(todos)
+    }
+
+    async fn restore(&self, id: i32, actor_id: i32) -> anyhow::Result<TodoEntity> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_todo = find_todo(&mut *tx, id).await?;
+        let Some(deleted_at) = old_todo.deleted_at else {
+            return Err(RepositoryError::NotFound(id).into());
+        };
+
+        // 別々に削除されたサブタスクはゴミ箱に残す
+        sqlx::query(
+            r#"
+update todos set deleted_at = null
+where (id = $1 or parent_id = $1) and deleted_at = $2
+            "#,
+        )
+        .bind(id)
+        .bind(deleted_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(parent_id) = old_todo.parent_id {
+            sync_parent_completion(&mut tx, parent_id).await?;
+        }
+
+        let todo = find_todo(&mut *tx, id).await?;
+        record_todo_change(&mut tx, actor_id, Some(&old_todo), Some(&todo)).await?;
+
+        tx.commit().await?;
+        Ok(todo)
+    }
+
+    async fn purge_trashed(&self, deleted_before: DateTime<Utc>) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        // subtasks restored or trashed on their own after their parent go along with it
+        let ids = sqlx::query_scalar::<_, i32>(
+            r#"
+select id from todos
+where deleted_at < $1
+   or parent_id in (select id from todos where deleted_at < $1)
+            "#,
+        )
+        .bind(deleted_before)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query("delete from todo_labels where todo_id = any($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+
+        // children first, parent_id keeps a parent around while any of them is
+        let children = sqlx::query("delete from todos where id = any($1) and parent_id is not null")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+        let parents = sqlx::query("delete from todos where id = any($1)")
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(children.rows_affected() + parents.rows_affected())
+    }
+
+    async fn save_recommendations(&self, workspace_id: i32, user_id: i32, texts: Vec<String>) -> anyhow::Result<Vec<Recommendation>> {
+        let mut tx = self.pool.begin().await?;
+
+        // unanswered ones are of no use once expired, decided ones are kept as feedback
+        sqlx::query("delete from recommendations where workspace_id = $1 and status = 'pending' and expires_at <= now()")
+            .bind(workspace_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut recommendations = sqlx::query_as::<_, Recommendation>(
+            r#"
+insert into recommendations (workspace_id, user_id, text, expires_at)
+select $1, $2, text, $4
+from unnest ($3::text[]) with ordinality as t(text, position)
+order by position
+returning id, workspace_id, user_id, text, status, todo_id, expires_at
+            "#,
+        )
+        .bind(workspace_id)
+        .bind(user_id)
+        .bind(texts)
+        .bind(Utc::now() + chrono::Duration::minutes(RECOMMENDATION_TTL_MINUTES))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        recommendations.sort_by_key(|recommendation| recommendation.id);
+        Ok(recommendations)
+    }
+
+    async fn accept_recommendations(&self, workspace_id: i32, actor_id: i32, ids: &[i32]) -> anyhow::Result<Vec<TodoEntity>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut todos = vec![];
+        for recommendation in pending_recommendations(&mut tx, workspace_id, actor_id, ids).await? {
+            let todo = create_todo(&mut tx, actor_id, workspace_id, CreateTodo::new(recommendation.text, vec![])).await?;
+            sqlx::query("update recommendations set status = 'accepted', todo_id = $2, decided_at = now() where id = $1")
+                .bind(recommendation.id)
+                .bind(todo.id)
+                .execute(&mut *tx)
+                .await?;
+            todos.push(todo);
+        }
+
+        tx.commit().await?;
+        Ok(todos)
+    }
+
+    async fn reject_recommendations(&self, workspace_id: i32, user_id: i32, ids: &[i32]) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let pending: Vec<i32> = pending_recommendations(&mut tx, workspace_id, user_id, ids)
+            .await?
+            .iter()
+            .map(|recommendation| recommendation.id)
+            .collect();
+        sqlx::query("update recommendations set status = 'rejected', decided_at = now() where id = any($1)")
+            .bind(pending)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rejected_recommendations(&self, workspace_id: i32, user_id: i32, limit: i64) -> anyhow::Result<Vec<String>> {
+        let texts = sqlx::query_scalar::<_, String>(
+            r#"
+select text from recommendations
+where workspace_id = $1 and user_id = $2 and status = 'rejected'
+order by decided_at desc, id desc
+limit $3
+            "#,
+        )
+        .bind(workspace_id)
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(texts)
+    }
+
+    async fn all_statuses(&self, workspace_id: i32) -> anyhow::Result<Vec<Status>> {
+        status::all(&self.pool, workspace_id).await
+    }
+
+    async fn create_status(&self, workspace_id: i32, actor_id: i32, payload: CreateStatus) -> anyhow::Result<Status> {
+        let mut tx = self.pool.begin().await?;
+
+        let status = status::create(&mut tx, workspace_id, actor_id, payload).await?;
+
+        tx.commit().await?;
+        Ok(status)
+    }
+
+    async fn update_status(&self, workspace_id: i32, id: i32, actor_id: i32, payload: UpdateStatus) -> anyhow::Result<Status> {
+        let mut tx = self.pool.begin().await?;
+
+        let status = status::update(&mut tx, workspace_id, id, actor_id, payload).await?;
+        sync_status_completion(&mut tx, &status, actor_id).await?;
+
+        tx.commit().await?;
+        Ok(status)
+    }
+
+    async fn delete_status(&self, workspace_id: i32, id: i32, actor_id: i32, move_to: Option<i32>) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let target = status::delete(&mut tx, workspace_id, id, actor_id, move_to).await?;
+        sync_status_completion(&mut tx, &target, actor_id).await?;
+
+        tx.commit().await?;
+        Ok(())
     }
 }
 
@@ -244,7 +1225,8 @@ returning *
 #[cfg(feature = "database-test")]
 mod test {
     use super::*;
-    use crate::models::user::CreateUser;
+    use crate::models::{todo::{DueFilter, Frequency, MoveTodo, TodoProgress}, user::CreateUser};
+    use chrono::{Duration, Weekday};
     use crate::{
         repositories::{
             label::{LabelRepository, LabelRepositoryForDb},
@@ -253,7 +1235,7 @@ mod test {
         },
         models::{
             label::CreateLabel,
-            workspace::CreateWorkspace,
+            workspace::{CreateWorkspace, WorkspaceRole},
         },
     };
     use dotenvy::dotenv;
@@ -265,45 +1247,82 @@ mod test {
         let user_id = 1;
         let workspace_id = 1;
         let label_1 = Label {
-            id: 1,
-            name: String::from("label 1"),
-            user_id,
-        };
-        let label_2 = Label {
-            id: 2,
-            name: String::from("label 2"),
-            user_id,
+            color: Some(String::from("#ff0000")),
+            ..Label::new(1, String::from("label 1"), user_id).in_workspace(workspace_id)
         };
+        let label_2 = Label::new(2, String::from("label 2"), user_id).in_workspace(workspace_id);
         let rows = vec![
             TodoWithLabelFromRow {
                 id: 1,
                 text: String::from("todo 1"),
                 completed: false,
+                status_id: 0,
                 user_id,
+                assignee_ids: vec![],
                 workspace_id,
+                due_at: None,
+                start_at: None,
+                parent_id: None,
+                auto_complete: false,
+                deleted_at: None,
+                recurrence: None,
+                series_id: None,
+                priority: None,
+                position: String::new(),
                 label_id: Some(label_1.id),
                 label_name: Some(label_1.name.clone()),
                 label_user_id: Some(user_id),
+                label_workspace_id: Some(workspace_id),
+                label_color: label_1.color.clone(),
+                label_icon: None,
             },
             TodoWithLabelFromRow {
                 id: 1,
                 text: String::from("todo 1"),
                 completed: false,
+                status_id: 0,
                 user_id,
+                assignee_ids: vec![],
                 workspace_id,
+                due_at: None,
+                start_at: None,
+                parent_id: None,
+                auto_complete: false,
+                deleted_at: None,
+                recurrence: None,
+                series_id: None,
+                priority: None,
+                position: String::new(),
                 label_id: Some(label_2.id),
                 label_name: Some(label_2.name.clone()),
                 label_user_id: Some(user_id),
+                label_workspace_id: Some(workspace_id),
+                label_color: None,
+                label_icon: None,
             },
             TodoWithLabelFromRow {
                 id: 2,
                 text: String::from("todo 2"),
                 completed: false,
+                status_id: 0,
                 user_id,
+                assignee_ids: vec![],
                 workspace_id,
+                due_at: None,
+                start_at: None,
+                parent_id: None,
+                auto_complete: false,
+                deleted_at: None,
+                recurrence: None,
+                series_id: None,
+                priority: None,
+                position: String::new(),
                 label_id: Some(label_1.id),
                 label_name: Some(label_1.name.clone()),
                 label_user_id: Some(user_id),
+                label_workspace_id: Some(workspace_id),
+                label_color: label_1.color.clone(),
+                label_icon: None,
             },
         ];
         let res = fold_entities(rows);
@@ -359,127 +1378,1657 @@ mod test {
         let todo = repository.find(created.id).await.expect("[find] returned Err");
         assert_eq!(created, todo);
 
-        let todos = repository.all_by_workspace(test_workspace_id).await.expect("[all_by_workspace] returned Err");
+        let todos = repository.all_by_workspace(test_workspace_id, TodoQuery::default()).await.expect("[all_by_workspace] returned Err").items;
         let todo = todos.first().unwrap();
         assert_eq!(created, *todo);
 
+        let todos = repository
+            .all_by_workspace(test_workspace_id, TodoQuery { due: Some(DueFilter::Overdue), ..Default::default() })
+            .await
+            .expect("[all_by_workspace] returned Err").items;
+        assert!(todos.iter().all(|t| t.id != created.id));
+
+        let due_at = Utc::now() - Duration::hours(1);
         let updated_text = "updated_test_text";
         let todo = repository
-            .update(todo.id, UpdateTodo { text: Some(updated_text.to_string()), completed: Some(true), label_ids: Some(vec![]) })
+            .update(todo.id, test_user_id, UpdateTodo {
+                text: Some(updated_text.to_string()),
+                label_ids: Some(vec![]),
+                due_at: Some(Some(due_at)),
+                priority: Some(Some(Priority::High)),
+                ..Default::default()
+            })
             .await
             .expect("[update] returned Err");
         assert_eq!(created.id, todo.id);
         assert_eq!(todo.text, updated_text);
-        assert!(todo.completed);
         assert!(todo.labels.len() == 0);
+        assert_eq!(todo.due_at.map(|d| d.timestamp()), Some(due_at.timestamp()));
+        assert_eq!(Some(Priority::High), todo.priority);
+
+        // a start sent alone can't pass the due date the todo already has
+        let payload = UpdateTodo { start_at: Some(Some(due_at + Duration::hours(1))), ..Default::default() };
+        let res = repository.update(todo.id, test_user_id, payload).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::StartAfterDue(_))));
+
+        let todos = repository
+            .all_by_workspace(test_workspace_id, TodoQuery { due: Some(DueFilter::Overdue), ..Default::default() })
+            .await
+            .expect("[all_by_workspace] returned Err").items;
+        assert!(todos.iter().any(|t| t.id == created.id));
+
+        let todo = repository
+            .update(todo.id, test_user_id, UpdateTodo { completed: Some(true), ..Default::default() })
+            .await
+            .expect("[update] returned Err");
+        assert!(todo.completed);
+        assert!(todo.due_at.is_some());
+        assert_eq!(Some(Priority::High), todo.priority);
+
+        let todos = repository
+            .all_by_workspace(test_workspace_id, TodoQuery { due: Some(DueFilter::Overdue), ..Default::default() })
+            .await
+            .expect("[all_by_workspace] returned Err").items;
+        assert!(todos.iter().all(|t| t.id != created.id));
 
-        let _ = repository.delete(todo.id).await.expect("[delete] returned Err");
+        let _ = repository.delete(todo.id, test_user_id).await.expect("[delete] returned Err");
         let res = repository.find(created.id).await;
         assert!(res.is_err());
     }
-}
-
-#[cfg(test)]
-pub mod test_utils {
-    use anyhow::Context;
-    use::std::{
-        collections::HashMap,
-        sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
-    };
-    use super::*;
 
-    type TodoData = HashMap<i32, TodoEntity>;
+    #[tokio::test]
+    async fn subtask_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
 
-    #[derive(Debug, Clone)]
-    pub struct TodoRepositoryForMemory {
-        store: Arc<RwLock<TodoData>>,
-        labels: Vec<Label>,
-    }
+        let test_user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_todo_subtask_user".to_string(), "test_todo_subtask_user".to_string(), "todo_subtask_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_todo_subtask_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let label = LabelRepositoryForDb::new(pool.clone())
+            .create_in_workspace(workspace.id, test_user.id, CreateLabel::new("test_todo_subtask_label".to_string()))
+            .await
+            .expect("Failed to create test label");
 
-    impl TodoRepositoryForMemory {
-        pub fn new(labels: Vec<Label>) -> Self {
-            TodoRepositoryForMemory {
-                store: Arc::default(),
-                labels,
-            }
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let mut payload = CreateTodo::new("parent".to_string(), vec![]);
+        payload.auto_complete = true;
+        let parent = repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        let mut children = vec![];
+        for text in ["step 1", "step 2"] {
+            let mut payload = CreateTodo::new(text.to_string(), vec![label.id]);
+            payload.parent_id = Some(parent.id);
+            children.push(repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err"));
         }
 
-        fn write_store_ref(&self) -> RwLockWriteGuard<TodoData> {
-            self.store.write().unwrap()
-        }
+        // children come nested, never on their own
+        let page = repository
+            .all_by_workspace(workspace.id, TodoQuery::default())
+            .await
+            .expect("[all_by_workspace] returned Err");
+        assert_eq!(1, page.total);
+        assert_eq!(vec![parent.id], page.items.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert_eq!(children, page.items[0].children);
+        assert_eq!(TodoProgress { completed: 0, total: 2 }, page.items[0].progress);
 
-        fn read_store_ref(&self) -> RwLockReadGuard<TodoData> {
-            self.store.read().unwrap()
+        // auto complete follows the children both ways
+        for child in &children {
+            repository
+                .update(child.id, test_user.id, UpdateTodo { completed: Some(true), ..Default::default() })
+                .await
+                .expect("[update] returned Err");
         }
+        let found = repository.find(parent.id).await.expect("[find] returned Err");
+        assert!(found.completed);
+        assert_eq!(TodoProgress { completed: 2, total: 2 }, found.progress);
+        repository
+            .update(children[0].id, test_user.id, UpdateTodo { completed: Some(false), ..Default::default() })
+            .await
+            .expect("[update] returned Err");
+        assert!(!repository.find(parent.id).await.unwrap().completed);
 
-        fn resolve_labels(&self, labels: Vec<i32>) -> Vec<Label> {
-            labels
-                .iter()
-                .filter_map(|id| {
-                    self.labels.iter().find(|label| label.id == *id).cloned()
-                })
-                .collect()
-        }
+        // deleting the last open child completes the parent, deleting the parent removes the rest
+        repository.delete(children[0].id, test_user.id).await.expect("[delete] returned Err");
+        assert!(repository.find(parent.id).await.unwrap().completed);
+        repository.delete(parent.id, test_user.id).await.expect("[delete] returned Err");
+        assert!(repository.find(children[1].id).await.is_err());
     }
 
-    #[async_trait]
-    impl TodoRepository for TodoRepositoryForMemory {
-        async fn create(&self, user_id: i32, workspace_id: i32, payload: CreateTodo) -> anyhow::Result<TodoEntity> {
-            let mut store = self.write_store_ref();
-            let id = (store.len() + 1) as i32;
-            let labels = self.resolve_labels(payload.label_ids);
-            let todo = TodoEntity::new(id, payload.text.clone(), labels, user_id, workspace_id);
-            store.insert(id, todo.clone());
-            Ok(todo)
-        }
+    #[tokio::test]
+    async fn trash_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
 
-        async fn find(&self, id: i32) -> anyhow::Result<TodoEntity> {
-            let store = self.read_store_ref();
-            let todo = store
-                .get(&id)
-                .map(|todo| todo.clone())
-                .ok_or(RepositoryError::NotFound(id))?;
-            Ok(todo)
-        }
+        let test_user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_todo_trash_user".to_string(), "test_todo_trash_user".to_string(), "todo_trash_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_todo_trash_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
 
-        async fn all_by_workspace(&self, workspace_id: i32) -> anyhow::Result<Vec<TodoEntity>> {
-            let store = self.read_store_ref();
-            Ok(store.values()
-                .filter(|todo| todo.workspace_id == workspace_id)
-                .cloned()
-                .collect())
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let parent = repository
+            .create(test_user.id, workspace.id, CreateTodo::new("trashed parent".to_string(), vec![]))
+            .await
+            .expect("[create] returned Err");
+        let mut children = vec![];
+        for text in ["trashed alone", "trashed along"] {
+            let mut payload = CreateTodo::new(text.to_string(), vec![]);
+            payload.parent_id = Some(parent.id);
+            children.push(repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err"));
         }
 
-        async fn update(&self, id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
-            let mut store = self.write_store_ref();
-            let todo = store.get(&id).context(RepositoryError::NotFound(id))?;
-            let text = payload.text.unwrap_or(todo.text.clone());
-            let completed = payload.completed.unwrap_or(todo.completed);
-            let labels = match payload.label_ids {
-                Some(label_ids) => self.resolve_labels(label_ids),
-                None => todo.labels.clone(),
-            };
-            let todo = TodoEntity {
-                id,
-                text,
-                completed,
-                labels,
-                user_id: todo.user_id,
-                workspace_id: todo.workspace_id,
-            };
-            store.insert(id, todo.clone());
-            Ok(todo)
-        }
+        repository.delete(children[0].id, test_user.id).await.expect("[delete] returned Err");
+        repository.delete(parent.id, test_user.id).await.expect("[delete] returned Err");
+        assert!(repository.delete(parent.id, test_user.id).await.is_err());
+        assert!(repository.find(parent.id).await.is_err());
+        let page = repository
+            .all_by_workspace(workspace.id, TodoQuery::default())
+            .await
+            .expect("[all_by_workspace] returned Err");
+        assert_eq!(0, page.total);
 
-        async fn delete(&self, id: i32) -> anyhow::Result<()> {
-            let mut store = self.write_store_ref();
-            store.remove(&id).ok_or(RepositoryError::NotFound(id))?;
-            Ok(())
-        }
-    }
+        // the child trashed with its parent comes nested, the other one is listed on its own
+        let trashed = repository.all_trashed(workspace.id).await.expect("[all_trashed] returned Err");
+        assert_eq!(vec![parent.id, children[0].id], trashed.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert_eq!(vec![children[1].id], trashed[0].children.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert!(trashed.iter().all(|t| t.deleted_at.is_some()));
 
-    mod test {
+        let restored = repository.restore(parent.id, test_user.id).await.expect("[restore] returned Err");
+        assert_eq!(None, restored.deleted_at);
+        assert_eq!(vec![children[1].id], restored.children.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert!(repository.restore(parent.id, test_user.id).await.is_err());
+        let trashed = repository.all_trashed(workspace.id).await.unwrap();
+        assert_eq!(vec![children[0].id], trashed.iter().map(|t| t.id).collect::<Vec<_>>());
+
+        // only todos trashed before the cutoff are purged
+        repository.purge_trashed(Utc::now() - chrono::Duration::days(1)).await.expect("[purge_trashed] returned Err");
+        assert!(repository.find_trashed(children[0].id).await.is_ok());
+        let purged = repository.purge_trashed(Utc::now() + chrono::Duration::seconds(1)).await.expect("[purge_trashed] returned Err");
+        assert!(purged >= 1);
+        assert!(repository.find_trashed(children[0].id).await.is_err());
+        assert!(repository.find(parent.id).await.is_ok());
+
+        // a subtask restored on its own goes when its parent is purged
+        repository.delete(parent.id, test_user.id).await.expect("[delete] returned Err");
+        repository.restore(children[1].id, test_user.id).await.expect("[restore] returned Err");
+        let purged = repository.purge_trashed(Utc::now() + chrono::Duration::seconds(1)).await.expect("[purge_trashed] returned Err");
+        assert!(purged >= 2);
+        assert!(repository.find_trashed(parent.id).await.is_err());
+        assert!(repository.find(children[1].id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn move_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let test_user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_todo_move_user".to_string(), "test_todo_move_user".to_string(), "todo_move_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_todo_move_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let mut todos = vec![];
+        for text in ["x", "y", "z"] {
+            todos.push(
+                repository
+                    .create(test_user.id, workspace.id, CreateTodo::new(text.to_string(), vec![]))
+                    .await
+                    .expect("[create] returned Err"),
+            );
+        }
+        let (x, y, z) = (todos[0].id, todos[1].id, todos[2].id);
+        let texts = |page: TodoPage| page.items.into_iter().map(|t| t.text).collect::<Vec<_>>();
+        let page = repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap();
+        assert_eq!(vec!["z", "y", "x"], texts(page));
+
+        repository
+            .move_todo(x, test_user.id, MoveTodo { before: Some(z), after: None })
+            .await
+            .expect("[move_todo] returned Err");
+        repository
+            .move_todo(z, test_user.id, MoveTodo { before: None, after: Some(y) })
+            .await
+            .expect("[move_todo] returned Err");
+        let query = TodoQuery { limit: Some(2), ..Default::default() };
+        let page = repository.all_by_workspace(workspace.id, query.clone()).await.unwrap();
+        let cursor = TodoCursor::decode(page.next_cursor.as_ref().expect("no next cursor")).unwrap();
+        assert_eq!(vec!["x", "y"], texts(page));
+        let page = repository
+            .all_by_workspace(workspace.id, TodoQuery { cursor: Some(cursor), ..query })
+            .await
+            .unwrap();
+        assert_eq!(vec!["z"], texts(page));
+
+        // subtasks are ordered among themselves, never next to a top level todo
+        let mut children = vec![];
+        for text in ["step 1", "step 2"] {
+            let mut payload = CreateTodo::new(text.to_string(), vec![]);
+            payload.parent_id = Some(x);
+            children.push(repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err"));
+        }
+        assert!(repository.move_todo(children[0].id, test_user.id, MoveTodo { before: Some(y), after: None }).await.is_err());
+        repository
+            .move_todo(children[1].id, test_user.id, MoveTodo { before: Some(children[0].id), after: None })
+            .await
+            .expect("[move_todo] returned Err");
+        let parent = repository.find(x).await.unwrap();
+        assert_eq!(vec![children[1].id, children[0].id], parent.children.iter().map(|t| t.id).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn status_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let test_user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_todo_status_user".to_string(), "test_todo_status_user".to_string(), "todo_status_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_todo_status_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let statuses = repository.all_statuses(workspace.id).await.expect("[all_statuses] returned Err");
+        let names: Vec<&str> = statuses.iter().map(|status| status.name.as_str()).collect();
+        assert_eq!(vec!["backlog", "doing", "review", "done"], names);
+        let (backlog, doing, done) = (&statuses[0], &statuses[1], &statuses[3]);
+
+        let todo = repository
+            .create(test_user.id, workspace.id, CreateTodo::new("status todo".to_string(), vec![]))
+            .await
+            .expect("[create] returned Err");
+        assert_eq!((backlog.id, false), (todo.status_id, todo.completed));
+
+        // completing picks the terminal column, moving out of it reopens
+        let payload = UpdateTodo { completed: Some(true), ..Default::default() };
+        let todo = repository.update(todo.id, test_user.id, payload).await.expect("[update] returned Err");
+        assert_eq!((done.id, true), (todo.status_id, todo.completed));
+        let payload = UpdateTodo { status_id: Some(doing.id), ..Default::default() };
+        let todo = repository.update(todo.id, test_user.id, payload).await.expect("[update] returned Err");
+        assert_eq!((doing.id, false), (todo.status_id, todo.completed));
+
+        let payload = UpdateStatus { wip_limit: Some(Some(1)), ..Default::default() };
+        repository.update_status(workspace.id, doing.id, test_user.id, payload).await.expect("[update_status] returned Err");
+        let mut payload = CreateTodo::new("over the limit".to_string(), vec![]);
+        payload.status_id = Some(doing.id);
+        payload.new_labels = vec![CreateLabel::new("test_todo_status_label".to_string())];
+        let res = repository.create(test_user.id, workspace.id, payload.clone()).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::WipLimit(id)) if *id == doing.id));
+        // labels to create go with the todo, or not at all
+        let label_repository = LabelRepositoryForDb::new(pool.clone());
+        assert!(label_repository.all_by_workspace(workspace.id, test_user.id).await.unwrap().is_empty());
+        payload.status_id = None;
+        let labelled = repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        assert_eq!(label_repository.all_by_workspace(workspace.id, test_user.id).await.unwrap(), labelled.labels);
+        let mut payload = CreateTodo::new("unknown".to_string(), vec![]);
+        payload.status_id = Some(-1);
+        let res = repository.create(test_user.id, workspace.id, payload).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::UnknownStatus(_))));
+
+        // a new terminal column moved to the front
+        let payload = CreateStatus::new("shipped".to_string(), true);
+        let shipped = repository.create_status(workspace.id, test_user.id, payload).await.expect("[create_status] returned Err");
+        let res = repository.create_status(workspace.id, test_user.id, CreateStatus::new("shipped".to_string(), false)).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::Duplicate(_))));
+        let payload = UpdateStatus { position: Some(0), ..Default::default() };
+        repository.update_status(workspace.id, shipped.id, test_user.id, payload).await.expect("[update_status] returned Err");
+        let ids: Vec<i32> = repository.all_statuses(workspace.id).await.unwrap().iter().map(|status| status.id).collect();
+        assert_eq!(vec![shipped.id, backlog.id, doing.id, statuses[2].id, done.id], ids);
+
+        let res = repository.delete_status(workspace.id, doing.id, test_user.id, None).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::InUse(id)) if *id == doing.id));
+        // the column taking the todos keeps to its WIP limit
+        let mut payload = CreateTodo::new("already shipped".to_string(), vec![]);
+        payload.status_id = Some(shipped.id);
+        repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        let payload = UpdateStatus { wip_limit: Some(Some(1)), ..Default::default() };
+        repository.update_status(workspace.id, shipped.id, test_user.id, payload).await.expect("[update_status] returned Err");
+        let res = repository.delete_status(workspace.id, doing.id, test_user.id, Some(shipped.id)).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::WipLimit(id)) if *id == shipped.id));
+        let payload = UpdateStatus { wip_limit: Some(None), ..Default::default() };
+        repository.update_status(workspace.id, shipped.id, test_user.id, payload).await.expect("[update_status] returned Err");
+        repository
+            .delete_status(workspace.id, doing.id, test_user.id, Some(shipped.id))
+            .await
+            .expect("[delete_status] returned Err");
+        let todo = repository.find(todo.id).await.unwrap();
+        assert_eq!((shipped.id, true), (todo.status_id, todo.completed));
+
+        // turning the only open column terminal leaves nothing to reopen into
+        repository.delete_status(workspace.id, statuses[2].id, test_user.id, None).await.expect("[delete_status] returned Err");
+        let payload = UpdateStatus { is_terminal: Some(true), ..Default::default() };
+        let res = repository.update_status(workspace.id, backlog.id, test_user.id, payload).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::LastStatus(_))));
+        let payload = UpdateStatus { is_terminal: Some(false), ..Default::default() };
+        repository.update_status(workspace.id, shipped.id, test_user.id, payload).await.expect("[update_status] returned Err");
+        let todo = repository.find(todo.id).await.unwrap();
+        assert_eq!((shipped.id, false), (todo.status_id, todo.completed));
+
+        // completing by turning the column terminal schedules the next occurrence too
+        let mut payload = CreateTodo::new("water the plants".to_string(), vec![]);
+        payload.due_at = Some("2026-06-01T09:00:00Z".parse().unwrap());
+        payload.recurrence = Some(Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            weekdays: vec![],
+            month_day: None,
+            until: None,
+            tz_offset: 0,
+        });
+        payload.status_id = Some(backlog.id);
+        let first = repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        let payload = UpdateStatus { is_terminal: Some(true), ..Default::default() };
+        repository.update_status(workspace.id, backlog.id, test_user.id, payload).await.expect("[update_status] returned Err");
+        let page = repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap();
+        let series: Vec<&TodoEntity> = page.items.iter().filter(|todo| todo.series_id == Some(first.id)).collect();
+        assert_eq!(2, series.len());
+        assert!(series.iter().any(|todo| todo.id == first.id && todo.completed));
+        assert!(series.iter().any(|todo| todo.id != first.id && !todo.completed));
+    }
+
+    #[tokio::test]
+    async fn assignee_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let test_user = user_repository
+            .create(CreateUser::new("auth0|test_todo_assignee_user".to_string(), "test_todo_assignee_user".to_string(), "todo_assignee_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let other_user = user_repository
+            .create(CreateUser::new("auth0|test_todo_assignee_other".to_string(), "test_todo_assignee_other".to_string(), "todo_assignee_other@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace_repository = WorkspaceRepositoryForDb::new(pool.clone());
+        let workspace = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_todo_assignee_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let other_workspace = workspace_repository
+            .create(other_user.id, CreateWorkspace::new("test_todo_assignee_other_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        workspace_repository
+            .add_member(other_workspace.id, other_user.id, test_user.id, WorkspaceRole::Editor)
+            .await
+            .expect("Failed to add member");
+
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let mut payload = CreateTodo::new("mine".to_string(), vec![]);
+        payload.assignee_ids = vec![test_user.id, test_user.id];
+        let mine = repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        assert_eq!(vec![test_user.id], mine.assignee_ids);
+
+        let mut payload = CreateTodo::new("stranger's".to_string(), vec![]);
+        payload.assignee_ids = vec![other_user.id];
+        let res = repository.create(test_user.id, workspace.id, payload).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::UnknownAssignee(id)) if *id == other_user.id));
+
+        let mut payload = CreateTodo::new("shared".to_string(), vec![]);
+        payload.assignee_ids = vec![other_user.id, test_user.id];
+        let shared = repository.create(other_user.id, other_workspace.id, payload).await.expect("[create] returned Err");
+        assert_eq!(vec![test_user.id, other_user.id], shared.assignee_ids);
+        repository
+            .create(other_user.id, other_workspace.id, CreateTodo::new("nobody's".to_string(), vec![]))
+            .await
+            .expect("[create] returned Err");
+
+        let texts = |page: TodoPage| page.items.into_iter().map(|t| t.text).collect::<Vec<_>>();
+        let query = TodoQuery { sort: TodoSort::Text, ..Default::default() };
+        let page = repository.all_assigned(test_user.id, query.clone()).await.unwrap();
+        assert_eq!(vec!["mine", "shared"], texts(page));
+        let page = repository
+            .all_by_workspace(other_workspace.id, TodoQuery { assigned: Some(false), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(vec!["nobody's"], texts(page));
+
+        // leaving the workspace drops the assignments there
+        workspace_repository
+            .remove_member(other_workspace.id, other_user.id, test_user.id)
+            .await
+            .expect("Failed to remove member");
+        let page = repository.all_assigned(test_user.id, query).await.unwrap();
+        assert_eq!(vec!["mine"], texts(page));
+        assert_eq!(vec![other_user.id], repository.find(shared.id).await.unwrap().assignee_ids);
+
+        let payload = UpdateTodo { assignee_ids: Some(vec![]), ..Default::default() };
+        let mine = repository.update(mine.id, test_user.id, payload).await.expect("[update] returned Err");
+        assert!(mine.assignee_ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn recurrence_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let test_user = UserRepositoryForDb::new(pool.clone())
+            .create(CreateUser::new("auth0|test_todo_recurrence_user".to_string(), "test_todo_recurrence_user".to_string(), "todo_recurrence_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_todo_recurrence_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let label = LabelRepositoryForDb::new(pool.clone())
+            .create_in_workspace(workspace.id, test_user.id, CreateLabel::new("test_todo_recurrence_label".to_string()))
+            .await
+            .expect("Failed to create test label");
+
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let recurrence = Recurrence {
+            frequency: Frequency::Weekly,
+            interval: 1,
+            weekdays: vec![Weekday::Mon, Weekday::Thu],
+            month_day: None,
+            until: None,
+            tz_offset: 0,
+        };
+        let mut payload = CreateTodo::new("take out the trash".to_string(), vec![label.id]);
+        payload.due_at = Some("2026-06-01T09:00:00Z".parse().unwrap());
+        payload.recurrence = Some(recurrence.clone());
+        let first = repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        assert_eq!(Some(first.id), first.series_id);
+        assert_eq!(Some(recurrence), first.recurrence);
+
+        let occurrences = |page: TodoPage| -> Vec<TodoEntity> {
+            let mut todos: Vec<TodoEntity> = page.items.into_iter().filter(|todo| todo.series_id == Some(first.id)).collect();
+            todos.sort_by_key(|todo| todo.id);
+            todos
+        };
+
+        // completing an occurrence schedules the next one, once
+        for completed in [true, false, true] {
+            repository
+                .update(first.id, test_user.id, UpdateTodo { completed: Some(completed), ..Default::default() })
+                .await
+                .expect("[update] returned Err");
+        }
+        let todos = occurrences(repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap());
+        assert_eq!(2, todos.len());
+        let second = todos[1].clone();
+        assert!(!second.completed);
+        assert_eq!(Some("2026-06-04T09:00:00Z".parse().unwrap()), second.due_at);
+        assert_eq!(vec![label.clone()], second.labels);
+
+        // edits of the whole series leave the completion of past occurrences alone
+        repository
+            .update_series(first.id, test_user.id, UpdateTodo { text: Some("empty the bins".to_string()), ..Default::default() })
+            .await
+            .expect("[update_series] returned Err");
+        let todos = occurrences(repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap());
+        assert!(todos.iter().all(|todo| todo.text == "empty the bins"));
+        assert_eq!(vec![true, false], todos.iter().map(|todo| todo.completed).collect::<Vec<_>>());
+
+        // skipping one occurrence keeps the series going, deleting all future ones ends it
+        repository.delete(second.id, test_user.id).await.expect("[delete] returned Err");
+        let todos = occurrences(repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap());
+        assert_eq!(2, todos.len());
+        let third = todos[1].clone();
+        assert_eq!(Some("2026-06-08T09:00:00Z".parse().unwrap()), third.due_at);
+
+        repository.delete_series(third.id, test_user.id).await.expect("[delete_series] returned Err");
+        let todos = occurrences(repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap());
+        assert_eq!(vec![first.id], todos.iter().map(|todo| todo.id).collect::<Vec<_>>());
+
+        // a monthly rule keeps the day the series started on once a short month is behind it
+        let mut payload = CreateTodo::new("pay the rent".to_string(), vec![]);
+        payload.due_at = Some("2026-01-31T09:00:00Z".parse().unwrap());
+        payload.recurrence = Some(Recurrence {
+            frequency: Frequency::Monthly,
+            interval: 1,
+            weekdays: vec![],
+            month_day: None,
+            until: None,
+            tz_offset: 0,
+        });
+        let rent = repository.create(test_user.id, workspace.id, payload).await.expect("[create] returned Err");
+        assert_eq!(Some(31), rent.recurrence.as_ref().and_then(|recurrence| recurrence.month_day));
+        let mut due = vec![];
+        let mut todo = rent;
+        for _ in 0..2 {
+            repository
+                .update(todo.id, test_user.id, UpdateTodo { completed: Some(true), ..Default::default() })
+                .await
+                .expect("[update] returned Err");
+            let page = repository.all_by_workspace(workspace.id, TodoQuery { completed: Some(false), ..Default::default() }).await.unwrap();
+            todo = page.items.into_iter().find(|next| next.series_id == todo.series_id).expect("no next occurrence");
+            due.extend(todo.due_at);
+        }
+        let expected: Vec<DateTime<Utc>> = vec!["2026-02-28T09:00:00Z".parse().unwrap(), "2026-03-31T09:00:00Z".parse().unwrap()];
+        assert_eq!(expected, due);
+    }
+
+    #[tokio::test]
+    async fn query_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let test_user = user_repository
+            .create(CreateUser::new("auth0|test_todo_query_user".to_string(), "test_todo_query_user".to_string(), "todo_query_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace = WorkspaceRepositoryForDb::new(pool.clone())
+            .create(test_user.id, CreateWorkspace::new("test_todo_query_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let label = LabelRepositoryForDb::new(pool.clone())
+            .create_in_workspace(workspace.id, test_user.id, CreateLabel::new("test_todo_query_label".to_string()))
+            .await
+            .expect("Failed to create test label");
+
+        // the in-memory repository has to page through the same data in the same order
+        let db = TodoRepositoryForDb::new(pool.clone());
+        let memory = test_utils::TodoRepositoryForMemory::new(vec![label.clone()]);
+        let now = Utc::now();
+        let fixtures = vec![
+            ("b_task", Some(now + Duration::days(2)), vec![label.id], false),
+            ("a_task", None, vec![], true),
+            ("c_task", Some(now - Duration::days(1)), vec![label.id], false),
+            ("B_task", Some(now + Duration::days(2)), vec![], false),
+            ("d_task", None, vec![label.id], false),
+        ];
+        for (text, due_at, label_ids, completed) in fixtures {
+            let mut payload = CreateTodo::new(text.to_string(), label_ids);
+            payload.due_at = due_at;
+            for repository in [&db as &dyn TodoRepository, &memory] {
+                let todo = repository.create(test_user.id, workspace.id, payload.clone()).await.unwrap();
+                repository
+                    .update(todo.id, test_user.id, UpdateTodo { completed: Some(completed), ..Default::default() })
+                    .await
+                    .unwrap();
+            }
+        }
+
+        async fn collect(repository: &dyn TodoRepository, workspace_id: i32, query: TodoQuery) -> (Vec<String>, i64) {
+            let mut texts = vec![];
+            let mut query = TodoQuery { limit: Some(2), ..query };
+            loop {
+                let page = repository.all_by_workspace(workspace_id, query.clone()).await.unwrap();
+                texts.extend(page.items.iter().map(|t| t.text.clone()));
+                match page.next_cursor {
+                    Some(cursor) => query.cursor = Some(TodoCursor::decode(&cursor).unwrap()),
+                    None => return (texts, page.total),
+                }
+            }
+        }
+
+        let queries = vec![
+            (TodoQuery::default(), vec!["d_task", "B_task", "c_task", "a_task", "b_task"]),
+            (TodoQuery { sort: TodoSort::Text, ..Default::default() }, vec!["B_task", "a_task", "b_task", "c_task", "d_task"]),
+            (TodoQuery { sort: TodoSort::DueAt, ..Default::default() }, vec!["c_task", "b_task", "B_task", "a_task", "d_task"]),
+            (
+                TodoQuery { sort: TodoSort::DueAt, order: Some(SortOrder::Desc), ..Default::default() },
+                vec!["B_task", "b_task", "c_task", "d_task", "a_task"],
+            ),
+            (TodoQuery { completed: Some(false), label_ids: vec![label.id], ..Default::default() }, vec!["d_task", "c_task", "b_task"]),
+            (TodoQuery { q: Some("b_".to_string()), ..Default::default() }, vec!["B_task", "b_task"]),
+            (TodoQuery { user_id: Some(test_user.id + 1), ..Default::default() }, vec![]),
+        ];
+        for (query, expected) in queries {
+            let (texts, total) = collect(&db, workspace.id, query.clone()).await;
+            assert_eq!(expected, texts, "{:?}", query);
+            assert_eq!(expected.len() as i64, total);
+            assert_eq!((texts, total), collect(&memory, workspace.id, query).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn search_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let test_user = user_repository
+            .create(CreateUser::new("auth0|test_todo_search_user".to_string(), "test_todo_search_user".to_string(), "todo_search_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace_repository = WorkspaceRepositoryForDb::new(pool.clone());
+        let workspace = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_todo_search_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let label = LabelRepositoryForDb::new(pool.clone())
+            .create_in_workspace(workspace.id, test_user.id, CreateLabel::new("finance".to_string()))
+            .await
+            .expect("Failed to create test label");
+        let other_workspace = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_todo_search_other_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let rent = repository
+            .create(test_user.id, workspace.id, CreateTodo::new("Pay rent tomorrow".to_string(), vec![]))
+            .await
+            .unwrap();
+        let review = repository
+            .create(test_user.id, workspace.id, CreateTodo::new("Review budget for renting".to_string(), vec![label.id]))
+            .await
+            .unwrap();
+        repository
+            .create(test_user.id, other_workspace.id, CreateTodo::new("Pay rent".to_string(), vec![]))
+            .await
+            .unwrap();
+
+        let search = |q: &str| SearchQuery { q: q.to_string(), limit: None };
+        let hits = repository.search(&[workspace.id], search("rent")).await.expect("[search] returned Err");
+        assert_eq!(vec![review.id, rent.id], hits.iter().map(|hit| hit.id).collect::<Vec<_>>());
+        assert_eq!("Pay <mark>rent</mark> tomorrow", hits[1].highlight);
+        assert!(hits.iter().all(|hit| hit.workspace_id == workspace.id));
+
+        let hits = repository.search(&[workspace.id], search("budget fin")).await.unwrap();
+        assert_eq!(vec![review.id], hits.iter().map(|hit| hit.id).collect::<Vec<_>>());
+        assert_eq!(vec![label.clone()], hits[0].labels);
+
+        // label changes keep the search vector up to date
+        repository
+            .update(rent.id, test_user.id, UpdateTodo { label_ids: Some(vec![label.id]), ..Default::default() })
+            .await
+            .unwrap();
+        let hits = repository.search(&[workspace.id], search("finance")).await.unwrap();
+        assert_eq!(2, hits.len());
+        // a match in the text outranks a match in a label name
+        let hits = repository.search(&[workspace.id], search("pay")).await.unwrap();
+        assert_eq!(vec![rent.id], hits.iter().map(|hit| hit.id).collect::<Vec<_>>());
+        let hits = repository.search(&[workspace.id], search("budget")).await.unwrap();
+        assert!(hits[0].rank > repository.search(&[workspace.id], search("finance")).await.unwrap()[0].rank);
+
+        let hits = repository.search(&[workspace.id, other_workspace.id], search("pay rent")).await.unwrap();
+        assert_eq!(2, hits.len());
+        let hits = repository.search(&[], search("rent")).await.unwrap();
+        assert!(hits.is_empty());
+        let hits = repository.search(&[workspace.id], search("' & !")).await.unwrap();
+        assert!(hits.is_empty());
+
+        // user text never comes back as markup
+        let script = repository
+            .create(test_user.id, other_workspace.id, CreateTodo::new("Pay rent <script>alert('x')</script>".to_string(), vec![]))
+            .await
+            .unwrap();
+        let hits = repository.search(&[other_workspace.id], search("rent")).await.unwrap();
+        assert_eq!(script.id, hits[0].id);
+        assert_eq!("Pay <mark>rent</mark> &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;", hits[0].highlight);
+    }
+
+    #[tokio::test]
+    async fn recommendation_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let test_user = user_repository
+            .create(CreateUser::new("auth0|test_todo_recommendation_user".to_string(), "test_todo_recommendation_user".to_string(), "todo_recommendation_user@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let other_user = user_repository
+            .create(CreateUser::new("auth0|test_todo_recommendation_other".to_string(), "test_todo_recommendation_other".to_string(), "todo_recommendation_other@example.com".to_string()))
+            .await
+            .expect("Failed to create test user");
+        let workspace_repository = WorkspaceRepositoryForDb::new(pool.clone());
+        let workspace = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_todo_recommendation_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+        let other_workspace = workspace_repository
+            .create(test_user.id, CreateWorkspace::new("test_todo_recommendation_other_workspace".to_string(), false, vec![]))
+            .await
+            .expect("Failed to create test workspace");
+
+        let repository = TodoRepositoryForDb::new(pool.clone());
+        let texts = vec!["pack a bag".to_string(), "renew passport".to_string(), "call the hotel".to_string()];
+        let recommendations = repository
+            .save_recommendations(workspace.id, test_user.id, texts.clone())
+            .await
+            .expect("[save_recommendations] returned Err");
+        assert_eq!(texts, recommendations.iter().map(|r| r.text.clone()).collect::<Vec<_>>());
+        assert!(recommendations.iter().all(|r| r.status == RecommendationStatus::Pending && !r.is_expired()));
+        let ids: Vec<i32> = recommendations.iter().map(|r| r.id).collect();
+
+        // nothing is created unless every recommendation can be accepted
+        let res = repository.accept_recommendations(workspace.id, test_user.id, &[ids[0], ids[2] + 1000]).await;
+        assert!(res.is_err());
+        let res = repository.accept_recommendations(other_workspace.id, test_user.id, &[ids[0]]).await;
+        assert!(res.is_err());
+        assert_eq!(0, repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap().total);
+
+        let todos = repository
+            .accept_recommendations(workspace.id, test_user.id, &[ids[0], ids[2], ids[0]])
+            .await
+            .expect("[accept_recommendations] returned Err");
+        assert_eq!(vec!["pack a bag", "call the hotel"], todos.iter().map(|t| t.text.as_str()).collect::<Vec<_>>());
+        assert!(todos.iter().all(|t| t.workspace_id == workspace.id && t.user_id == test_user.id));
+        assert_eq!(2, repository.all_by_workspace(workspace.id, TodoQuery::default()).await.unwrap().total);
+
+        let res = repository.accept_recommendations(workspace.id, test_user.id, &[ids[0]]).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::Duplicate(id)) if *id == ids[0]));
+        let res = repository.reject_recommendations(workspace.id, test_user.id, &[ids[2]]).await;
+        assert!(res.is_err());
+
+        // only the member they were made for answers them
+        let res = repository.reject_recommendations(workspace.id, other_user.id, &[ids[1]]).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::NotFound(_))));
+        assert!(repository.accept_recommendations(workspace.id, other_user.id, &[ids[1]]).await.is_err());
+
+        repository.reject_recommendations(workspace.id, test_user.id, &[ids[1]]).await.expect("[reject_recommendations] returned Err");
+        let rejected = repository.rejected_recommendations(workspace.id, test_user.id, 20).await.unwrap();
+        assert_eq!(vec!["renew passport"], rejected);
+        assert!(repository.rejected_recommendations(other_workspace.id, test_user.id, 20).await.unwrap().is_empty());
+        assert!(repository.rejected_recommendations(workspace.id, other_user.id, 20).await.unwrap().is_empty());
+        assert!(repository.rejected_recommendations(workspace.id, test_user.id, 0).await.unwrap().is_empty());
+
+        // an accepted recommendation outlives the todo it became
+        repository.delete(todos[0].id, test_user.id).await.unwrap();
+        repository.purge_trashed(Utc::now() + Duration::seconds(1)).await.unwrap();
+        let res = repository.accept_recommendations(workspace.id, test_user.id, &[ids[0]]).await;
+        assert!(matches!(res.unwrap_err().downcast_ref::<RepositoryError>(), Some(RepositoryError::Duplicate(_))));
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use anyhow::Context;
+    use::std::{
+        cmp::Ordering,
+        collections::HashMap,
+        sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    };
+    use super::*;
+    use crate::models::status::{first_of_kind, DEFAULT_STATUSES};
+
+    type TodoData = HashMap<i32, TodoEntity>;
+    // recommendations by id - 1, with the time they were decided on
+    type RecommendationData = Vec<(Recommendation, Option<DateTime<Utc>>)>;
+
+    #[derive(Debug, Clone)]
+    pub struct TodoRepositoryForMemory {
+        store: Arc<RwLock<TodoData>>,
+        labels: Vec<Label>,
+        recommendations: Arc<RwLock<RecommendationData>>,
+        statuses: Arc<RwLock<Vec<Status>>>,
+        // (workspace_id, user_id) of the users todos can be assigned to
+        members: Vec<(i32, i32)>,
+    }
+
+    impl TodoRepositoryForMemory {
+        pub fn new(labels: Vec<Label>) -> Self {
+            TodoRepositoryForMemory {
+                store: Arc::default(),
+                labels,
+                recommendations: Arc::default(),
+                statuses: Arc::default(),
+                members: vec![],
+            }
+        }
+
+        // workspaces aren't known here either, only the members given this way are assignable
+        pub fn with_members(mut self, workspace_id: i32, user_ids: &[i32]) -> Self {
+            self.members.extend(user_ids.iter().map(|user_id| (workspace_id, *user_id)));
+            self
+        }
+
+        // the assignees the database ends up with, checked the same way
+        fn resolve_assignees(&self, workspace_id: i32, user_ids: &[i32]) -> anyhow::Result<Vec<i32>> {
+            if let Some(id) = user_ids.iter().find(|id| !self.members.contains(&(workspace_id, **id))) {
+                return Err(RepositoryError::UnknownAssignee(*id).into());
+            }
+            let mut user_ids = user_ids.to_vec();
+            user_ids.sort_unstable();
+            user_ids.dedup();
+            Ok(user_ids)
+        }
+
+        // workspaces aren't known here, so each one gets the default columns the first time it is used
+        fn statuses_of(&self, workspace_id: i32) -> Vec<Status> {
+            let mut statuses = self.statuses.write().unwrap();
+            if !statuses.iter().any(|status| status.workspace_id == workspace_id) {
+                for (position, (name, is_terminal)) in DEFAULT_STATUSES.into_iter().enumerate() {
+                    let id = (statuses.len() + 1) as i32;
+                    statuses.push(Status::new(id, workspace_id, name.to_string(), position as i32, is_terminal));
+                }
+            }
+            let mut statuses: Vec<Status> = statuses
+                .iter()
+                .filter(|status| status.workspace_id == workspace_id)
+                .cloned()
+                .collect();
+            statuses.sort_by_key(|status| (status.position, status.id));
+            statuses
+        }
+
+        // same as the database's status::resolve
+        fn resolve_status(&self, workspace_id: i32, id: Option<i32>, is_terminal: bool) -> anyhow::Result<Status> {
+            let statuses = self.statuses_of(workspace_id);
+            let status = match id {
+                Some(id) => statuses.iter().find(|status| status.id == id).ok_or(RepositoryError::UnknownStatus(id))?,
+                None => first_of_kind(&statuses, is_terminal).ok_or(RepositoryError::LastStatus(workspace_id))?,
+            };
+            Ok(status.clone())
+        }
+
+        // the todo's own status while it matches `completed`, the leftmost matching one otherwise
+        fn completion_status(&self, todo: &TodoEntity, completed: bool) -> i32 {
+            let statuses = self.statuses_of(todo.workspace_id);
+            statuses
+                .iter()
+                .find(|status| status.id == todo.status_id && status.is_terminal == completed)
+                .or(first_of_kind(&statuses, completed))
+                .map_or(todo.status_id, |status| status.id)
+        }
+
+        fn check_wip_limit(store: &TodoData, status: &Status, except: i32) -> anyhow::Result<()> {
+            let count = store
+                .values()
+                .filter(|todo| {
+                    todo.status_id == status.id && todo.parent_id.is_none() && todo.deleted_at.is_none() && todo.id != except
+                })
+                .count();
+            if status.is_over_limit(count + 1) {
+                return Err(RepositoryError::WipLimit(status.id).into());
+            }
+            Ok(())
+        }
+
+        fn write_store_ref(&self) -> RwLockWriteGuard<TodoData> {
+            self.store.write().unwrap()
+        }
+
+        fn read_store_ref(&self) -> RwLockReadGuard<TodoData> {
+            self.store.read().unwrap()
+        }
+
+        // workspaces aren't known here, so the user's own labels count as visible everywhere
+        fn check_labels(&self, workspace_id: i32, user_id: i32, label_ids: &[i32]) -> anyhow::Result<()> {
+            for id in label_ids {
+                let visible = self.labels.iter().any(|label| {
+                    label.id == *id
+                        && match label.workspace_id {
+                            Some(label_workspace_id) => label_workspace_id == workspace_id,
+                            None => label.user_id == user_id,
+                        }
+                });
+                if !visible {
+                    return Err(RepositoryError::UnknownLabel(*id).into());
+                }
+            }
+            Ok(())
+        }
+
+        fn resolve_labels(&self, labels: Vec<i32>) -> Vec<Label> {
+            labels
+                .iter()
+                .filter_map(|id| {
+                    self.labels.iter().find(|label| label.id == *id).cloned()
+                })
+                .collect()
+        }
+
+        // the store keeps todos flat, children are attached on the way out
+        fn with_children(store: &TodoData, todo: TodoEntity) -> TodoEntity {
+            let mut children: Vec<TodoEntity> = store
+                .values()
+                .filter(|child| child.parent_id == Some(todo.id) && child.deleted_at == todo.deleted_at)
+                .cloned()
+                .collect();
+            children.sort_by(|a, b| a.position.cmp(&b.position).then(a.id.cmp(&b.id)));
+            todo.with_children(children)
+        }
+
+        fn sync_parent_completion(&self, store: &mut TodoData, parent_id: i32) {
+            let children: Vec<bool> = store
+                .values()
+                .filter(|child| child.parent_id == Some(parent_id) && child.deleted_at.is_none())
+                .map(|child| child.completed)
+                .collect();
+            if let Some(parent) = store.get_mut(&parent_id)
+                && parent.auto_complete
+                && !children.is_empty()
+            {
+                parent.completed = children.iter().all(|completed| *completed);
+                parent.status_id = self.completion_status(parent, parent.completed);
+            }
+        }
+
+        // same as the database's sibling_position
+        fn sibling_position(
+            store: &TodoData,
+            workspace_id: i32,
+            parent_id: Option<i32>,
+            from: Option<&str>,
+            order: SortOrder,
+            except: Option<i32>,
+        ) -> Option<String> {
+            let siblings = store
+                .values()
+                .filter(|todo| {
+                    todo.workspace_id == workspace_id
+                        && todo.parent_id == parent_id
+                        && todo.deleted_at.is_none()
+                        && Some(todo.id) != except
+                })
+                .map(|todo| todo.position.as_str());
+            match order {
+                SortOrder::Asc => siblings.filter(|position| from.is_none_or(|from| *position > from)).min(),
+                SortOrder::Desc => siblings.filter(|position| from.is_none_or(|from| *position < from)).max(),
+            }
+            .map(str::to_string)
+        }
+
+        // new todos go on top of the list, new subtasks below the existing ones
+        fn new_position(store: &TodoData, workspace_id: i32, parent_id: Option<i32>) -> String {
+            match parent_id {
+                Some(_) => {
+                    let last = Self::sibling_position(store, workspace_id, parent_id, None, SortOrder::Desc, None);
+                    rank_between(last.as_deref(), None)
+                }
+                None => {
+                    let first = Self::sibling_position(store, workspace_id, parent_id, None, SortOrder::Asc, None);
+                    rank_between(None, first.as_deref())
+                }
+            }
+        }
+
+        fn insert_todo(
+            &self,
+            store: &mut TodoData,
+            user_id: i32,
+            workspace_id: i32,
+            payload: CreateTodo,
+            position: String,
+            series_id: Option<i32>,
+        ) -> TodoEntity {
+            let id = (store.len() + 1) as i32;
+            let labels = self.resolve_labels(payload.label_ids);
+            // create turned unknown statuses and assignees down already, next occurrences go to the leftmost open column
+            let status = self
+                .resolve_status(workspace_id, payload.status_id, false)
+                .expect("workspace without an open status");
+            let assignee_ids = self
+                .resolve_assignees(workspace_id, &payload.assignee_ids)
+                .unwrap_or_default();
+            let todo = TodoEntity {
+                completed: status.is_terminal,
+                status_id: status.id,
+                assignee_ids,
+                due_at: payload.due_at,
+                start_at: payload.start_at,
+                parent_id: payload.parent_id,
+                auto_complete: payload.auto_complete,
+                series_id: series_id.or(payload.recurrence.as_ref().map(|_| id)),
+                recurrence: payload.recurrence.map(|recurrence| recurrence.anchored(payload.due_at)),
+                priority: payload.priority,
+                position,
+                ..TodoEntity::new(id, payload.text.clone(), labels, user_id, workspace_id)
+            };
+            store.insert(id, todo.clone());
+            todo
+        }
+
+        fn later_occurrences(store: &TodoData, id: i32) -> Vec<i32> {
+            let Some(series_id) = store.get(&id).and_then(|todo| todo.series_id) else {
+                return vec![];
+            };
+            let mut ids: Vec<i32> = store
+                .values()
+                .filter(|todo| todo.series_id == Some(series_id) && todo.id > id && todo.deleted_at.is_none())
+                .map(|todo| todo.id)
+                .collect();
+            ids.sort_unstable();
+            ids
+        }
+
+        fn advance_series(&self, store: &mut TodoData, todo: &TodoEntity) {
+            if todo.series_id.is_none() || !Self::later_occurrences(store, todo.id).is_empty() {
+                return;
+            }
+            if let Some(payload) = todo.next_occurrence() {
+                let next_position =
+                    Self::sibling_position(store, todo.workspace_id, todo.parent_id, Some(&todo.position), SortOrder::Asc, None);
+                let position = rank_between(Some(&todo.position), next_position.as_deref());
+                self.insert_todo(store, todo.user_id, todo.workspace_id, payload, position, todo.series_id);
+            }
+        }
+
+        fn advance_completed_series(&self, store: &mut TodoData, todo: &TodoEntity) {
+            let root = todo.parent_id.and_then(|parent_id| store.get(&parent_id)).unwrap_or(todo).clone();
+            if root.completed {
+                self.advance_series(store, &root);
+            }
+        }
+
+        // same as the database's sync_status_completion
+        fn sync_status_completion(&self, store: &mut TodoData, status: &Status) {
+            let mut ids: Vec<i32> = store
+                .values()
+                .filter(|todo| todo.status_id == status.id && todo.completed != status.is_terminal)
+                .map(|todo| todo.id)
+                .collect();
+            ids.sort();
+            for id in ids {
+                let Some(todo) = store.get_mut(&id).filter(|todo| todo.status_id == status.id) else {
+                    continue;
+                };
+                todo.completed = status.is_terminal;
+                if todo.deleted_at.is_some() {
+                    continue;
+                }
+                let parent_id = todo.parent_id;
+                self.sync_parent_completion(store, parent_id.unwrap_or(id));
+                let todo = store[&id].clone();
+                self.advance_completed_series(store, &todo);
+            }
+        }
+
+        fn update_todo(&self, store: &mut TodoData, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+            let todo = store.get(&id).context(RepositoryError::NotFound(id))?;
+            if payload.validate_dates_of(todo).is_err() {
+                return Err(RepositoryError::StartAfterDue(id).into());
+            }
+            if let Some(label_ids) = &payload.label_ids {
+                self.check_labels(todo.workspace_id, actor_id, label_ids)?;
+            }
+            let assignee_ids = match &payload.assignee_ids {
+                Some(assignee_ids) => self.resolve_assignees(todo.workspace_id, assignee_ids)?,
+                None => todo.assignee_ids.clone(),
+            };
+            let text = payload.text.unwrap_or(todo.text.clone());
+            let (status_id, completed) = match (payload.status_id, payload.completed) {
+                (Some(status_id), _) => {
+                    let status = self.resolve_status(todo.workspace_id, Some(status_id), false)?;
+                    (status.id, status.is_terminal)
+                }
+                (None, Some(completed)) => (self.completion_status(todo, completed), completed),
+                (None, None) => (todo.status_id, todo.completed),
+            };
+            if status_id != todo.status_id && todo.parent_id.is_none() {
+                let status = self.resolve_status(todo.workspace_id, Some(status_id), false)?;
+                Self::check_wip_limit(store, &status, id)?;
+            }
+            let due_at = payload.due_at.unwrap_or(todo.due_at);
+            let start_at = payload.start_at.unwrap_or(todo.start_at);
+            let auto_complete = payload.auto_complete.unwrap_or(todo.auto_complete);
+            let recurrence = match payload.recurrence {
+                Some(recurrence) => recurrence.map(|recurrence| recurrence.anchored(due_at)),
+                None => todo.recurrence.clone(),
+            };
+            let priority = payload.priority.unwrap_or(todo.priority);
+            let series_id = match recurrence {
+                Some(_) => todo.series_id.or(Some(id)),
+                None => todo.series_id,
+            };
+            let labels = match payload.label_ids {
+                Some(label_ids) => self.resolve_labels(label_ids),
+                None => todo.labels.clone(),
+            };
+            let todo = TodoEntity {
+                text,
+                completed,
+                status_id,
+                labels,
+                assignee_ids,
+                due_at,
+                start_at,
+                auto_complete,
+                recurrence,
+                series_id,
+                priority,
+                ..todo.clone()
+            };
+            store.insert(id, todo.clone());
+            self.sync_parent_completion(store, todo.parent_id.unwrap_or(id));
+            let todo = store.get(&id).cloned().context(RepositoryError::NotFound(id))?;
+            Ok(Self::with_children(store, todo))
+        }
+
+        // positions of the pending recommendations among `ids`, checked like the database does
+        fn pending_recommendations(
+            recommendations: &RecommendationData,
+            workspace_id: i32,
+            user_id: i32,
+            ids: &[i32],
+        ) -> anyhow::Result<Vec<usize>> {
+            let mut pending = vec![];
+            for id in ids {
+                let index = recommendations
+                    .iter()
+                    .position(|(recommendation, _)| {
+                        recommendation.id == *id
+                            && recommendation.workspace_id == workspace_id
+                            && recommendation.user_id == user_id
+                            && !recommendation.is_expired()
+                    })
+                    .ok_or(RepositoryError::NotFound(*id))?;
+                if recommendations[index].0.status != RecommendationStatus::Pending {
+                    return Err(RepositoryError::Duplicate(*id).into());
+                }
+                if !pending.contains(&index) {
+                    pending.push(index);
+                }
+            }
+            Ok(pending)
+        }
+
+        fn page(&self, workspace_id: Option<i32>, query: TodoQuery) -> anyhow::Result<TodoPage> {
+            let store = self.read_store_ref();
+            let due = query.due.map(|due| (due.bounds(query.now()), due.excludes_completed()));
+            let search = query.search_text().map(str::to_lowercase);
+            let mut todos: Vec<TodoEntity> = store.values()
+                .filter(|todo| workspace_id.is_none_or(|id| todo.workspace_id == id))
+                .filter(|todo| todo.parent_id.is_none() && todo.deleted_at.is_none())
+                .filter(|todo| match due {
+                    Some(((from, to), open_only)) => {
+                        todo.due_at.is_some_and(|due_at| from.is_none_or(|from| due_at >= from) && due_at < to)
+                            && !(open_only && todo.completed)
+                    }
+                    None => true,
+                })
+                .filter(|todo| query.completed.is_none_or(|completed| todo.completed == completed))
+                .filter(|todo| query.status_id.is_none_or(|status_id| todo.status_id == status_id))
+                .filter(|todo| query.user_id.is_none_or(|user_id| todo.user_id == user_id))
+                .filter(|todo| query.assignee_id.is_none_or(|assignee_id| todo.assignee_ids.contains(&assignee_id)))
+                .filter(|todo| query.assigned.is_none_or(|assigned| todo.assignee_ids.is_empty() != assigned))
+                .filter(|todo| search.as_ref().is_none_or(|q| todo.text.to_lowercase().contains(q)))
+                .filter(|todo| {
+                    query.label_ids.iter().all(|id| todo.labels.iter().any(|label| label.id == *id))
+                })
+                .cloned()
+                .collect();
+            let total = todos.len() as i64;
+
+            let order = query.order();
+            let compare = |a: &TodoCursor, b: &TodoCursor| {
+                let ordering = match query.sort {
+                    TodoSort::Position => a.position.cmp(&b.position).then(a.id.cmp(&b.id)),
+                    TodoSort::Created => a.id.cmp(&b.id),
+                    // todos without a due date always come last
+                    TodoSort::DueAt => match (a.due_at, b.due_at) {
+                        (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    }
+                    .then(a.id.cmp(&b.id)),
+                    TodoSort::Text => a.text.cmp(&b.text).then(a.id.cmp(&b.id)),
+                };
+                match (order, query.sort) {
+                    (SortOrder::Asc, _) => ordering,
+                    (SortOrder::Desc, TodoSort::DueAt) => match (a.due_at, b.due_at) {
+                        (Some(_), None) | (None, Some(_)) => ordering,
+                        _ => ordering.reverse(),
+                    },
+                    (SortOrder::Desc, _) => ordering.reverse(),
+                }
+            };
+            todos.sort_by(|a, b| compare(&TodoCursor::after(a), &TodoCursor::after(b)));
+            if let Some(cursor) = &query.cursor {
+                todos.retain(|todo| compare(&TodoCursor::after(todo), cursor) == Ordering::Greater);
+            }
+
+            let limit = query.limit() as usize;
+            let has_next = todos.len() > limit;
+            todos.truncate(limit);
+            let next_cursor = if has_next {
+                todos.last().map(|todo| TodoCursor::after(todo).encode())
+            } else {
+                None
+            };
+
+            Ok(TodoPage {
+                items: todos.into_iter().map(|todo| Self::with_children(&store, todo)).collect(),
+                next_cursor,
+                total,
+            })
+        }
+
+        fn trash_todo(&self, store: &mut TodoData, id: i32, deleted_at: DateTime<Utc>) -> anyhow::Result<TodoEntity> {
+            let todo = store
+                .get(&id)
+                .filter(|todo| todo.deleted_at.is_none())
+                .cloned()
+                .ok_or(RepositoryError::NotFound(id))?;
+            for trashed in store.values_mut() {
+                if (trashed.id == id || trashed.parent_id == Some(id)) && trashed.deleted_at.is_none() {
+                    trashed.deleted_at = Some(deleted_at);
+                }
+            }
+            if let Some(parent_id) = todo.parent_id {
+                self.sync_parent_completion(store, parent_id);
+            }
+            Ok(todo)
+        }
+    }
+
+    #[async_trait]
+    impl TodoRepository for TodoRepositoryForMemory {
+        async fn create(&self, user_id: i32, workspace_id: i32, mut payload: CreateTodo) -> anyhow::Result<TodoEntity> {
+            self.check_labels(workspace_id, user_id, &payload.label_ids)?;
+            // labels to create have to be among the ones given up front, found by name
+            for new_label in std::mem::take(&mut payload.new_labels) {
+                let label = self
+                    .labels
+                    .iter()
+                    .find(|label| label.workspace_id == Some(workspace_id) && label.name == new_label.name)
+                    .ok_or(RepositoryError::UnknownLabel(0))?;
+                payload.label_ids.push(label.id);
+            }
+            payload.label_ids.sort_unstable();
+            payload.label_ids.dedup();
+            self.resolve_assignees(workspace_id, &payload.assignee_ids)?;
+            let mut store = self.write_store_ref();
+            let status = self.resolve_status(workspace_id, payload.status_id, payload.completed)?;
+            if payload.parent_id.is_none() {
+                Self::check_wip_limit(&store, &status, 0)?;
+            }
+            let position = Self::new_position(&store, workspace_id, payload.parent_id);
+            let payload = CreateTodo { status_id: Some(status.id), ..payload };
+            let todo = self.insert_todo(&mut store, user_id, workspace_id, payload, position, None);
+            if let Some(parent_id) = todo.parent_id {
+                self.sync_parent_completion(&mut store, parent_id);
+            }
+            Ok(todo)
+        }
+
+        async fn find(&self, id: i32) -> anyhow::Result<TodoEntity> {
+            let store = self.read_store_ref();
+            let todo = store
+                .get(&id)
+                .filter(|todo| todo.deleted_at.is_none())
+                .cloned()
+                .ok_or(RepositoryError::NotFound(id))?;
+            Ok(Self::with_children(&store, todo))
+        }
+
+        async fn all_by_workspace(&self, workspace_id: i32, query: TodoQuery) -> anyhow::Result<TodoPage> {
+            self.page(Some(workspace_id), query)
+        }
+
+        async fn all_assigned(&self, user_id: i32, query: TodoQuery) -> anyhow::Result<TodoPage> {
+            self.page(None, TodoQuery { assignee_id: Some(user_id), ..query })
+        }
+
+        async fn search(&self, workspace_ids: &[i32], query: SearchQuery) -> anyhow::Result<Vec<SearchHit>> {
+            let store = self.read_store_ref();
+            let terms = query.terms();
+            if terms.is_empty() {
+                return Ok(vec![]);
+            }
+
+            let matches = |word: &str| terms.iter().any(|term| word.to_lowercase().starts_with(term));
+            let escape = |word: &str| {
+                word.replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;")
+                    .replace('"', "&quot;")
+                    .replace('\'', "&#39;")
+            };
+            let mut hits: Vec<SearchHit> = store
+                .values()
+                .filter(|todo| workspace_ids.contains(&todo.workspace_id) && todo.deleted_at.is_none())
+                .filter_map(|todo| {
+                    let text_words: Vec<String> = todo.text.split_whitespace().map(str::to_lowercase).collect();
+                    let label_words: Vec<String> = todo.labels
+                        .iter()
+                        .flat_map(|label| label.name.split_whitespace().map(str::to_lowercase))
+                        .collect();
+                    let mut rank = 0.0;
+                    for term in &terms {
+                        if text_words.iter().any(|word| word.starts_with(term)) {
+                            rank += 1.0;
+                        } else if label_words.iter().any(|word| word.starts_with(term)) {
+                            rank += 0.4;
+                        } else {
+                            return None;
+                        }
+                    }
+                    let highlight = todo.text
+                        .split_whitespace()
+                        .map(|word| if matches(word) { format!("<mark>{}</mark>", escape(word)) } else { escape(word) })
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    Some(SearchHit {
+                        id: todo.id,
+                        text: todo.text.clone(),
+                        completed: todo.completed,
+                        labels: todo.labels.clone(),
+                        workspace_id: todo.workspace_id,
+                        highlight,
+                        rank,
+                    })
+                })
+                .collect();
+            hits.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(b.id.cmp(&a.id)));
+            hits.truncate(query.limit() as usize);
+            Ok(hits)
+        }
+
+        async fn update(&self, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+            let mut store = self.write_store_ref();
+            let todo = self.update_todo(&mut store, id, actor_id, payload)?;
+            self.advance_completed_series(&mut store, &todo);
+            Ok(todo)
+        }
+
+        async fn move_todo(&self, id: i32, _actor_id: i32, payload: MoveTodo) -> anyhow::Result<TodoEntity> {
+            let mut store = self.write_store_ref();
+            let todo = store
+                .get(&id)
+                .filter(|todo| todo.deleted_at.is_none())
+                .cloned()
+                .ok_or(RepositoryError::NotFound(id))?;
+            let anchor_id = payload.anchor_id();
+            let anchor = store
+                .get(&anchor_id)
+                .filter(|anchor| todo.is_sibling(anchor))
+                .cloned()
+                .ok_or(RepositoryError::NotFound(anchor_id))?;
+            let order = match payload.before {
+                Some(_) => SortOrder::Desc,
+                None => SortOrder::Asc,
+            };
+            let neighbor = Self::sibling_position(&store, todo.workspace_id, todo.parent_id, Some(&anchor.position), order, Some(id));
+            let position = match payload.before {
+                Some(_) => rank_between(neighbor.as_deref(), Some(&anchor.position)),
+                None => rank_between(Some(&anchor.position), neighbor.as_deref()),
+            };
+            let todo = TodoEntity { position, ..todo };
+            store.insert(id, todo.clone());
+            Ok(Self::with_children(&store, todo))
+        }
+
+        async fn delete(&self, id: i32, _actor_id: i32) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            let todo = self.trash_todo(&mut store, id, Utc::now())?;
+            if todo.parent_id.is_none() && !todo.completed {
+                self.advance_series(&mut store, &todo);
+            }
+            Ok(())
+        }
+
+        async fn update_series(&self, id: i32, actor_id: i32, payload: UpdateTodo) -> anyhow::Result<TodoEntity> {
+            let mut store = self.write_store_ref();
+            let later_ids = Self::later_occurrences(&store, id);
+            let carried = UpdateTodo {
+                completed: None,
+                status_id: None,
+                due_at: None,
+                start_at: None,
+                ..payload.clone()
+            };
+            let todo = self.update_todo(&mut store, id, actor_id, payload)?;
+            for later_id in later_ids {
+                self.update_todo(&mut store, later_id, actor_id, carried.clone())?;
+            }
+            self.advance_completed_series(&mut store, &todo);
+            Ok(todo)
+        }
+
+        async fn delete_series(&self, id: i32, _actor_id: i32) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            let later_ids = Self::later_occurrences(&store, id);
+            let deleted_at = Utc::now();
+            self.trash_todo(&mut store, id, deleted_at)?;
+            for later_id in later_ids {
+                self.trash_todo(&mut store, later_id, deleted_at)?;
+            }
+            Ok(())
+        }
+
+        async fn find_trashed(&self, id: i32) -> anyhow::Result<TodoEntity> {
+            let store = self.read_store_ref();
+            let todo = store
+                .get(&id)
+                .filter(|todo| todo.deleted_at.is_some())
+                .cloned()
+                .ok_or(RepositoryError::NotFound(id))?;
+            Ok(Self::with_children(&store, todo))
+        }
+
+        async fn all_trashed(&self, workspace_id: i32) -> anyhow::Result<Vec<TodoEntity>> {
+            let store = self.read_store_ref();
+            let mut todos: Vec<TodoEntity> = store
+                .values()
+                .filter(|todo| todo.workspace_id == workspace_id && todo.deleted_at.is_some())
+                .filter(|todo| {
+                    todo.parent_id
+                        .and_then(|parent_id| store.get(&parent_id))
+                        .is_none_or(|parent| parent.deleted_at != todo.deleted_at)
+                })
+                .cloned()
+                .collect();
+            todos.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(b.id.cmp(&a.id)));
+            Ok(todos.into_iter().map(|todo| Self::with_children(&store, todo)).collect())
+        }
+
+        async fn restore(&self, id: i32, _actor_id: i32) -> anyhow::Result<TodoEntity> {
+            let mut store = self.write_store_ref();
+            let todo = store
+                .get(&id)
+                .filter(|todo| todo.deleted_at.is_some())
+                .cloned()
+                .ok_or(RepositoryError::NotFound(id))?;
+            for trashed in store.values_mut() {
+                if (trashed.id == id || trashed.parent_id == Some(id)) && trashed.deleted_at == todo.deleted_at {
+                    trashed.deleted_at = None;
+                }
+            }
+            if let Some(parent_id) = todo.parent_id {
+                self.sync_parent_completion(&mut store, parent_id);
+            }
+            let todo = store.get(&id).cloned().context(RepositoryError::NotFound(id))?;
+            Ok(Self::with_children(&store, todo))
+        }
+
+        async fn purge_trashed(&self, deleted_before: DateTime<Utc>) -> anyhow::Result<u64> {
+            let mut store = self.write_store_ref();
+            let before = store.len();
+            let purged: Vec<i32> = store
+                .values()
+                .filter(|todo| todo.deleted_at.is_some_and(|deleted_at| deleted_at < deleted_before))
+                .map(|todo| todo.id)
+                .collect();
+            store.retain(|_, todo| !purged.contains(&todo.id) && !todo.parent_id.is_some_and(|parent_id| purged.contains(&parent_id)));
+            Ok((before - store.len()) as u64)
+        }
+
+        async fn save_recommendations(&self, workspace_id: i32, user_id: i32, texts: Vec<String>) -> anyhow::Result<Vec<Recommendation>> {
+            let mut recommendations = self.recommendations.write().unwrap();
+            let expires_at = Utc::now() + chrono::Duration::minutes(RECOMMENDATION_TTL_MINUTES);
+            let mut saved = vec![];
+            for text in texts {
+                let recommendation = Recommendation {
+                    id: (recommendations.len() + 1) as i32,
+                    workspace_id,
+                    user_id,
+                    text,
+                    status: RecommendationStatus::Pending,
+                    todo_id: None,
+                    expires_at,
+                };
+                recommendations.push((recommendation.clone(), None));
+                saved.push(recommendation);
+            }
+            Ok(saved)
+        }
+
+        async fn accept_recommendations(&self, workspace_id: i32, actor_id: i32, ids: &[i32]) -> anyhow::Result<Vec<TodoEntity>> {
+            let mut recommendations = self.recommendations.write().unwrap();
+            let mut store = self.write_store_ref();
+            let mut todos = vec![];
+            for index in Self::pending_recommendations(&recommendations, workspace_id, actor_id, ids)? {
+                let (recommendation, decided_at) = &mut recommendations[index];
+                let position = Self::new_position(&store, workspace_id, None);
+                let payload = CreateTodo::new(recommendation.text.clone(), vec![]);
+                let todo = self.insert_todo(&mut store, actor_id, workspace_id, payload, position, None);
+                recommendation.status = RecommendationStatus::Accepted;
+                recommendation.todo_id = Some(todo.id);
+                *decided_at = Some(Utc::now());
+                todos.push(todo);
+            }
+            Ok(todos)
+        }
+
+        async fn reject_recommendations(&self, workspace_id: i32, user_id: i32, ids: &[i32]) -> anyhow::Result<()> {
+            let mut recommendations = self.recommendations.write().unwrap();
+            for index in Self::pending_recommendations(&recommendations, workspace_id, user_id, ids)? {
+                let (recommendation, decided_at) = &mut recommendations[index];
+                recommendation.status = RecommendationStatus::Rejected;
+                *decided_at = Some(Utc::now());
+            }
+            Ok(())
+        }
+
+        async fn rejected_recommendations(&self, workspace_id: i32, user_id: i32, limit: i64) -> anyhow::Result<Vec<String>> {
+            let recommendations = self.recommendations.read().unwrap();
+            let mut rejected: Vec<&(Recommendation, Option<DateTime<Utc>>)> = recommendations
+                .iter()
+                .filter(|(recommendation, _)| {
+                    recommendation.workspace_id == workspace_id
+                        && recommendation.user_id == user_id
+                        && recommendation.status == RecommendationStatus::Rejected
+                })
+                .collect();
+            rejected.sort_by(|(a, a_decided_at), (b, b_decided_at)| b_decided_at.cmp(a_decided_at).then(b.id.cmp(&a.id)));
+            Ok(rejected
+                .into_iter()
+                .take(limit as usize)
+                .map(|(recommendation, _)| recommendation.text.clone())
+                .collect())
+        }
+
+        async fn all_statuses(&self, workspace_id: i32) -> anyhow::Result<Vec<Status>> {
+            Ok(self.statuses_of(workspace_id))
+        }
+
+        async fn create_status(&self, workspace_id: i32, _actor_id: i32, payload: CreateStatus) -> anyhow::Result<Status> {
+            let existing = self.statuses_of(workspace_id);
+            if existing.iter().any(|status| status.name == payload.name) {
+                return Err(RepositoryError::Duplicate(workspace_id).into());
+            }
+            let mut statuses = self.statuses.write().unwrap();
+            let position = existing.iter().map(|status| status.position + 1).max().unwrap_or_default();
+            let status = Status {
+                wip_limit: payload.wip_limit,
+                ..Status::new((statuses.len() + 1) as i32, workspace_id, payload.name, position, payload.is_terminal)
+            };
+            statuses.push(status.clone());
+            Ok(status)
+        }
+
+        async fn update_status(&self, workspace_id: i32, id: i32, _actor_id: i32, payload: UpdateStatus) -> anyhow::Result<Status> {
+            let mut store = self.write_store_ref();
+            let mut ordered = self.statuses_of(workspace_id);
+            let old_status = ordered.iter().find(|status| status.id == id).cloned().ok_or(RepositoryError::NotFound(id))?;
+            let status = Status {
+                name: payload.name.unwrap_or(old_status.name.clone()),
+                wip_limit: payload.wip_limit.unwrap_or(old_status.wip_limit),
+                is_terminal: payload.is_terminal.unwrap_or(old_status.is_terminal),
+                ..old_status.clone()
+            };
+            if ordered.iter().any(|other| other.id != id && other.name == status.name) {
+                return Err(RepositoryError::Duplicate(id).into());
+            }
+            ordered.retain(|other| other.id != id);
+            let position = payload.position.unwrap_or(old_status.position) as usize;
+            ordered.insert(position.min(ordered.len()), status.clone());
+            if !ordered.iter().any(|other| other.is_terminal) || ordered.iter().all(|other| other.is_terminal) {
+                return Err(RepositoryError::LastStatus(id).into());
+            }
+
+            let mut statuses = self.statuses.write().unwrap();
+            statuses.retain(|other| other.workspace_id != workspace_id);
+            for (position, other) in ordered.into_iter().enumerate() {
+                statuses.push(Status { position: position as i32, ..other });
+            }
+            let status = statuses.iter().find(|other| other.id == id).cloned().context(RepositoryError::NotFound(id))?;
+            drop(statuses);
+            self.sync_status_completion(&mut store, &status);
+            Ok(status)
+        }
+
+        async fn delete_status(&self, workspace_id: i32, id: i32, _actor_id: i32, move_to: Option<i32>) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            let statuses = self.statuses_of(workspace_id);
+            let status = statuses.iter().find(|status| status.id == id).ok_or(RepositoryError::NotFound(id))?;
+            let others: Vec<Status> = statuses.iter().filter(|other| other.id != id).cloned().collect();
+            let target = match move_to {
+                Some(move_to) => others.iter().find(|other| other.id == move_to).ok_or(RepositoryError::NotFound(move_to))?,
+                None => {
+                    if store.values().any(|todo| todo.status_id == id && todo.deleted_at.is_none()) {
+                        return Err(RepositoryError::InUse(id).into());
+                    }
+                    first_of_kind(&others, status.is_terminal)
+                        .or(others.first())
+                        .ok_or(RepositoryError::LastStatus(id))?
+                }
+            }
+            .clone();
+            if !others.iter().any(|other| other.is_terminal) || others.iter().all(|other| other.is_terminal) {
+                return Err(RepositoryError::LastStatus(id).into());
+            }
+            let count = store
+                .values()
+                .filter(|todo| [id, target.id].contains(&todo.status_id) && todo.parent_id.is_none() && todo.deleted_at.is_none())
+                .count();
+            if move_to.is_some() && target.is_over_limit(count) {
+                return Err(RepositoryError::WipLimit(target.id).into());
+            }
+
+            for todo in store.values_mut().filter(|todo| todo.status_id == id) {
+                todo.status_id = target.id;
+            }
+            self.statuses.write().unwrap().retain(|other| other.id != id);
+            self.sync_status_completion(&mut store, &target);
+            Ok(())
+        }
+    }
+
+    mod test {
         use super::*;
+        use chrono::Weekday;
+        use crate::models::todo::{DueFilter, Frequency, MoveTodo, Recurrence, TodoSort};
 
         #[tokio::test]
         async fn todo_crud_scenario() {
@@ -489,7 +3038,12 @@ pub mod test_utils {
             let workspace_id = 1;
             let label = Label::new(id, "todo_label".to_string(), user_id);
             let labels = vec![label.clone()];
-            let expected = TodoEntity::new(id, text.clone(), labels.clone(), user_id, workspace_id);
+            // the workspace's first column is backlog
+            let expected = TodoEntity {
+                position: rank_between(None, None),
+                status_id: 1,
+                ..TodoEntity::new(id, text.clone(), labels.clone(), user_id, workspace_id)
+            };
 
             let repository = TodoRepositoryForMemory::new(labels.clone());
             let todo = repository
@@ -501,21 +3055,225 @@ pub mod test_utils {
             let todo = repository.find(todo.id).await.unwrap();
             assert_eq!(expected, todo);
 
-            let todo = repository.all_by_workspace(workspace_id).await.expect("failed get all todo");
+            let todo = repository.all_by_workspace(workspace_id, TodoQuery::default()).await.expect("failed get all todo").items;
             assert_eq!(vec![expected], todo);
 
             let text = "updated_todo_text".to_string();
             let todo = repository
-                .update(1, UpdateTodo { text: Some(text.clone()), completed: Some(true), label_ids: Some(vec![]) })
+                .update(1, user_id, UpdateTodo { text: Some(text.clone()), completed: Some(true), label_ids: Some(vec![]), ..Default::default() })
                 .await
                 .expect("failed update todo.");
             assert_eq!(
-                TodoEntity { id, text, completed: true, labels: vec![], user_id, workspace_id },
+                TodoEntity {
+                    completed: true,
+                    status_id: 4,
+                    position: rank_between(None, None),
+                    ..TodoEntity::new(id, text, vec![], user_id, workspace_id)
+                },
                 todo
             );
 
-            let res = repository.delete(id).await;
-            assert!(res.is_ok())
+            let res = repository.delete(id, user_id).await;
+            assert!(res.is_ok());
+            assert!(repository.find(id).await.is_err());
+            assert!(repository.delete(id, user_id).await.is_err());
+
+            let trashed = repository.all_trashed(workspace_id).await.expect("failed get trashed todo");
+            assert_eq!(vec![id], trashed.iter().map(|todo| todo.id).collect::<Vec<_>>());
+            let todo = repository.restore(id, user_id).await.expect("failed restore todo");
+            assert_eq!(None, todo.deleted_at);
+            assert!(repository.find(id).await.is_ok());
+
+            repository.delete(id, user_id).await.unwrap();
+            assert_eq!(0, repository.purge_trashed(Utc::now() - chrono::Duration::days(1)).await.unwrap());
+            assert_eq!(1, repository.purge_trashed(Utc::now() + chrono::Duration::seconds(1)).await.unwrap());
+            assert!(repository.all_trashed(workspace_id).await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn todo_recurrence_scenario() {
+            let repository = TodoRepositoryForMemory::new(vec![]);
+            let daily = Recurrence {
+                frequency: Frequency::Daily,
+                interval: 2,
+                weekdays: vec![],
+                month_day: None,
+                until: Some("2026-06-04T00:00:00Z".parse().unwrap()),
+                tz_offset: 0,
+            };
+            let mut payload = CreateTodo::new("water the plants".to_string(), vec![]);
+            payload.due_at = Some("2026-06-01T18:00:00Z".parse().unwrap());
+            payload.start_at = Some("2026-06-01T17:00:00Z".parse().unwrap());
+            payload.recurrence = Some(daily);
+            let first = repository.create(1, 1, payload).await.unwrap();
+
+            let completed = UpdateTodo { completed: Some(true), ..Default::default() };
+            repository.update(first.id, 1, completed.clone()).await.unwrap();
+            let second = repository.find(first.id + 1).await.expect("next occurrence not generated");
+            assert_eq!(Some(first.id), second.series_id);
+            assert_eq!(Some("2026-06-03T18:00:00Z".parse().unwrap()), second.due_at);
+            assert_eq!(Some("2026-06-03T17:00:00Z".parse().unwrap()), second.start_at);
+
+            // the series ends at `until`
+            repository.update(second.id, 1, completed).await.unwrap();
+            assert!(repository.find(second.id + 1).await.is_err());
+
+            // weekdays are counted in the rule's offset, every other week
+            let weekly = Recurrence {
+                frequency: Frequency::Weekly,
+                interval: 2,
+                weekdays: vec![Weekday::Mon],
+                month_day: None,
+                until: None,
+                tz_offset: 9 * 60,
+            };
+            assert_eq!(
+                Some("2026-06-14T23:00:00Z".parse().unwrap()),
+                weekly.next_after("2026-05-31T23:00:00Z".parse().unwrap())
+            );
+
+            // shorter months fall back to their last day
+            let monthly = Recurrence {
+                frequency: Frequency::Monthly,
+                interval: 1,
+                weekdays: vec![],
+                month_day: Some(31),
+                until: None,
+                tz_offset: 0,
+            };
+            let february = monthly.next_after("2026-01-31T09:00:00Z".parse().unwrap());
+            assert_eq!(Some("2026-02-28T09:00:00Z".parse().unwrap()), february);
+            assert_eq!(Some("2026-03-31T09:00:00Z".parse().unwrap()), monthly.next_after(february.unwrap()));
+        }
+
+        #[tokio::test]
+        async fn todo_due_filter_scenario() {
+            let user_id = 1;
+            let workspace_id = 1;
+            let repository = TodoRepositoryForMemory::new(vec![]);
+            let now = Utc::now();
+
+            let mut payload = CreateTodo::new("overdue".to_string(), vec![]);
+            payload.due_at = Some(now - chrono::Duration::days(3));
+            let overdue = repository.create(user_id, workspace_id, payload).await.unwrap();
+
+            let mut payload = CreateTodo::new("later".to_string(), vec![]);
+            payload.due_at = Some(now + chrono::Duration::days(30));
+            let later = repository.create(user_id, workspace_id, payload).await.unwrap();
+
+            let no_due = repository
+                .create(user_id, workspace_id, CreateTodo::new("no due".to_string(), vec![]))
+                .await
+                .unwrap();
+
+            let todos = repository.all_by_workspace(workspace_id, TodoQuery::default()).await.unwrap().items;
+            assert_eq!(
+                vec![no_due.id, later.id, overdue.id],
+                todos.iter().map(|t| t.id).collect::<Vec<_>>()
+            );
+
+            let query = TodoQuery { due: Some(DueFilter::Overdue), ..Default::default() };
+            let todos = repository.all_by_workspace(workspace_id, query.clone()).await.unwrap().items;
+            assert_eq!(vec![overdue.clone()], todos);
+
+            repository
+                .update(overdue.id, user_id, UpdateTodo { completed: Some(true), ..Default::default() })
+                .await
+                .unwrap();
+            let todos = repository.all_by_workspace(workspace_id, query).await.unwrap().items;
+            assert!(todos.is_empty());
+
+            let query = TodoQuery { due: Some(DueFilter::ThisWeek), ..Default::default() };
+            let todos = repository.all_by_workspace(workspace_id, query).await.unwrap().items;
+            assert!(todos.iter().all(|t| t.id != later.id && t.id != no_due.id));
+        }
+
+        #[tokio::test]
+        async fn todo_paging_scenario() {
+            let user_id = 1;
+            let workspace_id = 1;
+            let repository = TodoRepositoryForMemory::new(vec![]);
+            for text in ["c", "a", "b"] {
+                repository.create(user_id, workspace_id, CreateTodo::new(text.to_string(), vec![])).await.unwrap();
+            }
+            repository.create(user_id, 2, CreateTodo::new("other".to_string(), vec![])).await.unwrap();
+
+            let query = TodoQuery { sort: TodoSort::Text, limit: Some(2), ..Default::default() };
+            let page = repository.all_by_workspace(workspace_id, query.clone()).await.unwrap();
+            assert_eq!(3, page.total);
+            assert_eq!(vec!["a", "b"], page.items.iter().map(|t| t.text.as_str()).collect::<Vec<_>>());
+
+            let cursor = TodoCursor::decode(&page.next_cursor.expect("no next cursor")).unwrap();
+            let page = repository
+                .all_by_workspace(workspace_id, TodoQuery { cursor: Some(cursor), ..query })
+                .await
+                .unwrap();
+            assert_eq!(vec!["c"], page.items.iter().map(|t| t.text.as_str()).collect::<Vec<_>>());
+            assert_eq!(None, page.next_cursor);
+        }
+
+        #[tokio::test]
+        async fn todo_move_scenario() {
+            let user_id = 1;
+            let workspace_id = 1;
+            let repository = TodoRepositoryForMemory::new(vec![]);
+            let mut todos = vec![];
+            for text in ["x", "y", "z"] {
+                todos.push(repository.create(user_id, workspace_id, CreateTodo::new(text.to_string(), vec![])).await.unwrap());
+            }
+            let (x, y, z) = (todos[0].id, todos[1].id, todos[2].id);
+
+            // the gap between z and the other two keeps getting split
+            for _ in 0..40 {
+                repository.move_todo(x, user_id, MoveTodo { before: Some(y), after: None }).await.unwrap();
+                repository.move_todo(y, user_id, MoveTodo { before: Some(x), after: None }).await.unwrap();
+            }
+            let query = TodoQuery { limit: Some(2), ..Default::default() };
+            let page = repository.all_by_workspace(workspace_id, query.clone()).await.unwrap();
+            assert_eq!(vec!["z", "y"], page.items.iter().map(|t| t.text.as_str()).collect::<Vec<_>>());
+            let cursor = TodoCursor::decode(&page.next_cursor.expect("no next cursor")).unwrap();
+            let page = repository
+                .all_by_workspace(workspace_id, TodoQuery { cursor: Some(cursor), ..query })
+                .await
+                .unwrap();
+            assert_eq!(vec!["x"], page.items.iter().map(|t| t.text.as_str()).collect::<Vec<_>>());
+
+            let moved = repository.move_todo(z, user_id, MoveTodo { before: None, after: Some(x) }).await.unwrap();
+            assert!(moved.position > repository.find(x).await.unwrap().position);
+
+            // only siblings are anchors
+            let mut payload = CreateTodo::new("step".to_string(), vec![]);
+            payload.parent_id = Some(x);
+            let child = repository.create(user_id, workspace_id, payload).await.unwrap();
+            assert!(repository.move_todo(child.id, user_id, MoveTodo { before: Some(y), after: None }).await.is_err());
+            assert!(repository.move_todo(y, user_id, MoveTodo { before: Some(y), after: None }).await.is_err());
+        }
+
+        #[tokio::test]
+        async fn todo_recommendation_scenario() {
+            let user_id = 1;
+            let workspace_id = 1;
+            let repository = TodoRepositoryForMemory::new(vec![]);
+            let recommendations = repository
+                .save_recommendations(workspace_id, user_id, vec!["a".to_string(), "b".to_string(), "c".to_string()])
+                .await
+                .unwrap();
+            let ids: Vec<i32> = recommendations.iter().map(|r| r.id).collect();
+            assert_eq!(vec![1, 2, 3], ids);
+
+            assert!(repository.accept_recommendations(workspace_id, user_id, &[ids[0], 99]).await.is_err());
+            assert!(repository.accept_recommendations(2, user_id, &[ids[0]]).await.is_err());
+            assert_eq!(0, repository.all_by_workspace(workspace_id, TodoQuery::default()).await.unwrap().total);
+
+            let todos = repository.accept_recommendations(workspace_id, user_id, &[ids[2], ids[0]]).await.unwrap();
+            assert_eq!(vec!["c", "a"], todos.iter().map(|t| t.text.as_str()).collect::<Vec<_>>());
+            assert!(repository.accept_recommendations(workspace_id, user_id, &[ids[0]]).await.is_err());
+
+            assert!(repository.reject_recommendations(workspace_id, 2, &[ids[1]]).await.is_err());
+            repository.reject_recommendations(workspace_id, user_id, &[ids[1]]).await.unwrap();
+            assert!(repository.reject_recommendations(workspace_id, user_id, &[ids[1]]).await.is_err());
+            assert_eq!(vec!["b"], repository.rejected_recommendations(workspace_id, user_id, 20).await.unwrap());
+            assert!(repository.rejected_recommendations(workspace_id, 2, 20).await.unwrap().is_empty());
         }
     }
 }
@@ -1,14 +1,66 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
-use crate::models::user::{CreateUser, UpdateUser, User};
-use super::RepositoryError;
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, FromRow, PgPool};
+use crate::models::{
+    access_token::{AccessToken, CreateAccessToken, TokenScope, ACCESS_TOKEN_PREFIX, TOKEN_HINT_LENGTH},
+    calendar::CalendarFeed,
+    user::{CreateUser, UpdateUser, User},
+};
+use super::{generate_secret_token, hash_token, RepositoryError};
 
 #[async_trait]
 pub trait UserRepository: Send + Sync + 'static {
     async fn create(&self, payload: CreateUser) -> anyhow::Result<User>;
     async fn find(&self, id: i32) -> anyhow::Result<User>;
     async fn find_by_sub(&self, sub: String) -> anyhow::Result<User>;
+    async fn find_by_email(&self, email: String) -> anyhow::Result<User>;
     async fn update_name(&self, sub: String, payload: UpdateUser) -> anyhow::Result<User>;
+    async fn create_calendar_feed(&self, user_id: i32) -> anyhow::Result<CalendarFeed>;
+    async fn find_calendar_feed(&self, user_id: i32) -> anyhow::Result<CalendarFeed>;
+    async fn revoke_calendar_feed(&self, user_id: i32) -> anyhow::Result<()>;
+    async fn find_by_calendar_token(&self, token: String) -> anyhow::Result<User>;
+    async fn create_access_token(&self, user_id: i32, payload: CreateAccessToken) -> anyhow::Result<AccessToken>;
+    async fn all_access_tokens(&self, user_id: i32) -> anyhow::Result<Vec<AccessToken>>;
+    async fn revoke_access_token(&self, user_id: i32, id: i32) -> anyhow::Result<()>;
+    /// Records the use as well, expired tokens are not found.
+    async fn find_by_access_token(&self, token: String) -> anyhow::Result<(User, AccessToken)>;
+}
+
+fn generate_access_token() -> String {
+    format!("{}{}", ACCESS_TOKEN_PREFIX, generate_secret_token())
+}
+
+fn normalize_scopes(mut scopes: Vec<TokenScope>) -> Vec<TokenScope> {
+    scopes.sort();
+    scopes.dedup();
+    scopes
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct AccessTokenFromRow {
+    id: i32,
+    user_id: i32,
+    name: String,
+    scopes: Json<Vec<TokenScope>>,
+    token_hint: String,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<AccessTokenFromRow> for AccessToken {
+    fn from(row: AccessTokenFromRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            scopes: row.scopes.0,
+            token_hint: row.token_hint,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            created_at: row.created_at,
+            token: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -27,15 +79,16 @@ impl UserRepository for UserRepositoryForDb {
     async fn create(&self, payload: CreateUser) -> anyhow::Result<User> {
         let user = sqlx::query_as::<_, User>(
             r#"
-insert into users (sub, name, email)
-values ($1, $2, $3)
-on conflict (sub) do update set email = $3
+insert into users (sub, name, email, email_verified)
+values ($1, $2, $3, $4)
+on conflict (sub) do update set email = $3, email_verified = $4
 returning *
             "#,
         )
         .bind(payload.sub.clone())
         .bind(payload.name.clone())
         .bind(payload.email.clone())
+        .bind(payload.email_verified)
         .fetch_one(&self.pool)
         .await?;
 
@@ -76,6 +129,25 @@ select * from users where sub = $1
         Ok(user)
     }
 
+    async fn find_by_email(&self, email: String) -> anyhow::Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+select * from users where email = $1 and email_verified
+order by id asc
+limit 1
+            "#,
+        )
+        .bind(email)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => RepositoryError::NotFound(0),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
+
+        Ok(user)
+    }
+
     async fn update_name(&self, sub: String, payload: UpdateUser) -> anyhow::Result<User> {
         let user = sqlx::query_as::<_, User>(
             r#"
@@ -94,6 +166,140 @@ returning *
 
         Ok(user)
     }
+
+    // 作り直すと前のトークンは使えなくなる
+    async fn create_calendar_feed(&self, user_id: i32) -> anyhow::Result<CalendarFeed> {
+        let token = generate_secret_token();
+        let created_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+insert into calendar_feeds (user_id, token_hash)
+values ($1, $2)
+on conflict (user_id) do update set token_hash = $2, created_at = now()
+returning created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CalendarFeed { created_at, token: Some(token) })
+    }
+
+    async fn find_calendar_feed(&self, user_id: i32) -> anyhow::Result<CalendarFeed> {
+        let created_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+select created_at from calendar_feeds where user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(RepositoryError::NotFound(user_id))?;
+
+        Ok(CalendarFeed { created_at, token: None })
+    }
+
+    async fn revoke_calendar_feed(&self, user_id: i32) -> anyhow::Result<()> {
+        let result = sqlx::query("delete from calendar_feeds where user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(user_id).into());
+        }
+
+        Ok(())
+    }
+
+    async fn find_by_calendar_token(&self, token: String) -> anyhow::Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+select users.* from users
+            join calendar_feeds on calendar_feeds.user_id = users.id
+where calendar_feeds.token_hash = $1
+            "#,
+        )
+        .bind(hash_token(&token))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => RepositoryError::NotFound(0),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
+
+        Ok(user)
+    }
+
+    async fn create_access_token(&self, user_id: i32, payload: CreateAccessToken) -> anyhow::Result<AccessToken> {
+        let token = generate_access_token();
+        let row = sqlx::query_as::<_, AccessTokenFromRow>(
+            r#"
+insert into access_tokens (user_id, name, token_hash, token_hint, scopes, expires_at)
+values ($1, $2, $3, $4, $5, $6)
+returning id, user_id, name, scopes, token_hint, expires_at, last_used_at, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(payload.name)
+        .bind(hash_token(&token))
+        .bind(&token[..TOKEN_HINT_LENGTH])
+        .bind(Json(normalize_scopes(payload.scopes)))
+        .bind(payload.expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(AccessToken { token: Some(token), ..row.into() })
+    }
+
+    async fn all_access_tokens(&self, user_id: i32) -> anyhow::Result<Vec<AccessToken>> {
+        let rows = sqlx::query_as::<_, AccessTokenFromRow>(
+            r#"
+select id, user_id, name, scopes, token_hint, expires_at, last_used_at, created_at
+from access_tokens
+where user_id = $1
+order by id asc
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(AccessToken::from).collect())
+    }
+
+    async fn revoke_access_token(&self, user_id: i32, id: i32) -> anyhow::Result<()> {
+        let result = sqlx::query("delete from access_tokens where id = $1 and user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(id).into());
+        }
+
+        Ok(())
+    }
+
+    async fn find_by_access_token(&self, token: String) -> anyhow::Result<(User, AccessToken)> {
+        let row = sqlx::query_as::<_, AccessTokenFromRow>(
+            r#"
+update access_tokens set last_used_at = now()
+where token_hash = $1 and (expires_at is null or expires_at > now())
+returning id, user_id, name, scopes, token_hint, expires_at, last_used_at, created_at
+            "#,
+        )
+        .bind(hash_token(&token))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => RepositoryError::NotFound(0),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
+        let user = self.find(row.user_id).await?;
+
+        Ok((user, row.into()))
+    }
 }
 
 #[cfg(test)]
@@ -119,7 +325,7 @@ mod test {
 
         // create
         let created = repository
-            .create(CreateUser::new(user_sub.to_string(), user_name.to_string(), user_email.to_string()))
+            .create(CreateUser::new(user_sub.to_string(), user_name.to_string(), user_email.to_string()).verified())
             .await
             .expect("[create] returned Err");
         assert_eq!(created.name, Some(user_name.to_string()));
@@ -130,6 +336,90 @@ mod test {
             .await
             .expect("[find] returned Err");
         assert_eq!(created, user);
+
+        // find_by_email
+        let user = repository
+            .find_by_email(user_email.to_string())
+            .await
+            .expect("[find_by_email] returned Err");
+        assert_eq!(created, user);
+    }
+
+    #[tokio::test]
+    async fn calendar_feed_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let repository = UserRepositoryForDb::new(pool.clone());
+        let user = repository
+            .create(CreateUser::new("auth0|test_calendar_user".to_string(), "test_calendar_user".to_string(), "test_calendar_user@example.com".to_string()))
+            .await
+            .expect("[create] returned Err");
+        assert!(repository.find_calendar_feed(user.id).await.is_err());
+
+        let feed = repository.create_calendar_feed(user.id).await.expect("[create_calendar_feed] returned Err");
+        let token = feed.token.clone().expect("token not handed out");
+        assert_eq!(user, repository.find_by_calendar_token(token.clone()).await.expect("[find_by_calendar_token] returned Err"));
+        let found = repository.find_calendar_feed(user.id).await.expect("[find_calendar_feed] returned Err");
+        assert_eq!(CalendarFeed { token: None, ..feed }, found);
+
+        // a new feed replaces the old token
+        let renewed = repository.create_calendar_feed(user.id).await.expect("[create_calendar_feed] returned Err");
+        assert!(repository.find_by_calendar_token(token).await.is_err());
+        let token = renewed.token.expect("token not handed out");
+        assert!(repository.find_by_calendar_token(token.clone()).await.is_ok());
+
+        repository.revoke_calendar_feed(user.id).await.expect("[revoke_calendar_feed] returned Err");
+        assert!(repository.find_by_calendar_token(token).await.is_err());
+        assert!(repository.revoke_calendar_feed(user.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn access_token_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let repository = UserRepositoryForDb::new(pool.clone());
+        let user = repository
+            .create(CreateUser::new("auth0|test_token_user".to_string(), "test_token_user".to_string(), "test_token_user@example.com".to_string()))
+            .await
+            .expect("[create] returned Err");
+
+        let created = repository
+            .create_access_token(user.id, CreateAccessToken::new("ci".to_string(), vec![TokenScope::Write, TokenScope::Read, TokenScope::Write], None))
+            .await
+            .expect("[create_access_token] returned Err");
+        assert_eq!(vec![TokenScope::Read, TokenScope::Write], created.scopes);
+        let token = created.token.clone().expect("token not handed out");
+        assert!(token.starts_with(&created.token_hint));
+
+        let (found_user, used) = repository
+            .find_by_access_token(token.clone())
+            .await
+            .expect("[find_by_access_token] returned Err");
+        assert_eq!(user, found_user);
+        assert!(used.last_used_at.is_some());
+        let listed = repository.all_access_tokens(user.id).await.expect("[all_access_tokens] returned Err");
+        assert_eq!(vec![used], listed);
+
+        // expired tokens are no longer accepted
+        let expired = repository
+            .create_access_token(user.id, CreateAccessToken::new("old".to_string(), vec![TokenScope::Read], Some(Utc::now() - chrono::Duration::days(1))))
+            .await
+            .expect("[create_access_token] returned Err");
+        assert!(repository.find_by_access_token(expired.token.unwrap()).await.is_err());
+
+        // only the owner can revoke
+        assert!(repository.revoke_access_token(user.id + 1, created.id).await.is_err());
+        repository.revoke_access_token(user.id, created.id).await.expect("[revoke_access_token] returned Err");
+        assert!(repository.find_by_access_token(token).await.is_err());
+        assert!(repository.revoke_access_token(user.id, created.id).await.is_err());
     }
 }
 
@@ -142,16 +432,24 @@ pub mod test_utils {
     use super::*;
 
     type UserData = HashMap<i32, User>;
+    // token hash and creation time by user
+    type FeedData = HashMap<i32, (String, DateTime<Utc>)>;
+    // owner and token hash by token id
+    type AccessTokenData = HashMap<i32, (i32, String, AccessToken)>;
 
     #[derive(Debug, Clone)]
     pub struct UserRepositoryForMemory {
         store: Arc<RwLock<UserData>>,
+        feeds: Arc<RwLock<FeedData>>,
+        access_tokens: Arc<RwLock<AccessTokenData>>,
     }
 
     impl UserRepositoryForMemory {
         pub fn new() -> Self {
             UserRepositoryForMemory {
                 store: Arc::default(),
+                feeds: Arc::default(),
+                access_tokens: Arc::default(),
             }
         }
 
@@ -175,18 +473,24 @@ pub mod test_utils {
                 .map(|u| u.id);
 
             if let Some(id) = existing_id {
-                let updated = User::new(
-                    id,
-                    payload.sub.clone(),
-                    Some(payload.name.clone()),
-                    Some(payload.email.clone()),
-                );
+                let updated = User {
+                    email_verified: payload.email_verified,
+                    ..User::new(
+                        id,
+                        payload.sub.clone(),
+                        Some(payload.name.clone()),
+                        Some(payload.email.clone()),
+                    )
+                };
                 store.insert(id, updated.clone());
                 return Ok(updated);
             }
 
             let id = (store.len() + 1) as i32;
-            let user = User::new(id, payload.sub.clone(), Some(payload.name.clone()), Some(payload.email.clone()));
+            let user = User {
+                email_verified: payload.email_verified,
+                ..User::new(id, payload.sub.clone(), Some(payload.name.clone()), Some(payload.email.clone()))
+            };
             store.insert(id, user.clone());
             Ok(user)
         }
@@ -211,6 +515,17 @@ pub mod test_utils {
             Ok(user)
         }
 
+        async fn find_by_email(&self, email: String) -> anyhow::Result<User> {
+            let store = self.read_store_ref();
+            let user = store
+                .values()
+                .filter(|u| u.email_verified && u.email.as_deref() == Some(email.as_str()))
+                .min_by_key(|u| u.id)
+                .cloned()
+                .ok_or(RepositoryError::NotFound(0))?;
+            Ok(user)
+        }
+
         async fn update_name(&self, sub: String, payload: UpdateUser) -> anyhow::Result<User> {
             let mut store = self.write_store_ref();
             let user = store
@@ -220,6 +535,91 @@ pub mod test_utils {
             user.name = Some(payload.name);
             Ok(user.clone())
         }
+
+        async fn create_calendar_feed(&self, user_id: i32) -> anyhow::Result<CalendarFeed> {
+            let token = generate_secret_token();
+            let created_at = Utc::now();
+            self.feeds.write().unwrap().insert(user_id, (hash_token(&token), created_at));
+            Ok(CalendarFeed { created_at, token: Some(token) })
+        }
+
+        async fn find_calendar_feed(&self, user_id: i32) -> anyhow::Result<CalendarFeed> {
+            let feeds = self.feeds.read().unwrap();
+            let (_, created_at) = feeds.get(&user_id).ok_or(RepositoryError::NotFound(user_id))?;
+            Ok(CalendarFeed { created_at: *created_at, token: None })
+        }
+
+        async fn revoke_calendar_feed(&self, user_id: i32) -> anyhow::Result<()> {
+            self.feeds.write().unwrap().remove(&user_id).ok_or(RepositoryError::NotFound(user_id))?;
+            Ok(())
+        }
+
+        async fn find_by_calendar_token(&self, token: String) -> anyhow::Result<User> {
+            let token_hash = hash_token(&token);
+            let user_id = self.feeds
+                .read()
+                .unwrap()
+                .iter()
+                .find(|(_, (hash, _))| *hash == token_hash)
+                .map(|(user_id, _)| *user_id)
+                .ok_or(RepositoryError::NotFound(0))?;
+            self.find(user_id).await
+        }
+
+        async fn create_access_token(&self, user_id: i32, payload: CreateAccessToken) -> anyhow::Result<AccessToken> {
+            let token = generate_access_token();
+            let mut access_tokens = self.access_tokens.write().unwrap();
+            let access_token = AccessToken {
+                id: (access_tokens.len() + 1) as i32,
+                name: payload.name,
+                scopes: normalize_scopes(payload.scopes),
+                token_hint: token[..TOKEN_HINT_LENGTH].to_string(),
+                expires_at: payload.expires_at,
+                last_used_at: None,
+                created_at: Utc::now(),
+                token: None,
+            };
+            access_tokens.insert(access_token.id, (user_id, hash_token(&token), access_token.clone()));
+            Ok(AccessToken { token: Some(token), ..access_token })
+        }
+
+        async fn all_access_tokens(&self, user_id: i32) -> anyhow::Result<Vec<AccessToken>> {
+            let mut access_tokens: Vec<AccessToken> = self.access_tokens
+                .read()
+                .unwrap()
+                .values()
+                .filter(|(owner_id, _, _)| *owner_id == user_id)
+                .map(|(_, _, access_token)| access_token.clone())
+                .collect();
+            access_tokens.sort_by_key(|access_token| access_token.id);
+            Ok(access_tokens)
+        }
+
+        async fn revoke_access_token(&self, user_id: i32, id: i32) -> anyhow::Result<()> {
+            let mut access_tokens = self.access_tokens.write().unwrap();
+            match access_tokens.get(&id) {
+                Some((owner_id, _, _)) if *owner_id == user_id => {
+                    access_tokens.remove(&id);
+                    Ok(())
+                }
+                _ => Err(RepositoryError::NotFound(id).into()),
+            }
+        }
+
+        async fn find_by_access_token(&self, token: String) -> anyhow::Result<(User, AccessToken)> {
+            let token_hash = hash_token(&token);
+            let now = Utc::now();
+            let (user_id, access_token) = {
+                let mut access_tokens = self.access_tokens.write().unwrap();
+                let (user_id, _, access_token) = access_tokens
+                    .values_mut()
+                    .find(|(_, hash, access_token)| *hash == token_hash && !access_token.is_expired(now))
+                    .ok_or(RepositoryError::NotFound(0))?;
+                access_token.last_used_at = Some(now);
+                (*user_id, access_token.clone())
+            };
+            Ok((self.find(user_id).await?, access_token))
+        }
     }
 
     mod test {
@@ -231,12 +631,15 @@ pub mod test_utils {
             let name = "user_name".to_string();
             let email = "user@example.com".to_string();
             let id = 1;
-            let expected = User::new(id, sub.clone(), Some(name.clone()), Some(email.clone()));
+            let expected = User {
+                email_verified: true,
+                ..User::new(id, sub.clone(), Some(name.clone()), Some(email.clone()))
+            };
 
             // create
             let repository = UserRepositoryForMemory::new();
             let user = repository
-                .create(CreateUser::new(sub.clone(), name.clone(), email.clone()))
+                .create(CreateUser::new(sub.clone(), name.clone(), email.clone()).verified())
                 .await
                 .expect("failed create user");
             assert_eq!(expected, user);
@@ -254,6 +657,19 @@ pub mod test_utils {
                 .await
                 .expect("failed find_by_sub");
             assert_eq!(expected, user);
+
+            // find_by_email
+            let user = repository
+                .find_by_email(email.clone())
+                .await
+                .expect("failed find_by_email");
+            assert_eq!(expected, user);
+            assert!(repository.find_by_email("unknown@example.com".to_string()).await.is_err());
+            repository
+                .create(CreateUser::new("auth0|unverified".to_string(), name.clone(), "unverified@example.com".to_string()))
+                .await
+                .expect("failed create user");
+            assert!(repository.find_by_email("unverified@example.com".to_string()).await.is_err());
         }
     }
 }
@@ -1,10 +1,17 @@
 use async_trait::async_trait;
-use sqlx::{FromRow, PgPool};
+use chrono::{Duration, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::{Executor, FromRow, PgConnection, PgPool, Postgres};
 use crate::models::{
-    workspace::{CreateWorkspace, WorkspaceEntity},
+    activity::{ActivityAction, ActivityTarget, NewActivity},
+    event::{EventTarget, WorkspaceEvent},
+    workspace::{
+        CreateWorkspace, UpdateWorkspace, WorkspaceEntity, WorkspaceInvitation,
+        WorkspaceMember, WorkspaceRole, INVITATION_TTL_DAYS,
+    },
     user::User,
 };
-use super::RepositoryError;
+use super::{activity, event, status, RepositoryError};
 
 #[derive(Debug, Clone, PartialEq, Eq, FromRow)]
 struct WorkspaceWithUserFromRow {
@@ -15,6 +22,8 @@ struct WorkspaceWithUserFromRow {
     user_sub: Option<String>,
     user_name: Option<String>,
     user_email: Option<String>,
+    user_email_verified: Option<bool>,
+    user_role: Option<WorkspaceRole>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, FromRow)]
@@ -24,6 +33,22 @@ struct WorkspaceFromRow {
     is_personal: bool,
 }
 
+fn member_from_row(row: &WorkspaceWithUserFromRow) -> Option<WorkspaceMember> {
+    match (row.user_id, row.user_sub.clone(), row.user_role) {
+        (Some(user_id), Some(user_sub), Some(role)) => Some(WorkspaceMember::new(
+            User {
+                id: user_id,
+                sub: user_sub,
+                name: row.user_name.clone(),
+                email: row.user_email.clone(),
+                email_verified: row.user_email_verified.unwrap_or_default(),
+            },
+            role,
+        )),
+        _ => None,
+    }
+}
+
 fn fold_entities(rows: Vec<WorkspaceWithUserFromRow>) -> Vec<WorkspaceEntity> {
     let mut rows = rows.iter();
     let mut accum: Vec<WorkspaceEntity> = vec![];
@@ -31,49 +56,168 @@ fn fold_entities(rows: Vec<WorkspaceWithUserFromRow>) -> Vec<WorkspaceEntity> {
         let mut workspaces = accum.iter_mut();
         while let Some(ws) = workspaces.next() {
             if ws.id == row.id {
-                if let (Some(user_id), Some(user_sub)) =
-                    (row.user_id, row.user_sub.clone())
-                {
-                    ws.users.push(User {
-                        id: user_id,
-                        sub: user_sub,
-                        name: row.user_name.clone(),
-                        email: row.user_email.clone(),
-                    });
+                if let Some(member) = member_from_row(row) {
+                    if member.role == WorkspaceRole::Owner {
+                        ws.owner_id = Some(member.user.id);
+                    }
+                    ws.users.push(member);
                 }
                 continue 'outer;
             }
         }
 
-        let users = if let (Some(user_id), Some(user_sub))
-            = (row.user_id, row.user_sub.clone())
-        {
-            vec![User {
-                id: user_id,
-                sub: user_sub,
-                name: row.user_name.clone(),
-                email: row.user_email.clone(),
-            }]
-        } else {
-            vec![]
-        };
+        let users: Vec<WorkspaceMember> = member_from_row(row).into_iter().collect();
+        let owner_id = users
+            .iter()
+            .find(|member| member.role == WorkspaceRole::Owner)
+            .map(|member| member.user.id);
 
         accum.push(WorkspaceEntity {
             id: row.id,
             name: row.name.clone(),
             is_personal: row.is_personal,
+            owner_id,
             users,
         });
     }
     accum
 }
 
+async fn find_workspace<'e, E>(executor: E, id: i32) -> anyhow::Result<WorkspaceEntity>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let items = sqlx::query_as::<_, WorkspaceWithUserFromRow>(
+        r#"
+select workspaces.id, workspaces.name, workspaces.is_personal,
+       users.id as user_id,
+       users.sub as user_sub,
+       users.name as user_name,
+       users.email as user_email,
+       users.email_verified as user_email_verified,
+       wu.role as user_role
+from workspaces
+            left outer join workspace_users wu on workspaces.id = wu.workspace_id
+            left outer join users on users.id = wu.user_id
+where workspaces.id = $1
+order by users.id
+        "#,
+    )
+    .bind(id)
+    .fetch_all(executor)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
+        _ => RepositoryError::Unexpected(e.to_string()),
+    })?;
+
+    let workspaces = fold_entities(items);
+    let workspace = workspaces.first().ok_or(RepositoryError::NotFound(id))?;
+    Ok(workspace.clone())
+}
+
+// メンバーの増減や権限変更もワークスペースの更新として記録する
+async fn record_workspace_activity(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    before: Option<&WorkspaceEntity>,
+    after: Option<&WorkspaceEntity>,
+) -> anyhow::Result<()> {
+    let Some(workspace) = after.or(before) else {
+        return Ok(());
+    };
+    let activity = NewActivity::new(Some(workspace.id), actor_id, ActivityTarget::Workspace, workspace.id, before, after);
+    activity::record(conn, activity).await
+}
+
+async fn notify_member_change(
+    conn: &mut PgConnection,
+    workspace_id: i32,
+    user_id: i32,
+    action: ActivityAction,
+    actor_id: i32,
+) -> anyhow::Result<()> {
+    event::notify(conn, WorkspaceEvent::new(workspace_id, EventTarget::Member, user_id, action, actor_id)).await
+}
+
+/// Fails with `UnknownAssignee` for the first of `user_ids` that isn't a member of the workspace.
+pub async fn check_members(conn: &mut PgConnection, workspace_id: i32, user_ids: &[i32]) -> anyhow::Result<()> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    let unknown = sqlx::query_scalar::<_, i32>(
+        r#"
+select ids.id from unnest($2::int4[]) as ids(id)
+where ids.id not in (select user_id from workspace_users where workspace_id = $1)
+limit 1
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(user_ids)
+    .fetch_optional(conn)
+    .await?;
+
+    match unknown {
+        Some(id) => Err(RepositoryError::UnknownAssignee(id).into()),
+        None => Ok(()),
+    }
+}
+
+fn generate_invitation_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+// 未回答の招待があれば権限と有効期限を更新して同じトークンを使い回す
+async fn upsert_invitation(
+    conn: &mut PgConnection,
+    id: i32,
+    invited_by: i32,
+    email: &str,
+    role: WorkspaceRole,
+) -> anyhow::Result<String> {
+    let token = sqlx::query_scalar::<_, String>(
+        r#"
+insert into workspace_invitations (workspace_id, email, role, token, invited_by, expires_at)
+values ($1, $2, $3, $4, $5, $6)
+on conflict (workspace_id, lower(email)) where status = 'pending'
+do update set role = excluded.role, invited_by = excluded.invited_by, expires_at = excluded.expires_at
+returning token
+        "#,
+    )
+    .bind(id)
+    .bind(email)
+    .bind(role)
+    .bind(generate_invitation_token())
+    .bind(invited_by)
+    .bind(Utc::now() + Duration::days(INVITATION_TTL_DAYS))
+    .fetch_one(conn)
+    .await?;
+
+    Ok(token)
+}
+
 #[async_trait]
 pub trait WorkspaceRepository: Send + Sync + 'static {
     async fn create(&self, user_id: i32, payload: CreateWorkspace) -> anyhow::Result<WorkspaceEntity>;
     async fn find(&self, id: i32) -> anyhow::Result<WorkspaceEntity>;
     async fn all_by_user(&self, user_id: i32) -> anyhow::Result<Vec<WorkspaceEntity>>;
+    async fn update(&self, id: i32, actor_id: i32, payload: UpdateWorkspace) -> anyhow::Result<WorkspaceEntity>;
+    async fn delete(&self, id: i32, actor_id: i32) -> anyhow::Result<()>;
     async fn is_member(&self, id: i32, user_id: i32) -> anyhow::Result<bool>;
+    async fn find_role(&self, id: i32, user_id: i32) -> anyhow::Result<Option<WorkspaceRole>>;
+    async fn add_member(&self, id: i32, actor_id: i32, user_id: i32, role: WorkspaceRole) -> anyhow::Result<()>;
+    async fn update_role(&self, id: i32, actor_id: i32, user_id: i32, role: WorkspaceRole) -> anyhow::Result<()>;
+    async fn remove_member(&self, id: i32, actor_id: i32, user_id: i32) -> anyhow::Result<()>;
+    async fn transfer_ownership(&self, id: i32, actor_id: i32, user_id: i32) -> anyhow::Result<WorkspaceEntity>;
+    async fn create_invitation(&self, id: i32, invited_by: i32, email: String, role: WorkspaceRole) -> anyhow::Result<WorkspaceInvitation>;
+    async fn find_invitation(&self, token: String) -> anyhow::Result<WorkspaceInvitation>;
+    async fn pending_invitations(&self, email: String) -> anyhow::Result<Vec<WorkspaceInvitation>>;
+    async fn accept_invitation(&self, token: String, user_id: i32) -> anyhow::Result<WorkspaceEntity>;
+    async fn decline_invitation(&self, token: String) -> anyhow::Result<()>;
 }
 
 #[derive(Debug, Clone)]
@@ -103,11 +247,13 @@ returning id, name, is_personal
         .fetch_one(&mut *tx)
         .await?;
 
-        // 作成者を必ずメンバーに追加
+        status::create_defaults(&mut tx, row.id).await?;
+
+        // 作成者を必ずオーナーとして追加
         sqlx::query(
             r#"
-insert into workspace_users (workspace_id, user_id)
-values ($1, $2)
+insert into workspace_users (workspace_id, user_id, role)
+values ($1, $2, 'owner')
             "#,
         )
         .bind(row.id)
@@ -122,48 +268,42 @@ values ($1, $2)
 insert into workspace_users (workspace_id, user_id)
 select $1, users.id
 from unnest ($2::text[]) as t(email)
-inner join users on users.email = t.email
+inner join users on users.email = t.email and users.email_verified
 where users.id != $3
                 "#,
             )
             .bind(row.id)
-            .bind(payload.user_emails)
+            .bind(payload.user_emails.clone())
             .bind(user_id)
             .execute(&mut *tx)
             .await?;
+
+            // 未登録または未確認のemailには招待を送る
+            let unregistered = sqlx::query_scalar::<_, String>(
+                r#"
+select distinct t.email
+from unnest ($1::text[]) as t(email)
+where not exists (select 1 from users where users.email = t.email and users.email_verified)
+                "#,
+            )
+            .bind(payload.user_emails)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            for email in unregistered {
+                upsert_invitation(&mut tx, row.id, user_id, &email, WorkspaceRole::Editor).await?;
+            }
         }
 
-        tx.commit().await?;
+        let workspace = find_workspace(&mut *tx, row.id).await?;
+        record_workspace_activity(&mut tx, user_id, None, Some(&workspace)).await?;
 
-        let workspace = self.find(row.id).await?;
+        tx.commit().await?;
         Ok(workspace)
     }
 
     async fn find(&self, id: i32) -> anyhow::Result<WorkspaceEntity> {
-        let items = sqlx::query_as::<_, WorkspaceWithUserFromRow>(
-            r#"
-select workspaces.id, workspaces.name, workspaces.is_personal,
-       users.id as user_id,
-       users.sub as user_sub,
-       users.name as user_name,
-       users.email as user_email
-from workspaces
-            left outer join workspace_users wu on workspaces.id = wu.workspace_id
-            left outer join users on users.id = wu.user_id
-where workspaces.id = $1
-            "#,
-        )
-        .bind(id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => RepositoryError::NotFound(id),
-            _ => RepositoryError::Unexpected(e.to_string()),
-        })?;
-
-        let workspaces = fold_entities(items);
-        let workspace = workspaces.first().ok_or(RepositoryError::NotFound(id))?;
-        Ok(workspace.clone())
+        find_workspace(&self.pool, id).await
     }
 
     async fn all_by_user(&self, user_id: i32) -> anyhow::Result<Vec<WorkspaceEntity>> {
@@ -173,13 +313,15 @@ select workspaces.id, workspaces.name, workspaces.is_personal,
        users.id as user_id,
        users.sub as user_sub,
        users.name as user_name,
-       users.email as user_email
+       users.email as user_email,
+       users.email_verified as user_email_verified,
+       wu2.role as user_role
 from workspaces
             inner join workspace_users wu on workspaces.id = wu.workspace_id
             left outer join workspace_users wu2 on workspaces.id = wu2.workspace_id
             left outer join users on users.id = wu2.user_id
 where wu.user_id = $1
-order by workspaces.is_personal desc, workspaces.id desc
+order by workspaces.is_personal desc, workspaces.id desc, users.id
             "#,
         )
         .bind(user_id)
@@ -190,6 +332,77 @@ order by workspaces.is_personal desc, workspaces.id desc
         Ok(workspaces)
     }
 
+    async fn update(&self, id: i32, actor_id: i32, payload: UpdateWorkspace) -> anyhow::Result<WorkspaceEntity> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_workspace = find_workspace(&mut *tx, id).await?;
+        sqlx::query(
+            r#"
+update workspaces set name = $1
+where id = $2
+            "#,
+        )
+        .bind(payload.name.unwrap_or(old_workspace.name.clone()))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let workspace = find_workspace(&mut *tx, id).await?;
+        record_workspace_activity(&mut tx, actor_id, Some(&old_workspace), Some(&workspace)).await?;
+
+        tx.commit().await?;
+        Ok(workspace)
+    }
+
+    async fn delete(&self, id: i32, actor_id: i32) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_workspace = find_workspace(&mut *tx, id).await?;
+
+        // todoとラベルの紐付けごと削除
+        sqlx::query(
+            r#"
+delete from todo_labels
+where todo_id in (select id from todos where workspace_id = $1)
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("delete from todos where workspace_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("delete from workspace_invitations where workspace_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("delete from workspace_users where workspace_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query("delete from workspaces where id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(id).into());
+        }
+        record_workspace_activity(&mut tx, actor_id, Some(&old_workspace), None).await?;
+        // closes the event streams of everyone who was subscribed
+        for member in &old_workspace.users {
+            notify_member_change(&mut tx, id, member.user.id, ActivityAction::Delete, actor_id).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
     async fn is_member(&self, id: i32, user_id: i32) -> anyhow::Result<bool> {
         let row = sqlx::query(
             r#"
@@ -204,12 +417,450 @@ where workspace_id = $1 and user_id = $2
 
         Ok(row.is_some())
     }
+
+    async fn find_role(&self, id: i32, user_id: i32) -> anyhow::Result<Option<WorkspaceRole>> {
+        let role = sqlx::query_scalar::<_, WorkspaceRole>(
+            r#"
+select role from workspace_users
+where workspace_id = $1 and user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(role)
+    }
+
+    async fn add_member(&self, id: i32, actor_id: i32, user_id: i32, role: WorkspaceRole) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_workspace = find_workspace(&mut *tx, id).await?;
+        sqlx::query(
+            r#"
+insert into workspace_users (workspace_id, user_id, role)
+values ($1, $2, $3)
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(role)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => RepositoryError::Duplicate(user_id),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
+
+        let workspace = find_workspace(&mut *tx, id).await?;
+        record_workspace_activity(&mut tx, actor_id, Some(&old_workspace), Some(&workspace)).await?;
+        notify_member_change(&mut tx, id, user_id, ActivityAction::Create, actor_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_role(&self, id: i32, actor_id: i32, user_id: i32, role: WorkspaceRole) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_workspace = find_workspace(&mut *tx, id).await?;
+        let result = sqlx::query(
+            r#"
+update workspace_users set role = $3
+where workspace_id = $1 and user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(role)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(user_id).into());
+        }
+
+        let workspace = find_workspace(&mut *tx, id).await?;
+        record_workspace_activity(&mut tx, actor_id, Some(&old_workspace), Some(&workspace)).await?;
+        notify_member_change(&mut tx, id, user_id, ActivityAction::Update, actor_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn remove_member(&self, id: i32, actor_id: i32, user_id: i32) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_workspace = find_workspace(&mut *tx, id).await?;
+        let result = sqlx::query(
+            r#"
+delete from workspace_users
+where workspace_id = $1 and user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(user_id).into());
+        }
+
+        let workspace = find_workspace(&mut *tx, id).await?;
+        record_workspace_activity(&mut tx, actor_id, Some(&old_workspace), Some(&workspace)).await?;
+        notify_member_change(&mut tx, id, user_id, ActivityAction::Delete, actor_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn transfer_ownership(&self, id: i32, actor_id: i32, user_id: i32) -> anyhow::Result<WorkspaceEntity> {
+        let mut tx = self.pool.begin().await?;
+
+        let old_workspace = find_workspace(&mut *tx, id).await?;
+
+        // 旧オーナーは管理者として残る
+        sqlx::query(
+            r#"
+update workspace_users set role = 'admin'
+where workspace_id = $1 and role = 'owner'
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+update workspace_users set role = 'owner'
+where workspace_id = $1 and user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(user_id).into());
+        }
+
+        let workspace = find_workspace(&mut *tx, id).await?;
+        record_workspace_activity(&mut tx, actor_id, Some(&old_workspace), Some(&workspace)).await?;
+        for changed in old_workspace.owner_id.into_iter().chain([user_id]) {
+            notify_member_change(&mut tx, id, changed, ActivityAction::Update, actor_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(workspace)
+    }
+
+    async fn create_invitation(&self, id: i32, invited_by: i32, email: String, role: WorkspaceRole) -> anyhow::Result<WorkspaceInvitation> {
+        let mut conn = self.pool.acquire().await?;
+        let token = upsert_invitation(&mut conn, id, invited_by, &email, role).await?;
+
+        let invitation = self.find_invitation(token).await?;
+        Ok(invitation)
+    }
+
+    async fn find_invitation(&self, token: String) -> anyhow::Result<WorkspaceInvitation> {
+        let invitation = sqlx::query_as::<_, WorkspaceInvitation>(
+            r#"
+select wi.id, wi.workspace_id, workspaces.name as workspace_name, wi.email, wi.role,
+       wi.token, wi.status, wi.invited_by, wi.expires_at
+from workspace_invitations wi
+            inner join workspaces on workspaces.id = wi.workspace_id
+where wi.token = $1
+            "#,
+        )
+        .bind(token)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => RepositoryError::NotFound(0),
+            _ => RepositoryError::Unexpected(e.to_string()),
+        })?;
+
+        Ok(invitation)
+    }
+
+    async fn pending_invitations(&self, email: String) -> anyhow::Result<Vec<WorkspaceInvitation>> {
+        let invitations = sqlx::query_as::<_, WorkspaceInvitation>(
+            r#"
+select wi.id, wi.workspace_id, workspaces.name as workspace_name, wi.email, wi.role,
+       wi.token, wi.status, wi.invited_by, wi.expires_at
+from workspace_invitations wi
+            inner join workspaces on workspaces.id = wi.workspace_id
+where lower(wi.email) = lower($1) and wi.status = 'pending' and wi.expires_at > now()
+order by wi.id desc
+            "#,
+        )
+        .bind(email)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(invitations)
+    }
+
+    async fn accept_invitation(&self, token: String, user_id: i32) -> anyhow::Result<WorkspaceEntity> {
+        let mut tx = self.pool.begin().await?;
+
+        let (workspace_id, role) = sqlx::query_as::<_, (i32, WorkspaceRole)>(
+            r#"
+update workspace_invitations set status = 'accepted'
+where token = $1 and status = 'pending' and expires_at > now()
+returning workspace_id, role
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(RepositoryError::NotFound(0))?;
+
+        // 既にメンバーなら権限はそのまま
+        let old_workspace = find_workspace(&mut *tx, workspace_id).await?;
+        let result = sqlx::query(
+            r#"
+insert into workspace_users (workspace_id, user_id, role)
+values ($1, $2, $3)
+on conflict do nothing
+            "#,
+        )
+        .bind(workspace_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(&mut *tx)
+        .await?;
+
+        let workspace = find_workspace(&mut *tx, workspace_id).await?;
+        if result.rows_affected() > 0 {
+            record_workspace_activity(&mut tx, user_id, Some(&old_workspace), Some(&workspace)).await?;
+            notify_member_change(&mut tx, workspace_id, user_id, ActivityAction::Create, user_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(workspace)
+    }
+
+    async fn decline_invitation(&self, token: String) -> anyhow::Result<()> {
+        let result = sqlx::query(
+            r#"
+update workspace_invitations set status = 'declined'
+where token = $1 and status = 'pending'
+            "#,
+        )
+        .bind(token)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(0).into());
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 #[cfg(feature = "database-test")]
 mod test {
     use super::*;
+    use crate::models::{label::CreateLabel, todo::CreateTodo, user::CreateUser, workspace::InvitationStatus};
+    use crate::repositories::{
+        label::{LabelRepository, LabelRepositoryForDb},
+        todo::{TodoRepository, TodoRepositoryForDb},
+        user::{UserRepository, UserRepositoryForDb},
+    };
+    use dotenvy::dotenv;
+    use sqlx::PgPool;
+    use std::env;
+
+    #[tokio::test]
+    async fn membership_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let owner = user_repository
+            .create(CreateUser::new("auth0|test_workspace_owner".to_string(), "test_workspace_owner".to_string(), "workspace_owner@example.com".to_string()))
+            .await
+            .expect("Failed to create test owner");
+        let member = user_repository
+            .create(CreateUser::new("auth0|test_workspace_member".to_string(), "test_workspace_member".to_string(), "workspace_member@example.com".to_string()))
+            .await
+            .expect("Failed to create test member");
+
+        let repository = WorkspaceRepositoryForDb::new(pool.clone());
+
+        // create
+        let workspace = repository
+            .create(owner.id, CreateWorkspace::new("test_workspace".to_string(), false, vec![]))
+            .await
+            .expect("[create] returned Err");
+        assert_eq!(Some(owner.id), workspace.owner_id);
+        assert_eq!(vec![WorkspaceMember::new(owner.clone(), WorkspaceRole::Owner)], workspace.users);
+
+        // add_member
+        repository.add_member(workspace.id, owner.id, member.id, WorkspaceRole::Viewer).await.expect("[add_member] returned Err");
+        assert!(repository.is_member(workspace.id, member.id).await.unwrap());
+        assert_eq!(Some(WorkspaceRole::Viewer), repository.find_role(workspace.id, member.id).await.unwrap());
+        assert!(repository.add_member(workspace.id, owner.id, member.id, WorkspaceRole::Editor).await.is_err());
+        let workspaces = repository.all_by_user(member.id).await.expect("[all_by_user] returned Err");
+        assert!(workspaces.iter().any(|ws| ws.id == workspace.id));
+
+        // update_role
+        repository.update_role(workspace.id, owner.id, member.id, WorkspaceRole::Editor).await.expect("[update_role] returned Err");
+        assert_eq!(Some(WorkspaceRole::Editor), repository.find_role(workspace.id, member.id).await.unwrap());
+
+        // transfer_ownership
+        let workspace = repository
+            .transfer_ownership(workspace.id, owner.id, member.id)
+            .await
+            .expect("[transfer_ownership] returned Err");
+        assert_eq!(Some(member.id), workspace.owner_id);
+        assert_eq!(Some(WorkspaceRole::Admin), workspace.role_of(owner.id));
+
+        // remove_member
+        repository.remove_member(workspace.id, member.id, owner.id).await.expect("[remove_member] returned Err");
+        assert!(!repository.is_member(workspace.id, owner.id).await.unwrap());
+        assert!(repository.remove_member(workspace.id, member.id, owner.id).await.is_err());
+        assert_eq!(None, repository.find_role(workspace.id, owner.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn update_delete_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let owner = user_repository
+            .create(CreateUser::new("auth0|test_workspace_deleter".to_string(), "test_workspace_deleter".to_string(), "workspace_deleter@example.com".to_string()))
+            .await
+            .expect("Failed to create test owner");
+        let repository = WorkspaceRepositoryForDb::new(pool.clone());
+        let workspace = repository
+            .create(owner.id, CreateWorkspace::new("before_rename".to_string(), false, vec![]))
+            .await
+            .expect("[create] returned Err");
+
+        // update
+        let workspace = repository
+            .update(workspace.id, owner.id, UpdateWorkspace { name: Some("after_rename".to_string()) })
+            .await
+            .expect("[update] returned Err");
+        assert_eq!("after_rename", workspace.name);
+        let workspace = repository
+            .update(workspace.id, owner.id, UpdateWorkspace { name: None })
+            .await
+            .expect("[update] returned Err");
+        assert_eq!("after_rename", workspace.name);
+
+        // delete takes the todos and their labels along
+        let label = LabelRepositoryForDb::new(pool.clone())
+            .create_in_workspace(workspace.id, owner.id, CreateLabel::new("test_workspace_label".to_string()))
+            .await
+            .expect("Failed to create test label");
+        let todo_repository = TodoRepositoryForDb::new(pool.clone());
+        let todo = todo_repository
+            .create(owner.id, workspace.id, CreateTodo::new("doomed".to_string(), vec![label.id]))
+            .await
+            .expect("Failed to create test todo");
+        repository.delete(workspace.id, owner.id).await.expect("[delete] returned Err");
+        assert!(repository.find(workspace.id).await.is_err());
+        assert!(todo_repository.find(todo.id).await.is_err());
+        let (count,): (i64,) = sqlx::query_as("select count(*) from todo_labels where todo_id = $1")
+            .bind(todo.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(0, count);
+        let (count,): (i64,) = sqlx::query_as("select count(*) from labels where id = $1")
+            .bind(label.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(0, count);
+        assert!(repository.delete(workspace.id, owner.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn invitation_scenario() {
+        dotenv().ok();
+        let database_url = &env::var("DATABASE_URL").expect("undefined [DATABASE_URL]");
+        let pool = PgPool::connect(database_url)
+            .await
+            .expect("fail connect database");
+
+        let user_repository = UserRepositoryForDb::new(pool.clone());
+        let owner = user_repository
+            .create(CreateUser::new("auth0|test_workspace_inviter".to_string(), "test_workspace_inviter".to_string(), "workspace_inviter@example.com".to_string()))
+            .await
+            .expect("Failed to create test owner");
+        user_repository
+            .create(CreateUser::new("auth0|test_workspace_unverified".to_string(), "test_workspace_unverified".to_string(), "unverified_invitee@example.com".to_string()))
+            .await
+            .expect("Failed to create test unverified user");
+
+        let repository = WorkspaceRepositoryForDb::new(pool.clone());
+
+        // unregistered and unverified emails passed on create become invitations
+        let workspace = repository
+            .create(
+                owner.id,
+                CreateWorkspace::new("invite_workspace".to_string(), false, vec!["invitee@example.com".to_string(), "unverified_invitee@example.com".to_string()]),
+            )
+            .await
+            .expect("[create] returned Err");
+        assert_eq!(1, workspace.users.len());
+        assert_eq!(1, repository.pending_invitations("unverified_invitee@example.com".to_string()).await.unwrap().len());
+        let invitations = repository
+            .pending_invitations("Invitee@example.com".to_string())
+            .await
+            .expect("[pending_invitations] returned Err");
+        assert_eq!(1, invitations.len());
+        assert_eq!(workspace.id, invitations[0].workspace_id);
+        assert_eq!("invite_workspace", invitations[0].workspace_name);
+        assert_eq!(WorkspaceRole::Editor, invitations[0].role);
+
+        // inviting again refreshes the pending invitation
+        let invitation = repository
+            .create_invitation(workspace.id, owner.id, "invitee@example.com".to_string(), WorkspaceRole::Viewer)
+            .await
+            .expect("[create_invitation] returned Err");
+        assert_eq!(invitations[0].token, invitation.token);
+        assert_eq!(WorkspaceRole::Viewer, invitation.role);
+
+        // accept
+        let invitee = user_repository
+            .create(CreateUser::new("auth0|test_workspace_invitee".to_string(), "test_workspace_invitee".to_string(), "invitee@example.com".to_string()))
+            .await
+            .expect("Failed to create test invitee");
+        let workspace = repository
+            .accept_invitation(invitation.token.clone(), invitee.id)
+            .await
+            .expect("[accept_invitation] returned Err");
+        assert_eq!(Some(WorkspaceRole::Viewer), workspace.role_of(invitee.id));
+        let invitation = repository.find_invitation(invitation.token).await.unwrap();
+        assert_eq!(InvitationStatus::Accepted, invitation.status);
+        assert!(repository.accept_invitation(invitation.token.clone(), invitee.id).await.is_err());
+        assert!(repository.pending_invitations("invitee@example.com".to_string()).await.unwrap().is_empty());
+
+        // decline
+        let invitation = repository
+            .create_invitation(workspace.id, owner.id, "decliner@example.com".to_string(), WorkspaceRole::Editor)
+            .await
+            .expect("[create_invitation] returned Err");
+        repository.decline_invitation(invitation.token.clone()).await.expect("[decline_invitation] returned Err");
+        assert!(repository.decline_invitation(invitation.token).await.is_err());
+        assert!(repository.pending_invitations("decliner@example.com".to_string()).await.unwrap().is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -219,18 +870,23 @@ pub mod test_utils {
         sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     };
     use super::*;
+    use crate::models::workspace::InvitationStatus;
 
     type WorkspaceData = HashMap<i32, WorkspaceEntity>;
 
     #[derive(Debug, Clone)]
     pub struct WorkspaceRepositoryForMemory {
         store: Arc<RwLock<WorkspaceData>>,
+        invitations: Arc<RwLock<Vec<WorkspaceInvitation>>>,
+        users: Vec<User>,
     }
 
     impl WorkspaceRepositoryForMemory {
-        pub fn new() -> Self {
+        pub fn new(users: Vec<User>) -> Self {
             WorkspaceRepositoryForMemory {
                 store: Arc::default(),
+                invitations: Arc::default(),
+                users,
             }
         }
         fn write_store_ref(&self) -> RwLockWriteGuard<WorkspaceData> {
@@ -240,30 +896,278 @@ pub mod test_utils {
         fn read_store_ref(&self) -> RwLockReadGuard<WorkspaceData> {
             self.store.read().unwrap()
         }
+
+        fn resolve_user(&self, user_id: i32) -> anyhow::Result<User> {
+            let user = self.users
+                .iter()
+                .find(|user| user.id == user_id)
+                .cloned()
+                .ok_or(RepositoryError::NotFound(user_id))?;
+            Ok(user)
+        }
     }
 
     #[async_trait]
     impl WorkspaceRepository for WorkspaceRepositoryForMemory {
-        async fn create(&self, _user_id: i32, payload: CreateWorkspace) -> anyhow::Result<WorkspaceEntity> {
-            let mut store = self.write_store_ref();
-            let id = (store.len() + 1) as i32;
-            let workspace = WorkspaceEntity::new(id, payload.name, payload.is_personal, vec![]);
-            store.insert(id, workspace.clone());
+        async fn create(&self, user_id: i32, payload: CreateWorkspace) -> anyhow::Result<WorkspaceEntity> {
+            let mut users = vec![WorkspaceMember::new(self.resolve_user(user_id)?, WorkspaceRole::Owner)];
+            users.extend(
+                self.users
+                    .iter()
+                    .filter(|user| user.id != user_id)
+                    .filter(|user| user.email_verified)
+                    .filter(|user| user.email.as_ref().is_some_and(|email| payload.user_emails.contains(email)))
+                    .map(|user| WorkspaceMember::new(user.clone(), WorkspaceRole::Editor)),
+            );
+            let unregistered: Vec<String> = payload
+                .user_emails
+                .iter()
+                .filter(|email| !self.users.iter().any(|user| user.email_verified && user.email.as_ref() == Some(*email)))
+                .cloned()
+                .collect();
+            let workspace = {
+                let mut store = self.write_store_ref();
+                let id = (store.len() + 1) as i32;
+                let workspace = WorkspaceEntity::new(id, payload.name, payload.is_personal, Some(user_id), users);
+                store.insert(id, workspace.clone());
+                workspace
+            };
+            for email in unregistered {
+                self.create_invitation(workspace.id, user_id, email, WorkspaceRole::Editor).await?;
+            }
             Ok(workspace)
         }
 
         async fn find(&self, id: i32) -> anyhow::Result<WorkspaceEntity> {
             let store = self.read_store_ref();
-            store.get(&id).cloned().ok_or(anyhow::anyhow!("not found"))
+            let workspace = store.get(&id).cloned().ok_or(RepositoryError::NotFound(id))?;
+            Ok(workspace)
         }
 
-        async fn all_by_user(&self, _user_id: i32) -> anyhow::Result<Vec<WorkspaceEntity>> {
+        async fn all_by_user(&self, user_id: i32) -> anyhow::Result<Vec<WorkspaceEntity>> {
             let store = self.read_store_ref();
-            Ok(store.values().cloned().collect())
+            let mut workspaces: Vec<WorkspaceEntity> = store
+                .values()
+                .filter(|ws| ws.role_of(user_id).is_some())
+                .cloned()
+                .collect();
+            workspaces.sort_by_key(|ws| (!ws.is_personal, std::cmp::Reverse(ws.id)));
+            Ok(workspaces)
+        }
+
+        async fn update(&self, id: i32, _actor_id: i32, payload: UpdateWorkspace) -> anyhow::Result<WorkspaceEntity> {
+            let mut store = self.write_store_ref();
+            let workspace = store.get_mut(&id).ok_or(RepositoryError::NotFound(id))?;
+            if let Some(name) = payload.name {
+                workspace.name = name;
+            }
+            Ok(workspace.clone())
         }
 
-        async fn is_member(&self, _id: i32, _user_id: i32) -> anyhow::Result<bool> {
-            Ok(true)
+        async fn delete(&self, id: i32, _actor_id: i32) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            store.remove(&id).ok_or(RepositoryError::NotFound(id))?;
+            self.invitations.write().unwrap().retain(|invitation| invitation.workspace_id != id);
+            Ok(())
+        }
+
+        async fn is_member(&self, id: i32, user_id: i32) -> anyhow::Result<bool> {
+            let store = self.read_store_ref();
+            Ok(store
+                .get(&id)
+                .is_some_and(|ws| ws.role_of(user_id).is_some()))
+        }
+
+        async fn find_role(&self, id: i32, user_id: i32) -> anyhow::Result<Option<WorkspaceRole>> {
+            let store = self.read_store_ref();
+            Ok(store.get(&id).and_then(|ws| ws.role_of(user_id)))
+        }
+
+        async fn add_member(&self, id: i32, _actor_id: i32, user_id: i32, role: WorkspaceRole) -> anyhow::Result<()> {
+            let user = self.resolve_user(user_id)?;
+            let mut store = self.write_store_ref();
+            let workspace = store.get_mut(&id).ok_or(RepositoryError::NotFound(id))?;
+            if workspace.role_of(user_id).is_some() {
+                return Err(RepositoryError::Duplicate(user_id).into());
+            }
+            workspace.users.push(WorkspaceMember::new(user, role));
+            Ok(())
+        }
+
+        async fn update_role(&self, id: i32, _actor_id: i32, user_id: i32, role: WorkspaceRole) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            let workspace = store.get_mut(&id).ok_or(RepositoryError::NotFound(id))?;
+            let member = workspace
+                .users
+                .iter_mut()
+                .find(|member| member.user.id == user_id)
+                .ok_or(RepositoryError::NotFound(user_id))?;
+            member.role = role;
+            Ok(())
+        }
+
+        async fn remove_member(&self, id: i32, _actor_id: i32, user_id: i32) -> anyhow::Result<()> {
+            let mut store = self.write_store_ref();
+            let workspace = store.get_mut(&id).ok_or(RepositoryError::NotFound(id))?;
+            let len = workspace.users.len();
+            workspace.users.retain(|member| member.user.id != user_id);
+            if workspace.users.len() == len {
+                return Err(RepositoryError::NotFound(user_id).into());
+            }
+            Ok(())
+        }
+
+        async fn transfer_ownership(&self, id: i32, _actor_id: i32, user_id: i32) -> anyhow::Result<WorkspaceEntity> {
+            let mut store = self.write_store_ref();
+            let workspace = store.get_mut(&id).ok_or(RepositoryError::NotFound(id))?;
+            if workspace.role_of(user_id).is_none() {
+                return Err(RepositoryError::NotFound(user_id).into());
+            }
+            for member in workspace.users.iter_mut() {
+                if member.user.id == user_id {
+                    member.role = WorkspaceRole::Owner;
+                } else if member.role == WorkspaceRole::Owner {
+                    member.role = WorkspaceRole::Admin;
+                }
+            }
+            workspace.owner_id = Some(user_id);
+            Ok(workspace.clone())
+        }
+
+        async fn create_invitation(&self, id: i32, invited_by: i32, email: String, role: WorkspaceRole) -> anyhow::Result<WorkspaceInvitation> {
+            let workspace = self.find(id).await?;
+            let expires_at = Utc::now() + Duration::days(INVITATION_TTL_DAYS);
+            let mut invitations = self.invitations.write().unwrap();
+            if let Some(invitation) = invitations.iter_mut().find(|invitation| {
+                invitation.workspace_id == id
+                    && invitation.status == InvitationStatus::Pending
+                    && invitation.is_for(&email)
+            }) {
+                invitation.role = role;
+                invitation.invited_by = Some(invited_by);
+                invitation.expires_at = expires_at;
+                return Ok(invitation.clone());
+            }
+            let invitation = WorkspaceInvitation {
+                id: (invitations.len() + 1) as i32,
+                workspace_id: id,
+                workspace_name: workspace.name,
+                email,
+                role,
+                token: generate_invitation_token(),
+                status: InvitationStatus::Pending,
+                invited_by: Some(invited_by),
+                expires_at,
+            };
+            invitations.push(invitation.clone());
+            Ok(invitation)
+        }
+
+        async fn find_invitation(&self, token: String) -> anyhow::Result<WorkspaceInvitation> {
+            let invitations = self.invitations.read().unwrap();
+            let invitation = invitations
+                .iter()
+                .find(|invitation| invitation.token == token)
+                .cloned()
+                .ok_or(RepositoryError::NotFound(0))?;
+            Ok(invitation)
+        }
+
+        async fn pending_invitations(&self, email: String) -> anyhow::Result<Vec<WorkspaceInvitation>> {
+            let invitations = self.invitations.read().unwrap();
+            let mut pending: Vec<WorkspaceInvitation> = invitations
+                .iter()
+                .filter(|invitation| invitation.status == InvitationStatus::Pending)
+                .filter(|invitation| !invitation.is_expired() && invitation.is_for(&email))
+                .cloned()
+                .collect();
+            pending.sort_by_key(|invitation| std::cmp::Reverse(invitation.id));
+            Ok(pending)
+        }
+
+        async fn accept_invitation(&self, token: String, user_id: i32) -> anyhow::Result<WorkspaceEntity> {
+            let (workspace_id, role) = {
+                let mut invitations = self.invitations.write().unwrap();
+                let invitation = invitations
+                    .iter_mut()
+                    .find(|invitation| invitation.token == token)
+                    .filter(|invitation| invitation.status == InvitationStatus::Pending && !invitation.is_expired())
+                    .ok_or(RepositoryError::NotFound(0))?;
+                invitation.status = InvitationStatus::Accepted;
+                (invitation.workspace_id, invitation.role)
+            };
+            if !self.is_member(workspace_id, user_id).await? {
+                self.add_member(workspace_id, user_id, user_id, role).await?;
+            }
+            self.find(workspace_id).await
+        }
+
+        async fn decline_invitation(&self, token: String) -> anyhow::Result<()> {
+            let mut invitations = self.invitations.write().unwrap();
+            let invitation = invitations
+                .iter_mut()
+                .find(|invitation| invitation.token == token && invitation.status == InvitationStatus::Pending)
+                .ok_or(RepositoryError::NotFound(0))?;
+            invitation.status = InvitationStatus::Declined;
+            Ok(())
+        }
+    }
+
+    mod test {
+        use super::*;
+
+        #[tokio::test]
+        async fn workspace_membership_scenario() {
+            let owner = User::new(1, "auth0|owner".to_string(), Some("owner".to_string()), Some("owner@example.com".to_string()));
+            let member = User {
+                email_verified: true,
+                ..User::new(2, "auth0|member".to_string(), Some("member".to_string()), Some("member@example.com".to_string()))
+            };
+            let outsider = User::new(3, "auth0|outsider".to_string(), Some("outsider".to_string()), Some("outsider@example.com".to_string()));
+            let repository = WorkspaceRepositoryForMemory::new(vec![owner.clone(), member.clone(), outsider.clone()]);
+
+            // create
+            let workspace = repository
+                .create(owner.id, CreateWorkspace::new("team".to_string(), false, vec!["member@example.com".to_string(), "unknown@example.com".to_string()]))
+                .await
+                .expect("failed create workspace");
+            let expected = WorkspaceEntity::new(
+                1,
+                "team".to_string(),
+                false,
+                Some(owner.id),
+                vec![
+                    WorkspaceMember::new(owner.clone(), WorkspaceRole::Owner),
+                    WorkspaceMember::new(member.clone(), WorkspaceRole::Editor),
+                ],
+            );
+            assert_eq!(expected, workspace);
+
+            // is_member
+            assert!(repository.is_member(workspace.id, member.id).await.unwrap());
+            assert!(!repository.is_member(workspace.id, outsider.id).await.unwrap());
+
+            // add_member
+            repository.add_member(workspace.id, owner.id, outsider.id, WorkspaceRole::Viewer).await.expect("failed add member");
+            assert!(repository.add_member(workspace.id, owner.id, outsider.id, WorkspaceRole::Viewer).await.is_err());
+            let workspaces = repository.all_by_user(outsider.id).await.unwrap();
+            assert_eq!(vec![workspace.id], workspaces.iter().map(|ws| ws.id).collect::<Vec<_>>());
+            assert_eq!(Some(WorkspaceRole::Viewer), workspaces[0].role_of(outsider.id));
+
+            // update_role
+            repository.update_role(workspace.id, owner.id, outsider.id, WorkspaceRole::Admin).await.expect("failed update role");
+            assert_eq!(Some(WorkspaceRole::Admin), repository.find_role(workspace.id, outsider.id).await.unwrap());
+
+            // remove_member
+            repository.remove_member(workspace.id, owner.id, outsider.id).await.expect("failed remove member");
+            assert!(!repository.is_member(workspace.id, outsider.id).await.unwrap());
+            assert!(repository.remove_member(workspace.id, owner.id, outsider.id).await.is_err());
+
+            // transfer_ownership
+            let workspace = repository.transfer_ownership(workspace.id, owner.id, member.id).await.unwrap();
+            assert_eq!(Some(member.id), workspace.owner_id);
+            assert_eq!(Some(WorkspaceRole::Owner), workspace.role_of(member.id));
+            assert_eq!(Some(WorkspaceRole::Admin), workspace.role_of(owner.id));
         }
     }
 }
@@ -1 +1,7 @@
-pub mod groq;
+pub mod recommend;
+pub mod trash;
+pub mod calendar;
+pub mod transfer;
+pub mod oidc;
+pub mod llm;
+pub mod quick_add;
\ No newline at end of file
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use crate::models::{calendar::CalendarComponent, todo::TodoEntity};
+
+const PRODUCT_ID: &str = "-//todo-back//todos//EN";
+const UID_DOMAIN: &str = "todo-back";
+// RFC 5545 3.1: content lines are folded after 75 octets
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders todos, subtasks included, as an iCalendar document named `name`.
+/// As events only todos with a due date show up, spanning from their start if they have one.
+pub fn render(name: &str, todos: &[TodoEntity], component: CalendarComponent, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    for todo in todos.iter().flat_map(|todo| std::iter::once(todo).chain(todo.children.iter())) {
+        match component {
+            CalendarComponent::Todo => push_todo(&mut lines, todo, now),
+            CalendarComponent::Event => push_event(&mut lines, todo, now),
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+fn push_todo(lines: &mut Vec<String>, todo: &TodoEntity, now: DateTime<Utc>) {
+    lines.push("BEGIN:VTODO".to_string());
+    push_common(lines, todo, now);
+    if let Some(start_at) = todo.start_at {
+        lines.push(format!("DTSTART:{}", format_time(start_at)));
+    }
+    if let Some(due_at) = todo.due_at {
+        lines.push(format!("DUE:{}", format_time(due_at)));
+    }
+    if todo.completed {
+        lines.push("STATUS:COMPLETED".to_string());
+        lines.push("PERCENT-COMPLETE:100".to_string());
+    } else {
+        lines.push("STATUS:NEEDS-ACTION".to_string());
+    }
+    if let Some(parent_id) = todo.parent_id {
+        lines.push(format!("RELATED-TO:{}", uid(parent_id)));
+    }
+    lines.push("END:VTODO".to_string());
+}
+
+fn push_event(lines: &mut Vec<String>, todo: &TodoEntity, now: DateTime<Utc>) {
+    let Some(due_at) = todo.due_at else {
+        return;
+    };
+    lines.push("BEGIN:VEVENT".to_string());
+    push_common(lines, todo, now);
+    match todo.start_at {
+        Some(start_at) => {
+            lines.push(format!("DTSTART:{}", format_time(start_at)));
+            lines.push(format!("DTEND:{}", format_time(due_at)));
+        }
+        None => lines.push(format!("DTSTART:{}", format_time(due_at))),
+    }
+    // todos are reminders, they shouldn't block time in the calendar
+    lines.push("TRANSP:TRANSPARENT".to_string());
+    lines.push("END:VEVENT".to_string());
+}
+
+fn push_common(lines: &mut Vec<String>, todo: &TodoEntity, now: DateTime<Utc>) {
+    lines.push(format!("UID:{}", uid(todo.id)));
+    lines.push(format!("DTSTAMP:{}", format_time(now)));
+    lines.push(format!("SUMMARY:{}", escape(&todo.text)));
+    if !todo.labels.is_empty() {
+        let categories: Vec<String> = todo.labels.iter().map(|label| escape(&label.name)).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+}
+
+fn uid(todo_id: i32) -> String {
+    format!("todo-{}@{}", todo_id, UID_DOMAIN)
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// 改行はどの形でも\nにし、それ以外の制御文字は内容行を壊さないよう取り除く
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 長い行は文字の途中で切らないように75バイト以内で折り返す
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // the leading space counts towards the continuation line
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
@@ -0,0 +1,173 @@
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+const GROQ_BASE_URL: &str = "https://api.groq.com/openai/v1";
+const GROQ_MODEL: &str = "llama-3.3-70b-versatile";
+const OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
+const OLLAMA_MODEL: &str = "llama3.2";
+// llama.cpp serves whatever model it was started with and ignores the name
+const LLAMA_CPP_BASE_URL: &str = "http://localhost:8080/v1";
+const LLAMA_CPP_MODEL: &str = "default";
+const MOCK_REPLY: &str = r#"["今週の予定を見直す", "未完了のタスクを整理する", "明日の準備をする"]"#;
+
+/// A chat model answering a single prompt.
+#[async_trait]
+pub trait LlmProvider: Send + Sync + 'static {
+    /// Returns the reply to `prompt` as plain text, following the `system` instructions.
+    async fn complete(&self, system: &str, prompt: &str) -> anyhow::Result<String>;
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Option<Vec<ChatChoice>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessageResponse,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessageResponse {
+    content: String,
+}
+
+/// Any server speaking the OpenAI chat completions API: Groq, Ollama, llama.cpp and the like.
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatible {
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OpenAiCompatible {
+    pub fn new(base_url: String, model: String, api_key: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatible {
+    async fn complete(&self, system: &str, prompt: &str) -> anyhow::Result<String> {
+        let request_body = ChatRequest {
+            model: &self.model,
+            messages: vec![
+                ChatMessage { role: "system", content: system },
+                ChatMessage { role: "user", content: prompt },
+            ],
+        };
+
+        let mut request = self.client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&request_body);
+        // local servers usually run without a key
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("LLM API error from [{}]: {} - {}", self.base_url, status, body);
+        }
+
+        let chat_response: ChatResponse = response.json().await?;
+        Ok(chat_response
+            .choices
+            .and_then(|c| c.into_iter().next())
+            .map(|c| c.message.content)
+            .unwrap_or_default())
+    }
+}
+
+/// Answers every prompt with the same reply and remembers the prompts, for tests and offline development.
+#[derive(Debug, Clone)]
+pub struct MockLlm {
+    reply: String,
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl Default for MockLlm {
+    fn default() -> Self {
+        Self::new(MOCK_REPLY.to_string())
+    }
+}
+
+impl MockLlm {
+    pub fn new(reply: String) -> Self {
+        Self {
+            reply,
+            prompts: Arc::default(),
+        }
+    }
+
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockLlm {
+    async fn complete(&self, _system: &str, prompt: &str) -> anyhow::Result<String> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        Ok(self.reply.clone())
+    }
+}
+
+/// Picks the provider named by `LLM_PROVIDER`: `groq` (the default), `ollama`, `llama_cpp`,
+/// `openai` for any other compatible server, or `mock`.
+/// `LLM_BASE_URL`, `LLM_MODEL` and `LLM_API_KEY` override the provider's defaults,
+/// Groq still falls back to `GROQ_API_KEY`.
+pub fn from_env() -> anyhow::Result<Arc<dyn LlmProvider>> {
+    let provider = env::var("LLM_PROVIDER").unwrap_or("groq".to_string());
+    let base_url = env::var("LLM_BASE_URL").ok();
+    let model = env::var("LLM_MODEL").ok();
+    let api_key = env::var("LLM_API_KEY").ok().filter(|key| !key.is_empty());
+
+    let (default_base_url, default_model, api_key) = match provider.as_str() {
+        "mock" => return Ok(Arc::new(MockLlm::default())),
+        "groq" => {
+            let api_key = api_key.or(env::var("GROQ_API_KEY").ok().filter(|key| !key.is_empty()));
+            if api_key.is_none() {
+                tracing::warn!("no [LLM_API_KEY] or [GROQ_API_KEY], recommendations will fail");
+            }
+            (Some(GROQ_BASE_URL), Some(GROQ_MODEL), api_key)
+        }
+        "ollama" => (Some(OLLAMA_BASE_URL), Some(OLLAMA_MODEL), api_key),
+        "llama_cpp" => (Some(LLAMA_CPP_BASE_URL), Some(LLAMA_CPP_MODEL), api_key),
+        "openai" => (None, None, api_key),
+        other => anyhow::bail!("unknown [LLM_PROVIDER] {}", other),
+    };
+
+    let base_url = base_url
+        .or(default_base_url.map(str::to_string))
+        .context("undefined [LLM_BASE_URL]")?;
+    let model = model
+        .or(default_model.map(str::to_string))
+        .context("undefined [LLM_MODEL]")?;
+    Ok(Arc::new(OpenAiCompatible::new(base_url, model, api_key)))
+}
@@ -0,0 +1,300 @@
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+use anyhow::Context;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use crate::errors::AppError;
+
+/// How long fetched signing keys are trusted before they are fetched again.
+pub const DEFAULT_JWKS_TTL: Duration = Duration::from_secs(60 * 60);
+/// Least time between two fetches of an issuer's keys, whether they went stale or a token names a key we don't know.
+pub const DEFAULT_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const SUPPORTED_ALGORITHMS: [Algorithm; 3] = [Algorithm::RS256, Algorithm::RS384, Algorithm::RS512];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    pub iss: String,
+    pub aud: serde_json::Value,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    /// `sub` qualified by the issuer, what users are keyed by since two issuers may hand out the same `sub`.
+    #[serde(skip)]
+    pub subject: String,
+}
+
+/// An issuer whose tokens are accepted when they are meant for `audience`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustedIssuer {
+    pub issuer: String,
+    pub audience: String,
+    /// Subjects are used as they come instead of prefixed with the issuer.
+    pub bare_subjects: bool,
+}
+
+impl TrustedIssuer {
+    pub fn new(issuer: String, audience: String) -> Self {
+        Self {
+            issuer,
+            audience,
+            bare_subjects: false,
+        }
+    }
+
+    /// For the issuer users were created with before several issuers were trusted, so they keep their accounts.
+    pub fn with_bare_subjects(mut self) -> Self {
+        self.bare_subjects = true;
+        self
+    }
+
+    fn subject(&self, sub: &str) -> String {
+        if self.bare_subjects {
+            return sub.to_string();
+        }
+        format!("{}|{}", self.issuer.trim_end_matches('/'), sub)
+    }
+
+    // `https://example.com` and `https://example.com/` name the same issuer
+    fn matches(&self, iss: &str) -> bool {
+        self.issuer.trim_end_matches('/') == iss.trim_end_matches('/')
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwksResponse {
+    keys: Vec<Jwk>,
+}
+
+// keys of other types may be published alongside, they are skipped
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    #[serde(default)]
+    kid: Option<String>,
+    kty: String,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnverifiedClaims {
+    iss: String,
+}
+
+#[derive(Debug, Default)]
+struct KeySet {
+    jwks_uri: Option<String>,
+    keys: Vec<Jwk>,
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+impl KeySet {
+    fn find(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|jwk| jwk.kid.as_deref() == Some(kid))
+    }
+}
+
+#[derive(Debug)]
+struct IssuerKeys {
+    trusted: TrustedIssuer,
+    keys: Mutex<KeySet>,
+}
+
+/// Verifies JWTs of any OIDC issuer it trusts, finding their signing keys through discovery.
+#[derive(Debug)]
+pub struct OidcVerifier {
+    issuers: Vec<IssuerKeys>,
+    client: reqwest::Client,
+    jwks_ttl: Duration,
+    refetch_interval: Duration,
+}
+
+impl Default for OidcVerifier {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl OidcVerifier {
+    pub fn new(issuers: Vec<TrustedIssuer>) -> Self {
+        Self {
+            issuers: issuers
+                .into_iter()
+                .map(|trusted| IssuerKeys { trusted, keys: Mutex::default() })
+                .collect(),
+            client: reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            jwks_ttl: DEFAULT_JWKS_TTL,
+            refetch_interval: DEFAULT_REFETCH_INTERVAL,
+        }
+    }
+
+    pub fn jwks_ttl(mut self, jwks_ttl: Duration) -> Self {
+        self.jwks_ttl = jwks_ttl;
+        self
+    }
+
+    pub fn refetch_interval(mut self, refetch_interval: Duration) -> Self {
+        self.refetch_interval = refetch_interval;
+        self
+    }
+
+    /// Reads the trusted issuers from `OIDC_ISSUERS`, a comma separated list of issuer URLs,
+    /// each optionally followed by `|audience` to override `OIDC_AUDIENCE`.
+    /// `AUTH0_DOMAIN` and `AUTH0_AUDIENCE` are still honoured as one more issuer, the one keeping bare subjects.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let default_audience = env::var("OIDC_AUDIENCE").ok();
+        let mut issuers = vec![];
+        for entry in env::var("OIDC_ISSUERS").unwrap_or_default().split(',').map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+            let (issuer, audience) = match entry.split_once('|') {
+                Some((issuer, audience)) => (issuer.trim(), Some(audience.trim().to_string())),
+                None => (entry, default_audience.clone()),
+            };
+            let audience = audience.with_context(|| format!("no audience for issuer [{}], set OIDC_AUDIENCE", issuer))?;
+            issuers.push(TrustedIssuer::new(issuer.to_string(), audience));
+        }
+        if let Ok(domain) = env::var("AUTH0_DOMAIN") {
+            let audience = env::var("AUTH0_AUDIENCE").context("undefined [AUTH0_AUDIENCE]")?;
+            issuers.push(TrustedIssuer::new(format!("https://{}/", domain), audience).with_bare_subjects());
+        }
+
+        let mut verifier = Self::new(issuers);
+        if let Ok(seconds) = env::var("JWKS_TTL_SECONDS") {
+            verifier.jwks_ttl = Duration::from_secs(seconds.parse().context("JWKS_TTL_SECONDS must be a number")?);
+        }
+        Ok(verifier)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issuers.is_empty()
+    }
+
+    /// Checks the signature, issuer, audience and expiry of `token`.
+    /// Only failing to reach a trusted issuer is an internal error, anything wrong with the token is 401.
+    pub async fn verify(&self, token: &str) -> Result<Claims, AppError> {
+        let header = decode_header(token).map_err(|_| AppError::Unauthorized)?;
+        if !SUPPORTED_ALGORITHMS.contains(&header.alg) {
+            return Err(AppError::Unauthorized);
+        }
+        let kid = header.kid.ok_or(AppError::Unauthorized)?;
+
+        // the issuer decides which keys to check the signature with, so it is read before verifying
+        let iss = unverified_issuer(token).ok_or(AppError::Unauthorized)?;
+        let issuer = self.issuers
+            .iter()
+            .find(|issuer| issuer.trusted.matches(&iss))
+            .ok_or(AppError::Unauthorized)?;
+        let decoding_key = self.decoding_key(issuer, &kid).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&issuer.trusted.audience]);
+        validation.set_issuer(&[&iss]);
+        let mut claims = decode::<Claims>(token, &decoding_key, &validation)
+            .map_err(|_| AppError::Unauthorized)?
+            .claims;
+        claims.subject = issuer.trusted.subject(&claims.sub);
+
+        Ok(claims)
+    }
+
+    async fn decoding_key(&self, issuer: &IssuerKeys, kid: &str) -> Result<DecodingKey, AppError> {
+        let mut keys = issuer.keys.lock().await;
+        let now = Instant::now();
+        let expired = keys.fetched_at.is_none_or(|fetched_at| now.duration_since(fetched_at) >= self.jwks_ttl);
+        // a key we don't know is either rotated in or made up, only the former is worth asking again
+        let unknown = keys.find(kid).is_none();
+        // neither made up kids nor an unreachable issuer get to cause a fetch on every request
+        let due = keys.attempted_at.is_none_or(|attempted_at| now.duration_since(attempted_at) >= self.refetch_interval);
+
+        if (expired || unknown) && due {
+            keys.attempted_at = Some(now);
+            let jwks_uri = keys.jwks_uri.clone();
+            // requests carry on with the keys we hold while a fetch is under way, only the first fetch is waited for
+            let fetched = if keys.keys.is_empty() {
+                self.fetch_keys(&issuer.trusted, jwks_uri).await
+            } else {
+                drop(keys);
+                let fetched = self.fetch_keys(&issuer.trusted, jwks_uri).await;
+                keys = issuer.keys.lock().await;
+                fetched
+            };
+            match fetched {
+                Ok((jwks_uri, fetched)) => {
+                    keys.jwks_uri = Some(jwks_uri);
+                    keys.keys = fetched;
+                    keys.fetched_at = Some(now);
+                }
+                // keys we still hold keep working while the issuer is unreachable
+                Err(e) if !keys.keys.is_empty() => {
+                    tracing::warn!("failed to refresh keys of [{}]: {:?}", issuer.trusted.issuer, e);
+                }
+                Err(e) => return Err(AppError::Internal(e)),
+            }
+        }
+
+        let jwk = keys.find(kid).ok_or(AppError::Unauthorized)?;
+        let (Some(n), Some(e)) = (&jwk.n, &jwk.e) else {
+            return Err(AppError::Unauthorized);
+        };
+        Ok(DecodingKey::from_rsa_components(n, e).map_err(anyhow::Error::from)?)
+    }
+
+    // the JWKS location is discovered once and kept, only the keys themselves rotate
+    async fn fetch_keys(&self, issuer: &TrustedIssuer, jwks_uri: Option<String>) -> anyhow::Result<(String, Vec<Jwk>)> {
+        let jwks_uri = match jwks_uri {
+            Some(jwks_uri) => jwks_uri,
+            None => {
+                let url = format!("{}/.well-known/openid-configuration", issuer.issuer.trim_end_matches('/'));
+                let document: DiscoveryDocument = self.client
+                    .get(&url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                if !issuer.matches(&document.issuer) {
+                    anyhow::bail!("[{}] describes issuer [{}]", url, document.issuer);
+                }
+                document.jwks_uri
+            }
+        };
+
+        let jwks: JwksResponse = self.client
+            .get(&jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let keys = jwks.keys.into_iter().filter(|jwk| jwk.kty == "RSA").collect();
+        Ok((jwks_uri, keys))
+    }
+}
+
+fn unverified_issuer(token: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload).ok()?;
+    let claims: UnverifiedClaims = serde_json::from_slice(&bytes).ok()?;
+    Some(claims.iss)
+}
@@ -0,0 +1,460 @@
+use std::sync::LazyLock;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use regex::{Captures, Regex};
+use crate::models::todo::{Frequency, Priority, Recurrence};
+
+// placeholders shared by the patterns below
+const PATTERN_PARTS: [(&str, &str); 6] = [
+    ("%DATE_PREFIX%", r"(?:\b(?:on|by|due)\s+)?"),
+    ("%TIME_PREFIX%", r"(?:\bat\s+|@\s*)?"),
+    // Japanese dates and times are glued to the text by a particle, which goes with them
+    ("%PARTICLE%", "(?:までに|まで|中に|に|の)?"),
+    ("%WEEKDAY%", "monday|tuesday|wednesday|thursday|friday|saturday|sunday"),
+    ("%WEEKDAY_SHORT%", r"mon|tues?|wed|thu(?:rs?)?|fri|sat|sun"),
+    ("%MONTH%", r"jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?"),
+];
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const MAX_INTERVAL: u32 = 365;
+
+type DateRule = fn(&Captures, NaiveDate) -> Option<(NaiveDate, Option<NaiveTime>)>;
+type TimeRule = fn(&Captures) -> Option<NaiveTime>;
+type RecurrenceRule = fn(&Captures) -> Option<Recurrence>;
+type PriorityRule = fn(&Captures) -> Option<Priority>;
+
+/// What the quick-add syntax in a todo's text says, `text` is what is left once it is cut out.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParsedTodo {
+    pub text: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub labels: Vec<String>,
+    pub priority: Option<Priority>,
+    pub recurrence: Option<Recurrence>,
+}
+
+static LABEL: LazyLock<Regex> = LazyLock::new(|| re(r"(?:^|\s)(?P<m>#(?P<name>[^\s#!,、。]+))"));
+
+static PRIORITY_RULES: LazyLock<Vec<(Regex, PriorityRule)>> = LazyLock::new(|| vec![
+    (re(r"(?i)(?:^|\s)(?P<m>!(high|medium|med|low|高|中|低|[123]))\b"), |caps| priority(&caps[2])),
+    (re(r"優先度[:\s]*(高|中|低)"), |caps| priority(&caps[1])),
+]);
+
+static RECURRENCE_RULES: LazyLock<Vec<(Regex, RecurrenceRule)>> = LazyLock::new(|| vec![
+    (re(r"(?i)\bevery\s+(?:(other)\s+|(\d{1,3})\s+)?(day|week|month)s?\b"), |caps| {
+        let interval = match (caps.get(1), caps.get(2)) {
+            (Some(_), _) => 2,
+            (None, Some(n)) => n.as_str().parse().ok()?,
+            (None, None) => 1,
+        };
+        every(frequency(&caps[3])?, interval)
+    }),
+    (re(r"(?i)\bevery\s+weekday\b"), |_| weekly_on(vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri])),
+    (re(r"(?i)\bevery\s+((?:%WEEKDAY%|%WEEKDAY_SHORT%)(?:\s*(?:,|and|&)\s*(?:%WEEKDAY%|%WEEKDAY_SHORT%))*)\b\.?"), |caps| {
+        weekly_on(caps[1].split(|c: char| !c.is_ascii_alphabetic()).filter_map(english_weekday).collect())
+    }),
+    (re(r"(?i)\bevery\s+(\d{1,2})(?:st|nd|rd|th)(?:\s+of\s+the\s+month)?\b"), |caps| monthly_on(caps[1].parse().ok()?)),
+    (re(r"(?i)\b(daily|weekly|monthly)\b"), |caps| every(frequency(&caps[1])?, 1)),
+    (re("毎日%PARTICLE%"), |_| every(Frequency::Daily, 1)),
+    (re("毎週((?:[月火水木金土日]曜?日?[・、と,]\\s*)*[月火水木金土日]曜日?)%PARTICLE%"), |caps| {
+        weekly_on(caps[1].split(['・', '、', 'と', ',']).filter_map(|day| japanese_weekday(day.trim().chars().next()?)).collect())
+    }),
+    (re("隔週%PARTICLE%"), |_| every(Frequency::Weekly, 2)),
+    (re("毎週%PARTICLE%"), |_| every(Frequency::Weekly, 1)),
+    (re(r"毎月(\d{1,2})日%PARTICLE%"), |caps| monthly_on(caps[1].parse().ok()?)),
+    (re("毎月%PARTICLE%"), |_| every(Frequency::Monthly, 1)),
+    (re(r"(\d{1,3})(日|週間|[かヶケカ]月)(?:ごと|毎)に?"), |caps| every(frequency(&caps[2])?, caps[1].parse().ok()?)),
+]);
+
+static DATE_RULES: LazyLock<Vec<(Regex, DateRule)>> = LazyLock::new(|| vec![
+    (re(r"(?i)%DATE_PREFIX%\b(?:the\s+)?day\s+after\s+tomorrow\b"), |_, today| Some((today + Duration::days(2), None))),
+    (re(r"(?i)%DATE_PREFIX%\b(today|tonight|tomorrow|tmrw?)\b"), |caps, today| match caps[1].to_lowercase().as_str() {
+        "today" => Some((today, None)),
+        "tonight" => Some((today, NaiveTime::from_hms_opt(20, 0, 0))),
+        _ => Some((today + Duration::days(1), None)),
+    }),
+    (re(r"(?i)%DATE_PREFIX%\b(this\s+|next\s+)?(%WEEKDAY%)\b"), |caps, today| {
+        Some((weekday_date(today, caps.get(1).map(|m| m.as_str()), english_weekday(&caps[2])?), None))
+    }),
+    // "sat" or "wed" are words as well, short names need something in front saying they are days
+    (re(r"(?i)\b(?:(?:on|by|due)\s+(this\s+|next\s+)?|(this\s+|next\s+))(%WEEKDAY_SHORT%)\b\.?"), |caps, today| {
+        let modifier = caps.get(1).or(caps.get(2)).map(|m| m.as_str());
+        Some((weekday_date(today, modifier, english_weekday(&caps[3])?), None))
+    }),
+    (re(r"(?i)%DATE_PREFIX%\bin\s+(\d{1,3})\s+(day|week|month)s?\b"), |caps, today| {
+        Some((after(today, frequency(&caps[2])?, caps[1].parse().ok()?)?, None))
+    }),
+    (re(r"(?i)%DATE_PREFIX%\bnext\s+(week|month)\b"), |caps, today| match caps[1].to_lowercase().as_str() {
+        "week" => Some((week_day(today, 1, Weekday::Mon), None)),
+        _ => Some((today.with_day(1)?.checked_add_months(Months::new(1))?, None)),
+    }),
+    (re(r"(?i)%DATE_PREFIX%\b(?:this\s+)?weekend\b"), |_, today| Some((on_or_after(today, Weekday::Sat), None))),
+    (re(r"%DATE_PREFIX%\b(\d{4})-(\d{1,2})-(\d{1,2})\b"), |caps, _| {
+        Some((NaiveDate::from_ymd_opt(caps[1].parse().ok()?, caps[2].parse().ok()?, caps[3].parse().ok()?)?, None))
+    }),
+    (re(r"(?i)%DATE_PREFIX%\b(%MONTH%)\.?\s+(\d{1,2})(?:st|nd|rd|th)?\b(?:,?\s+(\d{4})\b)?"), |caps, today| {
+        Some((month_day(today, caps.get(3), month(&caps[1])?, caps[2].parse().ok()?)?, None))
+    }),
+    (re(r"(?i)%DATE_PREFIX%\b(\d{1,2})(?:st|nd|rd|th)?\s+(%MONTH%)\b\.?(?:\s+(\d{4})\b)?"), |caps, today| {
+        Some((month_day(today, caps.get(3), month(&caps[2])?, caps[1].parse().ok()?)?, None))
+    }),
+    (re(r"(?i)(?:^|[^\d/:])(?P<m>%DATE_PREFIX%(?P<month>\d{1,2})/(?P<day>\d{1,2})%PARTICLE%)(?:[^\d/:]|$)"), |caps, today| {
+        Some((month_day(today, None, caps["month"].parse().ok()?, caps["day"].parse().ok()?)?, None))
+    }),
+    (re(r"(\d{4})年(\d{1,2})月(\d{1,2})日%PARTICLE%"), |caps, today| {
+        Some((month_day(today, caps.get(1), caps[2].parse().ok()?, caps[3].parse().ok()?)?, None))
+    }),
+    (re(r"(\d{1,2})月(\d{1,2})日%PARTICLE%"), |caps, today| {
+        Some((month_day(today, None, caps[1].parse().ok()?, caps[2].parse().ok()?)?, None))
+    }),
+    (re("(明後日|あさって|明日|あした|今日|本日|今夜)%PARTICLE%"), |caps, today| match &caps[1] {
+        "明後日" | "あさって" => Some((today + Duration::days(2), None)),
+        "明日" | "あした" => Some((today + Duration::days(1), None)),
+        "今夜" => Some((today, NaiveTime::from_hms_opt(20, 0, 0))),
+        _ => Some((today, None)),
+    }),
+    (re(r"(\d{1,3})(日|週間|[かヶケカ]月)後%PARTICLE%"), |caps, today| {
+        Some((after(today, frequency(&caps[2])?, caps[1].parse().ok()?)?, None))
+    }),
+    (re("(?:(今週|来週|再来週)の?)?([月火水木金土日])曜日?%PARTICLE%"), |caps, today| {
+        let weekday = japanese_weekday(caps[2].chars().next()?)?;
+        let date = match caps.get(1).map(|m| m.as_str()) {
+            Some("来週") => week_day(today, 1, weekday),
+            Some("再来週") => week_day(today, 2, weekday),
+            _ => on_or_after(today, weekday),
+        };
+        Some((date, None))
+    }),
+    (re("(来週|再来週|来月|今?週末)%PARTICLE%"), |caps, today| match &caps[1] {
+        "来週" => Some((week_day(today, 1, Weekday::Mon), None)),
+        "再来週" => Some((week_day(today, 2, Weekday::Mon), None)),
+        "来月" => Some((today.with_day(1)?.checked_add_months(Months::new(1))?, None)),
+        _ => Some((on_or_after(today, Weekday::Sat), None)),
+    }),
+]);
+
+static TIME_RULES: LazyLock<Vec<(Regex, TimeRule)>> = LazyLock::new(|| vec![
+    (re(r"(?i)%TIME_PREFIX%\b(\d{1,2})(?::(\d{2}))?\s*([ap])\.?m\b\.?"), |caps| {
+        let hour: u32 = caps[1].parse().ok()?;
+        let minute = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        let pm = caps[3].eq_ignore_ascii_case("p");
+        NaiveTime::from_hms_opt(hour % 12 + if pm { 12 } else { 0 }, minute, 0)
+    }),
+    (re(r"(?i)(?:^|[^\d/:])(?P<m>%TIME_PREFIX%(?P<hour>\d{1,2}):(?P<minute>\d{2})%PARTICLE%)(?:[^\d:]|$)"), |caps| {
+        NaiveTime::from_hms_opt(caps["hour"].parse().ok()?, caps["minute"].parse().ok()?, 0)
+    }),
+    (re(r"(?i)%TIME_PREFIX%\bnoon\b|正午%PARTICLE%"), |_| NaiveTime::from_hms_opt(12, 0, 0)),
+    (re(r"(午前|午後|朝|夜)?(\d{1,2})時(?:(\d{1,2})分|(半))?%PARTICLE%"), |caps| {
+        let mut hour: u32 = caps[2].parse().ok()?;
+        if matches!(caps.get(1).map(|m| m.as_str()), Some("午後" | "夜")) && hour < 12 {
+            hour += 12;
+        }
+        let minute = match (caps.get(3), caps.get(4)) {
+            (Some(minute), _) => minute.as_str().parse().ok()?,
+            (None, Some(_)) => 30,
+            (None, None) => 0,
+        };
+        NaiveTime::from_hms_opt(hour, minute, 0)
+    }),
+]);
+
+/// Reads a due date and time, `#labels`, a `!priority` and a recurrence out of `input`,
+/// in English ("Pay rent tomorrow 9am #finance !high") or Japanese ("毎週月曜9時に燃えるゴミを出す").
+/// Dates and times are taken in the UTC offset of `now`, a date without a time is due by the end of the day.
+pub fn parse(input: &str, now: DateTime<FixedOffset>) -> ParsedTodo {
+    let mut rest = normalize(input);
+    let today = now.date_naive();
+
+    let mut labels: Vec<String> = vec![];
+    while let Some(name) = take(&mut rest, &LABEL, |caps| Some(caps["name"].to_string())) {
+        if !labels.iter().any(|label| label.to_lowercase() == name.to_lowercase()) {
+            labels.push(name);
+        }
+    }
+    let priority = PRIORITY_RULES.iter().find_map(|(re, rule)| take(&mut rest, re, rule));
+    let recurrence = RECURRENCE_RULES
+        .iter()
+        .find_map(|(re, rule)| take(&mut rest, re, rule))
+        .map(|recurrence| Recurrence { tz_offset: now.offset().local_minus_utc() / 60, ..recurrence });
+    let date = DATE_RULES.iter().find_map(|(re, rule)| take(&mut rest, re, |caps| rule(caps, today)));
+    let time = TIME_RULES.iter().find_map(|(re, rule)| take(&mut rest, re, rule));
+
+    let end_of_day = NaiveTime::from_hms_opt(23, 59, 0).unwrap();
+    let due_at = match (date, time, &recurrence) {
+        (Some((date, default_time)), time, _) => local(now, date, time.or(default_time).unwrap_or(end_of_day)),
+        (None, time, Some(recurrence)) => first_occurrence(recurrence, now, time.unwrap_or(end_of_day)),
+        // a time on its own is the next time the clock shows it
+        (None, Some(time), None) => local(now, today, time)
+            .map(|at| if at < now { at + Duration::days(1) } else { at }),
+        (None, None, None) => None,
+    };
+
+    let text = rest
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c == ',' || c == '、' || c.is_whitespace())
+        .to_string();
+    ParsedTodo {
+        // nothing but syntax, the text stays as it was typed
+        text: if text.is_empty() { input.trim().to_string() } else { text },
+        due_at,
+        labels,
+        priority,
+        recurrence,
+    }
+}
+
+fn re(pattern: &str) -> Regex {
+    let pattern = PATTERN_PARTS
+        .iter()
+        .fold(pattern.to_string(), |pattern, (name, part)| pattern.replace(name, part));
+    Regex::new(&pattern).unwrap()
+}
+
+// full width digits and symbols are common in Japanese input
+fn normalize(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_digit(c as u32 - '０' as u32, 10).unwrap(),
+            '＃' => '#',
+            '！' => '!',
+            '：' => ':',
+            '／' => '/',
+            '　' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+// Cuts the first match `read` makes sense of out of `text`, the `m` group when the pattern has one.
+fn take<T>(text: &mut String, re: &Regex, read: impl Fn(&Captures) -> Option<T>) -> Option<T> {
+    let (range, value) = re.captures_iter(text).find_map(|caps| {
+        let matched = caps.name("m").or(caps.get(0))?;
+        read(&caps).map(|value| (matched.range(), value))
+    })?;
+    text.replace_range(range, " ");
+    Some(value)
+}
+
+fn local(now: DateTime<FixedOffset>, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    now.offset()
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+// a rule given without a date starts with its first occurrence from today on
+fn first_occurrence(recurrence: &Recurrence, now: DateTime<FixedOffset>, time: NaiveTime) -> Option<DateTime<Utc>> {
+    let today = now.date_naive();
+    let date = match (recurrence.frequency, recurrence.month_day) {
+        (Frequency::Weekly, _) if !recurrence.weekdays.is_empty() => (0..7)
+            .map(|days| today + Duration::days(days))
+            .find(|date| recurrence.weekdays.contains(&date.weekday()))?,
+        // shorter months use their last day
+        (Frequency::Monthly, Some(day)) => (1..=day).rev().find_map(|day| today.with_day(day))?,
+        _ => today,
+    };
+    let at = local(now, date, time)?;
+    if at < now { recurrence.next_after(at) } else { Some(at) }
+}
+
+fn every(frequency: Frequency, interval: u32) -> Option<Recurrence> {
+    (1..=MAX_INTERVAL).contains(&interval).then_some(Recurrence {
+        frequency,
+        interval,
+        weekdays: vec![],
+        month_day: None,
+        until: None,
+        tz_offset: 0,
+    })
+}
+
+fn weekly_on(mut weekdays: Vec<Weekday>) -> Option<Recurrence> {
+    weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+    weekdays.dedup();
+    if weekdays.is_empty() {
+        return None;
+    }
+    Some(Recurrence { weekdays, ..every(Frequency::Weekly, 1)? })
+}
+
+fn monthly_on(day: u32) -> Option<Recurrence> {
+    (1..=31).contains(&day).then_some(Recurrence { month_day: Some(day), ..every(Frequency::Monthly, 1)? })
+}
+
+fn frequency(unit: &str) -> Option<Frequency> {
+    match unit.to_lowercase().as_str() {
+        "day" | "daily" | "日" => Some(Frequency::Daily),
+        "week" | "weekly" | "週間" => Some(Frequency::Weekly),
+        "month" | "monthly" => Some(Frequency::Monthly),
+        unit if unit.ends_with('月') => Some(Frequency::Monthly),
+        _ => None,
+    }
+}
+
+fn priority(level: &str) -> Option<Priority> {
+    match level.to_lowercase().as_str() {
+        "high" | "高" | "1" => Some(Priority::High),
+        "medium" | "med" | "中" | "2" => Some(Priority::Medium),
+        "low" | "低" | "3" => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+fn english_weekday(name: &str) -> Option<Weekday> {
+    name.get(..3)?.parse().ok()
+}
+
+fn japanese_weekday(c: char) -> Option<Weekday> {
+    match c {
+        '月' => Some(Weekday::Mon),
+        '火' => Some(Weekday::Tue),
+        '水' => Some(Weekday::Wed),
+        '木' => Some(Weekday::Thu),
+        '金' => Some(Weekday::Fri),
+        '土' => Some(Weekday::Sat),
+        '日' => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn month(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    MONTHS.iter().position(|month| name.starts_with(month)).map(|index| index as u32 + 1)
+}
+
+fn on_or_after(from: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - from.weekday().num_days_from_monday()) % 7;
+    from + Duration::days(days as i64)
+}
+
+// `weekday` of the week `weeks` after this one, weeks start on Monday
+fn week_day(today: NaiveDate, weeks: i64, weekday: Weekday) -> NaiveDate {
+    today - Duration::days(today.weekday().num_days_from_monday() as i64)
+        + Duration::weeks(weeks)
+        + Duration::days(weekday.num_days_from_monday() as i64)
+}
+
+// "friday" is the coming one, "next friday" the one of next week
+fn weekday_date(today: NaiveDate, modifier: Option<&str>, weekday: Weekday) -> NaiveDate {
+    match modifier.map(|modifier| modifier.trim().to_lowercase()) {
+        Some(modifier) if modifier == "next" => week_day(today, 1, weekday),
+        _ => on_or_after(today, weekday),
+    }
+}
+
+fn after(today: NaiveDate, unit: Frequency, count: u32) -> Option<NaiveDate> {
+    match unit {
+        Frequency::Daily => Some(today + Duration::days(count as i64)),
+        Frequency::Weekly => Some(today + Duration::weeks(count as i64)),
+        Frequency::Monthly => today.checked_add_months(Months::new(count)),
+    }
+}
+
+// without a year, the next time that day comes around
+fn month_day(today: NaiveDate, year: Option<regex::Match>, month: u32, day: u32) -> Option<NaiveDate> {
+    if let Some(year) = year {
+        return NaiveDate::from_ymd_opt(year.as_str().parse().ok()?, month, day);
+    }
+    let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if date < today { NaiveDate::from_ymd_opt(today.year() + 1, month, day) } else { Some(date) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a Wednesday afternoon in Tokyo
+    fn now() -> DateTime<FixedOffset> {
+        "2026-06-03T14:00:00+09:00".parse().unwrap()
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn should_read_dates_and_times() {
+        for (input, text, due_at) in [
+            ("Pay rent tomorrow 9am #finance !high", "Pay rent", Some("2026-06-04T09:00:00+09:00")),
+            ("dentist on friday at 4:30pm", "dentist", Some("2026-06-05T16:30:00+09:00")),
+            ("dentist next friday", "dentist", Some("2026-06-12T23:59:00+09:00")),
+            ("report in 3 days", "report", Some("2026-06-06T23:59:00+09:00")),
+            ("trip jul 4th 2027", "trip", Some("2027-07-04T23:59:00+09:00")),
+            // slashes are a date, colons a time, a date that has passed this year is next year's
+            ("dentist 3/4", "dentist", Some("2027-03-04T23:59:00+09:00")),
+            ("dentist 12:30", "dentist", Some("2026-06-04T12:30:00+09:00")),
+            ("dentist 3/4 12:30", "dentist", Some("2027-03-04T12:30:00+09:00")),
+            // a time on its own is today's while it is still ahead
+            ("standup 15:00", "standup", Some("2026-06-03T15:00:00+09:00")),
+            ("standup 9am", "standup", Some("2026-06-04T09:00:00+09:00")),
+            // short weekday names are words too without "on" or "next" in front
+            ("call mom on sat", "call mom", Some("2026-06-06T23:59:00+09:00")),
+            ("call mom sat", "call mom sat", None),
+            ("3日後に書類を出す", "書類を出す", Some("2026-06-06T23:59:00+09:00")),
+            ("来週の金曜までにレポート", "レポート", Some("2026-06-12T23:59:00+09:00")),
+            ("金曜にレポート", "レポート", Some("2026-06-05T23:59:00+09:00")),
+            ("明日の午後3時半に会議", "会議", Some("2026-06-04T15:30:00+09:00")),
+            ("１２月２５日にプレゼント", "プレゼント", Some("2026-12-25T23:59:00+09:00")),
+            ("buy milk", "buy milk", None),
+        ] {
+            let parsed = parse(input, now());
+            assert_eq!((text, due_at.map(at)), (parsed.text.as_str(), parsed.due_at), "{}", input);
+        }
+    }
+
+    #[test]
+    fn should_read_recurrences() {
+        for (input, text, frequency, interval, weekdays, month_day, due_at) in [
+            ("毎週月曜9時に燃えるゴミを出す", "燃えるゴミを出す", Frequency::Weekly, 1, vec![Weekday::Mon], None, "2026-06-08T09:00:00+09:00"),
+            ("毎週月・木曜に資源ゴミ", "資源ゴミ", Frequency::Weekly, 1, vec![Weekday::Mon, Weekday::Thu], None, "2026-06-04T23:59:00+09:00"),
+            ("water plants every other day", "water plants", Frequency::Daily, 2, vec![], None, "2026-06-03T23:59:00+09:00"),
+            ("gym every mon, wed and fri 7am", "gym", Frequency::Weekly, 1, vec![Weekday::Mon, Weekday::Wed, Weekday::Fri], None, "2026-06-05T07:00:00+09:00"),
+            ("pay rent every 25th", "pay rent", Frequency::Monthly, 1, vec![], Some(25), "2026-06-25T23:59:00+09:00"),
+            ("毎月1日に家賃", "家賃", Frequency::Monthly, 1, vec![], Some(1), "2026-07-01T23:59:00+09:00"),
+            // a date given along with the rule is where it starts
+            ("review next monday every 2 weeks", "review", Frequency::Weekly, 2, vec![], None, "2026-06-08T23:59:00+09:00"),
+        ] {
+            let parsed = parse(input, now());
+            let recurrence = parsed.recurrence.expect(input);
+            assert_eq!(
+                (text, frequency, interval, weekdays, month_day, 540),
+                (
+                    parsed.text.as_str(),
+                    recurrence.frequency,
+                    recurrence.interval,
+                    recurrence.weekdays,
+                    recurrence.month_day,
+                    recurrence.tz_offset,
+                ),
+                "{}",
+                input
+            );
+            assert_eq!(Some(at(due_at)), parsed.due_at, "{}", input);
+        }
+    }
+
+    #[test]
+    fn should_read_labels_and_priority() {
+        for (input, text, labels, priority) in [
+            ("Pay rent tomorrow 9am #finance !high", "Pay rent", vec!["finance"], Some(Priority::High)),
+            ("#home fix the sink #Home #diy !2", "fix the sink", vec!["home", "diy"], Some(Priority::Medium)),
+            ("優先度:低 ＃買い物 牛乳", "牛乳", vec!["買い物"], Some(Priority::Low)),
+            // a label ends where a priority starts
+            ("issue #12!low", "issue", vec!["12"], Some(Priority::Low)),
+            // nothing but syntax, the text stays as typed
+            ("#finance", "#finance", vec!["finance"], None),
+        ] {
+            let parsed = parse(input, now());
+            assert_eq!(
+                (text, labels.iter().map(|label| label.to_string()).collect::<Vec<_>>(), priority),
+                (parsed.text.as_str(), parsed.labels, parsed.priority),
+                "{}",
+                input
+            );
+        }
+    }
+}
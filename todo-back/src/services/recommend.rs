@@ -0,0 +1,68 @@
+use super::llm::LlmProvider;
+
+const SYSTEM_PROMPT: &str = "あなたはタスク管理のアシスタントです。JSON配列のみで回答してください。";
+
+pub async fn recommend_todos(
+    llm: &dyn LlmProvider,
+    existing_todos: &[String],
+    rejected_todos: &[String],
+) -> anyhow::Result<Vec<String>> {
+    let todo_list = if existing_todos.is_empty() {
+        "（まだタスクがありません）".to_string()
+    } else {
+        existing_todos
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!("{}. {}", i + 1, t))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    // suggestions the user already turned down are listed so the model doesn't repeat them
+    let rejected_list = if rejected_todos.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "以下のタスクは以前に提案して不要とされたものです。同じものや似たものは提案しないでください:\n\n{}\n\n",
+            rejected_todos
+                .iter()
+                .map(|t| format!("- {}", t))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    };
+
+    let prompt = format!(
+        "以下はユーザーの既存のTodoリストです:\n\n\
+         {}\n\n\
+         {}\
+         上記のタスクを踏まえて、ユーザーに役立ちそうな新しいタスクを3つ提案してください。\n\
+         各タスクは簡潔に1行で記述してください。\n\
+         回答は以下のJSON配列形式のみで返してください。説明文は不要です:\n\
+         [\"タスク1\", \"タスク2\", \"タスク3\"]",
+        todo_list,
+        rejected_list
+    );
+
+    let text = llm.complete(SYSTEM_PROMPT, &prompt).await?;
+
+    // Extract JSON array from response (may be wrapped in ```json ... ```)
+    let json_str = text
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+
+    let recommendations: Vec<String> = serde_json::from_str(json_str)
+        .unwrap_or_else(|_| {
+            // Fallback: split by newlines if JSON parsing fails
+            text.lines()
+                .map(|l| l.trim().trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ' ').to_string())
+                .filter(|l| !l.is_empty())
+                .take(3)
+                .collect()
+        });
+
+    Ok(recommendations)
+}
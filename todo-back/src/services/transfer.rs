@@ -0,0 +1,297 @@
+use std::{collections::HashMap, iter::once};
+use anyhow::{bail, Context};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::{
+    errors::FieldError,
+    models::{
+        todo::TodoEntity,
+        transfer::{TodoRecord, TransferFormat},
+    },
+};
+
+const CSV_HEADERS: [&str; 7] = ["id", "parent_id", "text", "completed", "labels", "due_at", "start_at"];
+const CSV_LABEL_SEPARATOR: &str = ";";
+
+// subtasks point at their parent through the ids of the exported rows
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    #[serde(default)]
+    id: Option<i32>,
+    #[serde(default)]
+    parent_id: Option<i32>,
+    text: String,
+    #[serde(default)]
+    completed: Option<bool>,
+    #[serde(default)]
+    labels: String,
+    #[serde(default)]
+    due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    start_at: Option<DateTime<Utc>>,
+}
+
+impl From<&TodoEntity> for CsvRow {
+    fn from(todo: &TodoEntity) -> Self {
+        Self {
+            id: Some(todo.id),
+            parent_id: todo.parent_id,
+            text: todo.text.clone(),
+            completed: Some(todo.completed),
+            labels: todo.labels
+                .iter()
+                .map(|label| label.name.as_str())
+                .collect::<Vec<_>>()
+                .join(CSV_LABEL_SEPARATOR),
+            due_at: todo.due_at,
+            start_at: todo.start_at,
+        }
+    }
+}
+
+impl From<CsvRow> for TodoRecord {
+    fn from(row: CsvRow) -> Self {
+        Self {
+            text: row.text,
+            completed: row.completed.unwrap_or_default(),
+            labels: row.labels
+                .split(CSV_LABEL_SEPARATOR)
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+            due_at: row.due_at,
+            start_at: row.start_at,
+            subtasks: vec![],
+        }
+    }
+}
+
+fn csv_writer() -> csv::Writer<Vec<u8>> {
+    csv::WriterBuilder::new().has_headers(false).from_writer(vec![])
+}
+
+fn finish_csv(writer: csv::Writer<Vec<u8>>) -> anyhow::Result<String> {
+    let bytes = writer.into_inner().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// What an export starts with, before the first page.
+pub fn header(format: TransferFormat, name: &str) -> anyhow::Result<String> {
+    match format {
+        TransferFormat::Json => Ok("[".to_string()),
+        TransferFormat::Csv => {
+            let mut writer = csv_writer();
+            writer.write_record(CSV_HEADERS)?;
+            finish_csv(writer)
+        }
+        TransferFormat::Markdown => Ok(format!("# {}\n\n", name)),
+    }
+}
+
+/// What an export ends with, after the last page.
+pub fn footer(format: TransferFormat) -> String {
+    match format {
+        TransferFormat::Json => "\n]\n".to_string(),
+        TransferFormat::Csv | TransferFormat::Markdown => String::new(),
+    }
+}
+
+/// Encodes a page of top level todos along with their subtasks.
+/// `first` tells whether anything has been written since the header.
+pub fn page(format: TransferFormat, todos: &[TodoEntity], first: bool) -> anyhow::Result<String> {
+    match format {
+        TransferFormat::Json => {
+            let mut chunk = String::new();
+            for (i, todo) in todos.iter().enumerate() {
+                chunk.push_str(if first && i == 0 { "\n" } else { ",\n" });
+                chunk.push_str(&serde_json::to_string(&TodoRecord::from(todo))?);
+            }
+            Ok(chunk)
+        }
+        TransferFormat::Csv => {
+            let mut writer = csv_writer();
+            for todo in todos.iter().flat_map(|todo| once(todo).chain(todo.children.iter())) {
+                writer.serialize(CsvRow::from(todo))?;
+            }
+            finish_csv(writer)
+        }
+        TransferFormat::Markdown => {
+            let mut chunk = String::new();
+            for todo in todos {
+                chunk.push_str(&markdown_item(todo, 0));
+                for child in &todo.children {
+                    chunk.push_str(&markdown_item(child, 1));
+                }
+            }
+            Ok(chunk)
+        }
+    }
+}
+
+// Labels become #tags and dates due:/start: tokens, both read back on import.
+// A tag ends at whitespace, so spaces in label names turn into dashes.
+fn markdown_item(todo: &TodoEntity, depth: usize) -> String {
+    let mut item = format!(
+        "{}- [{}] {}",
+        "  ".repeat(depth),
+        if todo.completed { "x" } else { " " },
+        todo.text.replace(['\r', '\n'], " ")
+    );
+    for label in &todo.labels {
+        item.push_str(&format!(" #{}", label.name.split_whitespace().collect::<Vec<_>>().join("-")));
+    }
+    if let Some(due_at) = todo.due_at {
+        item.push_str(&format!(" due:{}", due_at.to_rfc3339_opts(SecondsFormat::Secs, true)));
+    }
+    if let Some(start_at) = todo.start_at {
+        item.push_str(&format!(" start:{}", start_at.to_rfc3339_opts(SecondsFormat::Secs, true)));
+    }
+    item.push('\n');
+    item
+}
+
+/// A todo read from an imported document, `parent` is the row of the todo it is a subtask of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedRow {
+    pub row: usize,
+    pub parent: Option<usize>,
+    pub record: Result<TodoRecord, FieldError>,
+}
+
+fn invalid_row(error: impl ToString) -> FieldError {
+    FieldError::new("__all__", "invalid_row", Some(error.to_string()))
+}
+
+/// Reads the rows of an imported document, a row that can't be read fails on its own.
+/// Only a document that isn't in `format` at all is an error.
+pub fn parse(format: TransferFormat, body: &str) -> anyhow::Result<Vec<ImportedRow>> {
+    match format {
+        TransferFormat::Json => {
+            let values: Vec<Value> = serde_json::from_str(body).context("expected a JSON array of todos")?;
+            let mut rows = vec![];
+            flatten_json(values, None, &mut rows);
+            Ok(rows)
+        }
+        TransferFormat::Csv => parse_csv(body),
+        TransferFormat::Markdown => Ok(parse_markdown(body)),
+    }
+}
+
+fn flatten_json(values: Vec<Value>, parent: Option<usize>, rows: &mut Vec<ImportedRow>) {
+    for mut value in values {
+        // subtasks are read one by one, so a broken one doesn't take its parent down
+        let subtasks = value.as_object_mut().and_then(|object| object.remove("subtasks"));
+        let row = rows.len() + 1;
+        let record = serde_json::from_value::<TodoRecord>(value).map_err(invalid_row);
+        rows.push(ImportedRow { row, parent, record });
+        match subtasks {
+            Some(Value::Array(subtasks)) => flatten_json(subtasks, Some(row), rows),
+            Some(Value::Null) | None => {}
+            Some(_) => rows.push(ImportedRow {
+                row: rows.len() + 1,
+                parent: Some(row),
+                record: Err(FieldError::new("subtasks", "invalid_type", Some("subtasks must be an array".to_string()))),
+            }),
+        }
+    }
+}
+
+fn parse_csv(body: &str) -> anyhow::Result<Vec<ImportedRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+    if !reader.headers()?.iter().any(|header| header == "text") {
+        bail!("the CSV header needs a text column");
+    }
+
+    let mut rows_by_id: HashMap<i32, usize> = HashMap::new();
+    let mut rows = vec![];
+    for (index, result) in reader.deserialize::<CsvRow>().enumerate() {
+        let row = index + 1;
+        let csv_row = match result {
+            Ok(csv_row) => csv_row,
+            Err(e) => {
+                rows.push(ImportedRow { row, parent: None, record: Err(invalid_row(e)) });
+                continue;
+            }
+        };
+        if let Some(id) = csv_row.id {
+            rows_by_id.insert(id, row);
+        }
+        let imported = match csv_row.parent_id.map(|parent_id| rows_by_id.get(&parent_id).copied().ok_or(parent_id)) {
+            Some(Err(parent_id)) => ImportedRow {
+                row,
+                parent: None,
+                record: Err(FieldError::new(
+                    "parent_id",
+                    "unknown_parent",
+                    Some(format!("no earlier row has the id {}", parent_id)),
+                )),
+            },
+            Some(Ok(parent)) => ImportedRow { row, parent: Some(parent), record: Ok(csv_row.into()) },
+            None => ImportedRow { row, parent: None, record: Ok(csv_row.into()) },
+        };
+        rows.push(imported);
+    }
+    Ok(rows)
+}
+
+// Anything but checklist items is skipped, indented items are subtasks of the item above.
+fn parse_markdown(body: &str) -> Vec<ImportedRow> {
+    let mut rows = vec![];
+    let mut last_root = None;
+    for (index, line) in body.lines().enumerate() {
+        let row = index + 1;
+        let item = line.trim_start();
+        let Some(rest) = item.strip_prefix("- [").or_else(|| item.strip_prefix("* [")) else {
+            continue;
+        };
+        let (completed, rest) = match (rest.get(..1), rest.get(1..)) {
+            (Some(" "), Some(rest)) => (false, rest),
+            (Some("x" | "X"), Some(rest)) => (true, rest),
+            _ => continue,
+        };
+        let Some(text) = rest.strip_prefix(']') else {
+            continue;
+        };
+
+        let parent = if item.len() < line.len() { last_root } else { None };
+        if parent.is_none() {
+            last_root = Some(row);
+        }
+        rows.push(ImportedRow { row, parent, record: markdown_record(completed, text) });
+    }
+    rows
+}
+
+fn markdown_record(completed: bool, text: &str) -> Result<TodoRecord, FieldError> {
+    let parse_date = |field: &str, value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|date| date.with_timezone(&Utc))
+            .map_err(|e| FieldError::new(field, "invalid_date", Some(e.to_string())))
+    };
+    let mut record = TodoRecord {
+        text: String::new(),
+        completed,
+        labels: vec![],
+        due_at: None,
+        start_at: None,
+        subtasks: vec![],
+    };
+    let mut words = vec![];
+    for word in text.split_whitespace() {
+        if let Some(label) = word.strip_prefix('#').filter(|label| !label.is_empty()) {
+            record.labels.push(label.to_string());
+        } else if let Some(due_at) = word.strip_prefix("due:") {
+            record.due_at = Some(parse_date("due_at", due_at)?);
+        } else if let Some(start_at) = word.strip_prefix("start:") {
+            record.start_at = Some(parse_date("start_at", start_at)?);
+        } else {
+            words.push(word);
+        }
+    }
+    record.text = words.join(" ");
+    Ok(record)
+}
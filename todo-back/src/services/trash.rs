@@ -0,0 +1,15 @@
+use chrono::{Duration, Utc};
+use crate::repositories::todo::TodoRepository;
+
+/// Deletes todos for good once they have been in the trash longer than `retention`, checking every `interval`.
+pub async fn purge_trash(repository: impl TodoRepository, retention: Duration, interval: std::time::Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match repository.purge_trashed(Utc::now() - retention).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("purged {} trashed todos", count),
+            Err(e) => tracing::error!("failed to purge trashed todos: {}", e),
+        }
+    }
+}
@@ -15,7 +15,7 @@ import type { RecommendedTodo } from '../types/todo'
 
 type Props = {
   onRecommend: () => Promise<RecommendedTodo[]>
-  onAddTodos: (texts: string[]) => void
+  onAddTodos: (acceptedIds: number[], rejectedIds: number[]) => void
 }
 
 export const RecommendButton: FC<Props> = ({ onRecommend, onAddTodos }) => {
@@ -53,11 +53,15 @@ export const RecommendButton: FC<Props> = ({ onRecommend, onAddTodos }) => {
   }
 
   const handleAdd = () => {
-    const texts = recommendations
+    // what was left unchecked is turned down, so it isn't suggested again
+    const acceptedIds = recommendations
       .filter((_, i) => selected.has(i))
-      .map((r) => r.text)
-    if (texts.length > 0) {
-      onAddTodos(texts)
+      .map((r) => r.id)
+    const rejectedIds = recommendations
+      .filter((_, i) => !selected.has(i))
+      .map((r) => r.id)
+    if (acceptedIds.length > 0) {
+      onAddTodos(acceptedIds, rejectedIds)
     }
     setOpen(false)
   }
@@ -94,7 +98,7 @@ export const RecommendButton: FC<Props> = ({ onRecommend, onAddTodos }) => {
             <Stack spacing={1}>
               {recommendations.map((rec, i) => (
                 <FormControlLabel
-                  key={i}
+                  key={rec.id}
                   control={
                     <Checkbox
                       checked={selected.has(i)}
@@ -1,9 +1,9 @@
-import type { Label, NewLabelPayload } from '../../types/label'
+import type { Label, NewLabelPayload, UpdateLabelPayload } from '../../types/label'
 
 const API_URL = import.meta.env.VITE_API_URL
 
-export const addLabelItem = async (token: string, payload: NewLabelPayload) => {
-  const res = await fetch(`${API_URL}/labels`, {
+export const addLabelItem = async (token: string, workspaceId: number, payload: NewLabelPayload) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/labels`, {
     method: 'POST',
     headers: {
       'Content-Type': 'application/json',
@@ -17,8 +17,8 @@ export const addLabelItem = async (token: string, payload: NewLabelPayload) => {
   return json
 }
 
-export const getLabelItems = async (token: string) => {
-  const res = await fetch(`${API_URL}/labels`, {
+export const getLabelItems = async (token: string, workspaceId: number) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/labels`, {
     headers: {
       Authorization: `Bearer ${token}`
     },
@@ -29,8 +29,45 @@ export const getLabelItems = async (token: string) => {
   return json
 }
 
-export const deleteLabelItem = async (token: string, id: number) => {
-  const res = await fetch(`${API_URL}/labels/${id}`, {
+const labelPath = (label: Label) =>
+  label.workspace_id === null
+    ? `/labels/${label.id}`
+    : `/workspaces/${label.workspace_id}/labels/${label.id}`
+
+export const updateLabelItem = async (token: string, label: Label, payload: UpdateLabelPayload) => {
+  const res = await fetch(`${API_URL}${labelPath(label)}`, {
+    method: 'PATCH',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify(payload),
+  })
+  if (!res.ok)
+    throw new Error('update label request failed')
+  const json: Label = await res.json()
+  return json
+}
+
+// todos tagged with `label` get `into` instead, `label` is deleted
+export const mergeLabelItem = async (token: string, label: Label, into: number) => {
+  const res = await fetch(`${API_URL}${labelPath(label)}/merge`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify({ into }),
+  })
+  if (!res.ok)
+    throw new Error('merge label request failed')
+  const json: Label = await res.json()
+  return json
+}
+
+// without `detach` a label still on todos is not deleted
+export const deleteLabelItem = async (token: string, label: Label, detach = false) => {
+  const res = await fetch(`${API_URL}${labelPath(label)}?detach=${detach}`, {
     method: 'DELETE',
     headers: {
       Authorization: `Bearer ${token}`
@@ -0,0 +1,70 @@
+import type { BoardColumn, NewStatusPayload, Status, UpdateStatusPayload } from '../../types/status'
+
+const API_URL = import.meta.env.VITE_API_URL
+
+export const getStatusItems = async (token: string, workspaceId: number) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/statuses`, {
+    headers: {
+      Authorization: `Bearer ${token}`
+    },
+  })
+  if (!res.ok)
+    throw new Error('get status request failed')
+  const json: Status[] = await res.json()
+  return json
+}
+
+export const addStatusItem = async (token: string, workspaceId: number, payload: NewStatusPayload) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/statuses`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify(payload),
+  })
+  if (!res.ok)
+    throw new Error('add status request failed')
+  const json: Status = await res.json()
+  return json
+}
+
+export const updateStatusItem = async (token: string, workspaceId: number, id: number, payload: UpdateStatusPayload) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/statuses/${id}`, {
+    method: 'PATCH',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify(payload),
+  })
+  if (!res.ok)
+    throw new Error('update status request failed')
+  const json: Status = await res.json()
+  return json
+}
+
+// todos still in the column go to `moveTo`, without it such a column is not deleted
+export const deleteStatusItem = async (token: string, workspaceId: number, id: number, moveTo?: number) => {
+  const query = moveTo === undefined ? '' : `?move_to=${moveTo}`
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/statuses/${id}${query}`, {
+    method: 'DELETE',
+    headers: {
+      Authorization: `Bearer ${token}`
+    },
+  })
+  if (!res.ok)
+    throw new Error('delete status request failed')
+}
+
+export const getBoard = async (token: string, workspaceId: number) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/board`, {
+    headers: {
+      Authorization: `Bearer ${token}`
+    },
+  })
+  if (!res.ok)
+    throw new Error('get board request failed')
+  const json: BoardColumn[] = await res.json()
+  return json
+}
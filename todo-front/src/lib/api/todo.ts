@@ -1,4 +1,4 @@
-import type { NewTodoPayload, RecommendedTodo, Todo, UpdateTodoPayload } from '../../types/todo'
+import type { MoveTodoPayload, NewTodoPayload, QuickAddPreview, RecommendedTodo, Todo, TodoPage, UpdateTodoPayload } from '../../types/todo'
 
 const API_URL = import.meta.env.VITE_API_URL
 
@@ -18,19 +18,88 @@ export const addTodoItem = async (token: string, workspaceId: number, payload: N
   return json
 }
 
-export const getTodoItems = async (token: string, workspaceId: number) => {
-  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos`, {
+// `#labels`, `!priority`, due dates and recurrences written in the text are read by the server
+export const quickAddTodoItem = async (token: string, workspaceId: number, payload: NewTodoPayload) => {
+  const params = new URLSearchParams({ parse: 'true', tz_offset: String(-new Date().getTimezoneOffset()) })
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos?${params}`, {
+    method: 'POST',
     headers: {
+      'Content-Type': 'application/json',
       Authorization: `Bearer ${token}`,
     },
+    body: JSON.stringify(payload),
   })
   if (!res.ok) {
-    throw new Error('get todos request failed')
+    throw new Error('quick add todo request failed')
   }
-  const json: Todo[] = await res.json()
+  const json: Todo = await res.json()
   return json
 }
 
+export const previewQuickAdd = async (token: string, workspaceId: number, text: string): Promise<QuickAddPreview> => {
+  const params = new URLSearchParams({ tz_offset: String(-new Date().getTimezoneOffset()) })
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos/parse?${params}`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify({ text }),
+  })
+  if (!res.ok) {
+    throw new Error('preview quick add request failed')
+  }
+  const json: QuickAddPreview = await res.json()
+  return json
+}
+
+export const getTodoItems = async (token: string, workspaceId: number) => {
+  const todos: Todo[] = []
+  let cursor: string | null = null
+  do {
+    const params = new URLSearchParams({ limit: '200' })
+    if (cursor) {
+      params.set('cursor', cursor)
+    }
+    const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos?${params}`, {
+      headers: {
+        Authorization: `Bearer ${token}`,
+      },
+    })
+    if (!res.ok) {
+      throw new Error('get todos request failed')
+    }
+    const json: TodoPage = await res.json()
+    todos.push(...json.items)
+    cursor = json.next_cursor
+  } while (cursor)
+  return todos
+}
+
+// todos assigned to the signed in user, from every workspace
+export const getAssignedTodoItems = async (token: string) => {
+  const todos: Todo[] = []
+  let cursor: string | null = null
+  do {
+    const params = new URLSearchParams({ limit: '200' })
+    if (cursor) {
+      params.set('cursor', cursor)
+    }
+    const res = await fetch(`${API_URL}/users/me/assigned?${params}`, {
+      headers: {
+        Authorization: `Bearer ${token}`,
+      },
+    })
+    if (!res.ok) {
+      throw new Error('get assigned todos request failed')
+    }
+    const json: TodoPage = await res.json()
+    todos.push(...json.items)
+    cursor = json.next_cursor
+  } while (cursor)
+  return todos
+}
+
 export const updateTodoItem = async (token: string, workspaceId: number, payload: UpdateTodoPayload) => {
   const { id, ...updateTodo } = payload
   const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos/${id}`, {
@@ -48,6 +117,22 @@ export const updateTodoItem = async (token: string, workspaceId: number, payload
   return json
 }
 
+export const moveTodoItem = async (token: string, workspaceId: number, id: number, payload: MoveTodoPayload) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos/${id}/move`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify(payload),
+  })
+  if (!res.ok) {
+    throw new Error('move todo request failed')
+  }
+  const json: Todo = await res.json()
+  return json
+}
+
 export const deleteTodoItem = async (token: string, workspaceId: number, id: number) => {
   const res = await fetch(`${API_URL}/workspaces/${workspaceId}/todos/${id}`, {
     method: 'DELETE',
@@ -73,3 +158,33 @@ export const getRecommendations = async (token: string, workspaceId: number): Pr
   const json: RecommendedTodo[] = await res.json()
   return json
 }
+
+export const acceptRecommendations = async (token: string, workspaceId: number, ids: number[]): Promise<Todo[]> => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/recommendations/accept`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify({ ids }),
+  })
+  if (!res.ok) {
+    throw new Error('accept recommendations request failed')
+  }
+  const json: Todo[] = await res.json()
+  return json
+}
+
+export const rejectRecommendations = async (token: string, workspaceId: number, ids: number[]) => {
+  const res = await fetch(`${API_URL}/workspaces/${workspaceId}/recommendations/reject`, {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+      Authorization: `Bearer ${token}`,
+    },
+    body: JSON.stringify({ ids }),
+  })
+  if (!res.ok) {
+    throw new Error('reject recommendations request failed')
+  }
+}
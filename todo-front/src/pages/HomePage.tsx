@@ -23,6 +23,8 @@ import {
   updateTodoItem,
   deleteTodoItem,
   getRecommendations,
+  acceptRecommendations,
+  rejectRecommendations,
 } from '../lib/api/todo'
 import {
   addUserItem,
@@ -67,11 +69,7 @@ export const HomePage: FC = () => {
 
   const initWorkspaces = async () => {
     const token = await getAccessTokenSilently()
-    const [labels, fetchedWorkspaces] = await Promise.all([
-      getLabelItems(token),
-      getWorkspaces(token).catch(() => [] as Workspace[]),
-    ])
-    setLabels(labels)
+    const fetchedWorkspaces = await getWorkspaces(token).catch(() => [] as Workspace[])
     setWorkspaces(fetchedWorkspaces)
 
     if (fetchedWorkspaces.length === 0) {
@@ -81,8 +79,12 @@ export const HomePage: FC = () => {
       const personalWs = fetchedWorkspaces.find((w) => w.is_personal)
       const defaultWs = personalWs ?? fetchedWorkspaces[0]
       setWorkspaceId(defaultWs.id)
-      const todos = await getTodoItems(token, defaultWs.id)
+      const [todos, labels] = await Promise.all([
+        getTodoItems(token, defaultWs.id),
+        getLabelItems(token, defaultWs.id),
+      ])
       setTodos(todos)
+      setLabels(labels)
     }
   }
 
@@ -128,12 +130,12 @@ export const HomePage: FC = () => {
     return await getRecommendations(token, workspaceId)
   }
 
-  const onAddRecommendedTodos = async (texts: string[]) => {
+  const onAddRecommendedTodos = async (acceptedIds: number[], rejectedIds: number[]) => {
     if (workspaceId === null) return
     const token = await getToken()
-    for (const text of texts) {
-      const payload = { text, label_ids: [] }
-      await addTodoItem(token, workspaceId, payload)
+    await acceptRecommendations(token, workspaceId, acceptedIds)
+    if (rejectedIds.length > 0) {
+      await rejectRecommendations(token, workspaceId, rejectedIds)
     }
     const todos = await getTodoItems(token, workspaceId)
     setTodos(todos)
@@ -152,8 +154,12 @@ export const HomePage: FC = () => {
     setWorkspaceId(wsId)
     setFilterLabelId(null)
     const token = await getToken()
-    const todos = await getTodoItems(token, wsId)
+    const [todos, labels] = await Promise.all([
+      getTodoItems(token, wsId),
+      getLabelItems(token, wsId),
+    ])
     setTodos(todos)
+    setLabels(labels)
   }
 
   const onSubmitNewWorkspace = async (payload: NewWorkspacePayload) => {
@@ -169,16 +175,19 @@ export const HomePage: FC = () => {
   }
 
   const onSubmitNewLabel = async (newLabel: NewLabelPayload) => {
+    if (workspaceId === null) return
     const token = await getToken()
-    await addLabelItem(token, newLabel)
-    const labels = await getLabelItems(token)
+    await addLabelItem(token, workspaceId, newLabel)
+    const labels = await getLabelItems(token, workspaceId)
     setLabels(labels)
   }
 
   const onDeleteLabel = async (id: number) => {
+    const label = labels.find((label) => label.id === id)
+    if (workspaceId === null || !label) return
     const token = await getToken()
-    await deleteLabelItem(token, id)
-    const labels = await getLabelItems(token)
+    await deleteLabelItem(token, label, true)
+    const labels = await getLabelItems(token, workspaceId)
     setLabels(labels)
   }
 
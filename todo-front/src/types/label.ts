@@ -2,8 +2,21 @@ export type Label = {
   id: number
   name: string
   user_id: number
+  // shared with the workspace, null for a label of the user's own
+  workspace_id: number | null
+  color: string | null
+  icon: string | null
 }
 
 export type NewLabelPayload = {
   name: string
+  color?: string | null
+  icon?: string | null
+}
+
+// omitted fields stay as they are, null clears color or icon
+export type UpdateLabelPayload = {
+  name?: string
+  color?: string | null
+  icon?: string | null
 }
\ No newline at end of file
@@ -0,0 +1,31 @@
+import type { TodoPage } from './todo'
+
+export type Status = {
+  id: number
+  workspace_id: number
+  name: string
+  position: number
+  // null when the column takes any number of todos
+  wip_limit: number | null
+  // todos in a terminal column count as completed
+  is_terminal: boolean
+}
+
+export type NewStatusPayload = {
+  name: string
+  wip_limit?: number | null
+  is_terminal?: boolean
+}
+
+// omitted fields stay as they are, null clears the WIP limit
+export type UpdateStatusPayload = {
+  name?: string
+  wip_limit?: number | null
+  is_terminal?: boolean
+  position?: number
+}
+
+export type BoardColumn = {
+  status: Status
+  todos: TodoPage
+}
@@ -6,16 +6,78 @@ export type Todo = {
   completed: boolean
   labels: Label[]
   user_id: number
+  // members of the workspace responsible for the todo
+  assignee_ids: number[]
   workspace_id: number
+  due_at: string | null
+  start_at: string | null
+  parent_id: number | null
+  auto_complete: boolean
+  children: Todo[]
+  progress: TodoProgress
+  deleted_at: string | null
+  recurrence: Recurrence | null
+  series_id: number | null
+  priority: Priority | null
+  position: string
+  status_id: number
+}
+
+export type Priority = 'low' | 'medium' | 'high'
+
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun'
+
+export type Recurrence = {
+  frequency: 'daily' | 'weekly' | 'monthly'
+  interval?: number
+  weekdays?: Weekday[]
+  month_day?: number | null
+  until?: string | null
+  tz_offset?: number
+}
+
+export type TodoProgress = {
+  completed: number
+  total: number
+}
+
+export type TodoPage = {
+  items: Todo[]
+  next_cursor: string | null
+  total: number
 }
 
 export type NewTodoPayload = {
   text: string
   label_ids: number[]
+  assignee_ids?: number[]
+  due_at?: string | null
+  start_at?: string | null
+  parent_id?: number | null
+  auto_complete?: boolean
+  recurrence?: Recurrence | null
+  priority?: Priority | null
+  status_id?: number
 }
 
+export type QuickAddPreview = {
+  text: string
+  due_at: string | null
+  labels: { name: string; id: number | null }[]
+  priority: Priority | null
+  recurrence: Recurrence | null
+}
+
+export type RecommendationStatus = 'pending' | 'accepted' | 'rejected'
+
 export type RecommendedTodo = {
+  id: number
+  workspace_id: number
+  user_id: number
   text: string
+  status: RecommendationStatus
+  todo_id: number | null
+  expires_at: string
 }
 
 export type UpdateTodoPayload = {
@@ -23,4 +85,16 @@ export type UpdateTodoPayload = {
   text?: string
   completed?: boolean
   label_ids?: number[]
+  // replaces the assignees, an empty list unassigns everybody
+  assignee_ids?: number[]
+  due_at?: string | null
+  start_at?: string | null
+  auto_complete?: boolean
+  recurrence?: Recurrence | null
+  priority?: Priority | null
+  // moving to another column also sets completed by whether it is terminal
+  status_id?: number
 }
+
+// exactly one of them, the todo ends up right before or right after that sibling
+export type MoveTodoPayload = { before: number; after?: undefined } | { before?: undefined; after: number }
@@ -3,6 +3,8 @@ export type User = {
   sub: string | null
   name: string
   email: string | null
+  // only a verified email receives invitations and can be added to workspaces
+  email_verified: boolean
 }
 
 export type NewUserPayload = {
@@ -1,10 +1,17 @@
 import type { User } from './user'
 
+export type WorkspaceRole = 'owner' | 'admin' | 'editor' | 'viewer'
+
+export type WorkspaceMember = User & {
+  role: WorkspaceRole
+}
+
 export type Workspace = {
   id: number
   name: string
   is_personal: boolean
-  users: User[]
+  owner_id: number | null
+  users: WorkspaceMember[]
 }
 
 export type NewWorkspacePayload = {